@@ -0,0 +1,310 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+/// Largest magnitude of the exponent accepted by `BigFixed::parse`, far beyond any
+/// usable coordinate but small enough to keep the power of ten cheap to compute.
+const MAX_EXPONENT: i64 = 10_000;
+
+/// Signed arbitrary-precision fixed-point number.
+///
+/// The value is `mantissa / 2^frac_bits`. Arithmetic between numbers of different
+/// precision is carried out at the higher of the two precisions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BigFixed {
+    mantissa: BigInt,
+    frac_bits: u32,
+}
+
+impl BigFixed {
+    /// Creates zero with the given number of fractional bits.
+    pub fn zero(frac_bits: u32) -> BigFixed {
+        BigFixed { mantissa: BigInt::zero(), frac_bits }
+    }
+
+    /// Converts an `f64` exactly (down to the given precision).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to convert; must be finite.
+    /// * `frac_bits` - Number of fractional bits of the result.
+    pub fn from_f64(value: f64, frac_bits: u32) -> BigFixed {
+        assert!(value.is_finite(), "Cannot convert {} to a fixed-point number", value);
+        if value == 0.0 {
+            return BigFixed::zero(frac_bits);
+        }
+
+        // Decompose value into integer mantissa * 2^exponent
+        let bits = value.to_bits();
+        let raw_exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & 0x000f_ffff_ffff_ffff;
+        let (mantissa, exponent) = if raw_exponent == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | 0x0010_0000_0000_0000, raw_exponent - 1075)
+        };
+
+        let mut mantissa = BigInt::from(mantissa);
+        let shift = exponent + frac_bits as i64;
+        if shift >= 0 {
+            mantissa <<= shift as usize;
+        } else {
+            mantissa >>= (-shift) as usize;
+        }
+        if value < 0.0 {
+            mantissa = -mantissa;
+        }
+        BigFixed { mantissa, frac_bits }
+    }
+
+    /// Parses a decimal number such as `-0.7436438870371587047521915` or `1.25e-30`.
+    ///
+    /// Digits beyond the given precision are truncated.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The decimal representation.
+    /// * `frac_bits` - Number of fractional bits of the result.
+    ///
+    /// # Returns
+    ///
+    /// The parsed number, or an error message describing the invalid input.
+    pub fn parse(text: &str, frac_bits: u32) -> Result<BigFixed, String> {
+        let trimmed = text.trim();
+        let (negative, unsigned) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let (number, exponent) = match unsigned.find(['e', 'E']) {
+            Some(pos) => {
+                let exponent = unsigned[pos + 1..]
+                    .parse::<i64>()
+                    .map_err(|_| format!("invalid exponent in '{}'", text))?;
+                if exponent.abs() > MAX_EXPONENT {
+                    return Err(format!("exponent out of range in '{}' (at most {} in magnitude)", text, MAX_EXPONENT));
+                }
+                (&unsigned[..pos], exponent)
+            }
+            None => (unsigned, 0),
+        };
+        let (integer_part, fraction_part) = match number.split_once('.') {
+            Some((integer, fraction)) => (integer, fraction),
+            None => (number, ""),
+        };
+        if integer_part.is_empty() && fraction_part.is_empty() {
+            return Err(format!("'{}' is not a number", text));
+        }
+        let digits = format!("{}{}", integer_part, fraction_part);
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("'{}' is not a number", text));
+        }
+
+        // value = digits * 10^decimal_exponent
+        let digits: BigInt = digits.parse().map_err(|_| format!("'{}' is not a number", text))?;
+        let decimal_exponent = exponent - fraction_part.len() as i64;
+        let power = u32::try_from(decimal_exponent.unsigned_abs()).map_err(|_| format!("exponent out of range in '{}'", text))?;
+        let scaled = digits << frac_bits as usize;
+        let mut mantissa = if decimal_exponent >= 0 {
+            scaled * BigInt::from(10).pow(power)
+        } else {
+            scaled / BigInt::from(10).pow(power)
+        };
+        if negative {
+            mantissa = -mantissa;
+        }
+        Ok(BigFixed { mantissa, frac_bits })
+    }
+
+    /// Number of fractional bits.
+    pub fn frac_bits(&self) -> u32 {
+        self.frac_bits
+    }
+
+    /// Returns the same value with a different number of fractional bits.
+    pub fn with_frac_bits(&self, frac_bits: u32) -> BigFixed {
+        let mantissa = if frac_bits >= self.frac_bits {
+            &self.mantissa << (frac_bits - self.frac_bits) as usize
+        } else {
+            &self.mantissa >> (self.frac_bits - frac_bits) as usize
+        };
+        BigFixed { mantissa, frac_bits }
+    }
+
+    /// Divides by a positive integer, truncating toward zero.
+    pub fn div_int(&self, divisor: u32) -> BigFixed {
+        assert!(divisor > 0, "Division by zero");
+        BigFixed { mantissa: &self.mantissa / BigInt::from(divisor), frac_bits: self.frac_bits }
+    }
+
+    /// Formats the value as a decimal string.
+    ///
+    /// The number of fractional digits matches the binary precision (about 0.3
+    /// digits per bit), rounded to nearest, with trailing zeros removed.
+    pub fn to_decimal_string(&self) -> String {
+        let digits = (self.frac_bits as f64 * std::f64::consts::LOG10_2).floor() as usize;
+        let scaled = self.mantissa.abs() * BigInt::from(10).pow(digits as u32);
+        let rounded = if self.frac_bits > 0 {
+            (scaled + (BigInt::from(1) << (self.frac_bits - 1) as usize)) >> self.frac_bits as usize
+        } else {
+            scaled
+        };
+
+        let text = format!("{:0>width$}", rounded.to_string(), width = digits + 1);
+        let (integer, fraction) = text.split_at(text.len() - digits);
+        let fraction = fraction.trim_end_matches('0');
+        let sign = if self.mantissa.is_negative() && !rounded.is_zero() { "-" } else { "" };
+        if fraction.is_empty() {
+            format!("{}{}", sign, integer)
+        } else {
+            format!("{}{}.{}", sign, integer, fraction)
+        }
+    }
+
+    /// Converts to the nearest `f64` (up to truncation of the low bits).
+    pub fn to_f64(&self) -> f64 {
+        let significant_bits = self.mantissa.bits();
+        if significant_bits == 0 {
+            return 0.0;
+        }
+        // Keep 64 significant bits so the conversion never overflows
+        let excess = significant_bits.saturating_sub(64);
+        let top = (self.mantissa.abs() >> excess as usize).to_f64().unwrap_or(0.0);
+        // Scale in two steps so tiny values don't underflow in the intermediate power
+        let exponent = (excess as i64 - self.frac_bits as i64).max(i32::MIN as i64 / 2) as i32;
+        let value = top * 2f64.powi(exponent / 2) * 2f64.powi(exponent - exponent / 2);
+        if self.mantissa.is_negative() { -value } else { value }
+    }
+
+    /// Aligns two numbers to the higher precision.
+    fn aligned(&self, other: &BigFixed) -> (BigInt, BigInt, u32) {
+        let frac_bits = self.frac_bits.max(other.frac_bits);
+        (
+            self.with_frac_bits(frac_bits).mantissa,
+            other.with_frac_bits(frac_bits).mantissa,
+            frac_bits,
+        )
+    }
+}
+
+impl Add for &BigFixed {
+    type Output = BigFixed;
+
+    fn add(self, other: &BigFixed) -> BigFixed {
+        let (a, b, frac_bits) = self.aligned(other);
+        BigFixed { mantissa: a + b, frac_bits }
+    }
+}
+
+impl Sub for &BigFixed {
+    type Output = BigFixed;
+
+    fn sub(self, other: &BigFixed) -> BigFixed {
+        let (a, b, frac_bits) = self.aligned(other);
+        BigFixed { mantissa: a - b, frac_bits }
+    }
+}
+
+impl Mul for &BigFixed {
+    type Output = BigFixed;
+
+    fn mul(self, other: &BigFixed) -> BigFixed {
+        let (a, b, frac_bits) = self.aligned(other);
+        BigFixed { mantissa: (a * b) >> frac_bits as usize, frac_bits }
+    }
+}
+
+impl Div for &BigFixed {
+    type Output = BigFixed;
+
+    fn div(self, other: &BigFixed) -> BigFixed {
+        let (a, b, frac_bits) = self.aligned(other);
+        assert!(!b.is_zero(), "Division by zero");
+        BigFixed { mantissa: (a << frac_bits as usize) / b, frac_bits }
+    }
+}
+
+impl Neg for &BigFixed {
+    type Output = BigFixed;
+
+    fn neg(self) -> BigFixed {
+        BigFixed { mantissa: -&self.mantissa, frac_bits: self.frac_bits }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_f64_roundtrip() {
+        for value in [0.0, 1.0, -1.0, 0.5, -0.743643887037158, 3.0e-20, -1234.5678] {
+            assert_eq!(BigFixed::from_f64(value, 128).to_f64(), value);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(BigFixed::parse("1.5", 64).unwrap().to_f64(), 1.5);
+        assert_eq!(BigFixed::parse("-0.25", 64).unwrap().to_f64(), -0.25);
+        assert_eq!(BigFixed::parse("+2", 64).unwrap().to_f64(), 2.0);
+        assert_eq!(BigFixed::parse("125e-3", 64).unwrap().to_f64(), 0.125);
+        assert_eq!(BigFixed::parse(".5E1", 64).unwrap().to_f64(), 5.0);
+        assert!((BigFixed::parse("1e-30", 200).unwrap().to_f64() - 1e-30).abs() < 1e-45);
+
+        assert!(BigFixed::parse("", 64).is_err());
+        assert!(BigFixed::parse("1.2.3", 64).is_err());
+        assert!(BigFixed::parse("abc", 64).is_err());
+        assert!(BigFixed::parse("1e", 64).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_exponents_out_of_range() {
+        // Would wrap to 10^0 if the exponent were truncated to 32 bits
+        assert!(BigFixed::parse("1e4294967296", 64).is_err());
+        // Would build a billion-digit power of ten
+        assert!(BigFixed::parse("1e999999999", 64).is_err());
+        assert!(BigFixed::parse("1e-999999999", 64).is_err());
+        assert!(BigFixed::parse("1e10001", 64).is_err());
+
+        assert_eq!(BigFixed::parse("1e10000", 0).unwrap(), BigFixed::parse(&format!("1{}", "0".repeat(10000)), 0).unwrap());
+        assert_eq!(BigFixed::parse("1e-10000", 64).unwrap().to_f64(), 0.0);
+    }
+
+    #[test]
+    fn test_parse_keeps_digits_beyond_f64() {
+        let a = BigFixed::parse("0.10000000000000000000000000001", 200).unwrap();
+        let b = BigFixed::parse("0.1", 200).unwrap();
+        let difference = (&a - &b).to_f64();
+        assert!((difference - 1e-29).abs() < 1e-40);
+    }
+
+    #[test]
+    fn test_to_decimal_string() {
+        assert_eq!(BigFixed::parse("0.3", 64).unwrap().to_decimal_string(), "0.3");
+        assert_eq!(BigFixed::parse("-12.5", 64).unwrap().to_decimal_string(), "-12.5");
+        assert_eq!(BigFixed::zero(64).to_decimal_string(), "0");
+        assert_eq!(BigFixed::from_f64(7.0, 0).to_decimal_string(), "7");
+
+        let text = "-0.743643887037158704752191506114774";
+        assert_eq!(BigFixed::parse(text, 160).unwrap().to_decimal_string(), text);
+    }
+
+    #[test]
+    fn test_div_int() {
+        assert_eq!(BigFixed::from_f64(10.0, 64).div_int(4).to_f64(), 2.5);
+        assert_eq!(BigFixed::from_f64(-3.0, 64).div_int(2).to_f64(), -1.5);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = BigFixed::from_f64(1.5, 96);
+        let b = BigFixed::from_f64(-0.25, 64);
+        assert_eq!((&a + &b).to_f64(), 1.25);
+        assert_eq!((&a - &b).to_f64(), 1.75);
+        assert_eq!((&a * &b).to_f64(), -0.375);
+        assert_eq!((&a / &b).to_f64(), -6.0);
+        assert_eq!((-&a).to_f64(), -1.5);
+        assert_eq!((&a + &b).frac_bits(), 96);
+    }
+}
@@ -2,6 +2,10 @@ use std::path::Path;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::decimal::{ComplexDecimal, Decimal};
+use crate::generate_flame::FlameTransform;
+use crate::generate_ifs::IfsMap;
+
 /// Configuration structure for saving/loading parameters.
 #[derive(Serialize, Deserialize)]
 pub struct Config {
@@ -10,14 +14,14 @@ pub struct Config {
     pub max_iterations: u32,
     pub output_path: String,
     pub bands: u32,
-    pub center_x: f64,
-    pub center_y: f64,
-    pub zoom: f64,
+    pub center_x: Decimal,
+    pub center_y: Decimal,
+    pub zoom: Decimal,
     pub m_size: f64,
     pub grid_input: Option<String>,
-    pub end_center_x: Option<f64>,
-    pub end_center_y: Option<f64>,
-    pub end_zoom: Option<f64>,
+    pub end_center_x: Option<Decimal>,
+    pub end_center_y: Option<Decimal>,
+    pub end_zoom: Option<Decimal>,
     pub fps: f64,
     pub duration: f64,
     pub frames_dir: String,
@@ -26,10 +30,361 @@ pub struct Config {
     pub zoom_text_y: i32,
     pub zoom_font_size: f32,
     pub function: String,
+    #[serde(default)]
+    pub threads: usize,
+    #[serde(default = "default_color_mode")]
+    pub color_mode: String,
+    #[serde(default = "default_interior")]
+    pub interior: String,
+    #[serde(default = "default_bailout")]
+    pub bailout: f64,
+    #[serde(default = "default_julia_re")]
+    pub julia_re: f64,
+    #[serde(default = "default_julia_im")]
+    pub julia_im: f64,
+    pub end_julia_re: Option<f64>,
+    pub end_julia_im: Option<f64>,
+    #[serde(default = "default_julia_path")]
+    pub julia_path: String,
+    #[serde(default = "default_engine")]
+    pub engine: String,
+    pub center: Option<ComplexDecimal>,
+    pub scale: Option<f64>,
+    pub end_center: Option<ComplexDecimal>,
+    pub end_scale: Option<f64>,
+    #[serde(default = "default_fractal")]
+    pub fractal: String,
+    #[serde(default = "default_power")]
+    pub power: f64,
+    #[serde(default = "default_formula")]
+    pub formula: String,
+    #[serde(default = "default_formula_seed")]
+    pub formula_seed: String,
+    #[serde(default = "default_trap")]
+    pub trap: String,
+    #[serde(default)]
+    pub trap_x: f64,
+    #[serde(default)]
+    pub trap_y: f64,
+    #[serde(default = "default_trap_radius")]
+    pub trap_radius: f64,
+    #[serde(default)]
+    pub trap_angle: f64,
+    #[serde(default = "default_trap_width")]
+    pub trap_width: f64,
+    pub trap_image: Option<String>,
+    pub end_trap_x: Option<f64>,
+    pub end_trap_y: Option<f64>,
+    pub end_trap_radius: Option<f64>,
+    pub end_trap_angle: Option<f64>,
+    #[serde(default = "default_stripe_density")]
+    pub stripe_density: f64,
+    #[serde(default = "default_samples")]
+    pub samples: u64,
+    #[serde(default)]
+    pub seed: u64,
+    pub red_iterations: Option<u32>,
+    pub green_iterations: Option<u32>,
+    pub blue_iterations: Option<u32>,
+    #[serde(default)]
+    pub anti_buddhabrot: bool,
+    #[serde(default = "default_sampling")]
+    pub sampling: String,
+    #[serde(default = "default_newton_coefficients")]
+    pub newton_coefficients: String,
+    pub newton_roots: Option<String>,
+    #[serde(default = "default_newton_relaxation")]
+    pub newton_relaxation: f64,
+    #[serde(default = "default_newton_variant")]
+    pub newton_variant: String,
+    #[serde(default = "default_lyapunov_sequence")]
+    pub lyapunov_sequence: String,
+    #[serde(default = "default_bifurcation_map")]
+    pub bifurcation_map: String,
+    #[serde(default = "default_bifurcation_transient")]
+    pub bifurcation_transient: u32,
+    #[serde(default = "default_bifurcation_samples")]
+    pub bifurcation_samples: u32,
+    #[serde(default = "default_attractor")]
+    pub attractor: String,
+    pub attractor_params: Option<String>,
+    pub end_attractor_params: Option<String>,
+    #[serde(default = "default_flame_gamma")]
+    pub flame_gamma: f64,
+    #[serde(default = "default_flame_vibrancy")]
+    pub flame_vibrancy: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flame_transforms: Vec<FlameTransform>,
+    #[serde(default = "default_ifs")]
+    pub ifs: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ifs_maps: Vec<IfsMap>,
+    #[serde(default = "default_chaos_vertices")]
+    pub chaos_vertices: u32,
+    #[serde(default = "default_chaos_ratio")]
+    pub chaos_ratio: f64,
+    #[serde(default = "default_chaos_rule")]
+    pub chaos_rule: String,
+    #[serde(default)]
+    pub packet_x: f64,
+    #[serde(default)]
+    pub packet_y: f64,
+    #[serde(default = "default_packet_sigma")]
+    pub packet_sigma: f64,
+    #[serde(default)]
+    pub packet_kx: f64,
+    #[serde(default)]
+    pub packet_ky: f64,
+    #[serde(default = "default_schrodinger_grid")]
+    pub schrodinger_grid: u32,
+    #[serde(default)]
+    pub schrodinger_time: f64,
+    pub end_schrodinger_time: Option<f64>,
+    #[serde(default = "default_schrodinger_dt")]
+    pub schrodinger_dt: f64,
+    #[serde(default = "default_potential_height")]
+    pub potential_height: f64,
+    #[serde(default = "default_potential_width")]
+    pub potential_width: f64,
+    #[serde(default = "default_well_width")]
+    pub well_width: f64,
+    #[serde(default = "default_barrier_gap")]
+    pub barrier_gap: f64,
+    #[serde(default = "default_slit_separation")]
+    pub slit_separation: f64,
+    #[serde(default = "default_slit_width")]
+    pub slit_width: f64,
+    #[serde(default = "default_harmonic_omega")]
+    pub harmonic_omega: f64,
+    #[serde(default = "default_potential")]
+    pub potential: String,
+    #[serde(default = "default_potential_formula")]
+    pub potential_formula: String,
+    #[serde(default = "default_wave_display")]
+    pub wave_display: String,
+    #[serde(default = "default_schrodinger_mode")]
+    pub schrodinger_mode: String,
+    #[serde(default = "default_eigenstates")]
+    pub eigenstates: u32,
+    #[serde(default = "default_eigenstate_dimensions")]
+    pub eigenstate_dimensions: u32,
+    #[serde(default = "default_orbital_n")]
+    pub orbital_n: u32,
+    #[serde(default = "default_orbital_l")]
+    pub orbital_l: u32,
+    #[serde(default)]
+    pub orbital_m: i32,
+    pub end_orbital_m: Option<i32>,
+    #[serde(default = "default_slice_tilt")]
+    pub slice_tilt: f64,
+    #[serde(default)]
+    pub slice_azimuth: f64,
+    pub end_slice_tilt: Option<f64>,
+    pub end_slice_azimuth: Option<f64>,
     #[serde(flatten)]
     pub extra: std::collections::HashMap<String, toml::Value>,
 }
 
+fn default_color_mode() -> String {
+    "bands".to_string()
+}
+
+fn default_interior() -> String {
+    "solid".to_string()
+}
+
+fn default_bailout() -> f64 {
+    2.0
+}
+
+fn default_julia_re() -> f64 {
+    -0.8
+}
+
+fn default_julia_im() -> f64 {
+    0.156
+}
+
+fn default_julia_path() -> String {
+    "line".to_string()
+}
+
+fn default_engine() -> String {
+    "direct".to_string()
+}
+
+fn default_fractal() -> String {
+    "mandelbrot".to_string()
+}
+
+fn default_power() -> f64 {
+    2.0
+}
+
+fn default_formula() -> String {
+    "z^2 + c".to_string()
+}
+
+fn default_formula_seed() -> String {
+    "mandelbrot".to_string()
+}
+
+fn default_trap() -> String {
+    "point".to_string()
+}
+
+fn default_trap_radius() -> f64 {
+    1.0
+}
+
+fn default_trap_width() -> f64 {
+    0.1
+}
+
+fn default_stripe_density() -> f64 {
+    5.0
+}
+
+fn default_samples() -> u64 {
+    1000000
+}
+
+fn default_sampling() -> String {
+    "uniform".to_string()
+}
+
+fn default_newton_coefficients() -> String {
+    "1; 0; 0; -1".to_string()
+}
+
+fn default_newton_relaxation() -> f64 {
+    1.0
+}
+
+fn default_newton_variant() -> String {
+    "newton".to_string()
+}
+
+fn default_lyapunov_sequence() -> String {
+    "AB".to_string()
+}
+
+fn default_bifurcation_map() -> String {
+    "logistic".to_string()
+}
+
+fn default_bifurcation_transient() -> u32 {
+    1000
+}
+
+fn default_bifurcation_samples() -> u32 {
+    10000
+}
+
+fn default_attractor() -> String {
+    "clifford".to_string()
+}
+
+fn default_flame_gamma() -> f64 {
+    4.0
+}
+
+fn default_flame_vibrancy() -> f64 {
+    1.0
+}
+
+fn default_ifs() -> String {
+    "fern".to_string()
+}
+
+fn default_chaos_vertices() -> u32 {
+    3
+}
+
+fn default_chaos_ratio() -> f64 {
+    0.5
+}
+
+fn default_chaos_rule() -> String {
+    "any".to_string()
+}
+
+fn default_packet_sigma() -> f64 {
+    0.5
+}
+
+fn default_schrodinger_grid() -> u32 {
+    256
+}
+
+fn default_schrodinger_dt() -> f64 {
+    0.005
+}
+
+fn default_potential() -> String {
+    "free".to_string()
+}
+
+fn default_potential_height() -> f64 {
+    20.0
+}
+
+fn default_potential_width() -> f64 {
+    0.2
+}
+
+fn default_well_width() -> f64 {
+    4.0
+}
+
+fn default_barrier_gap() -> f64 {
+    1.0
+}
+
+fn default_slit_separation() -> f64 {
+    1.0
+}
+
+fn default_slit_width() -> f64 {
+    0.3
+}
+
+fn default_harmonic_omega() -> f64 {
+    1.0
+}
+
+fn default_potential_formula() -> String {
+    "0.5 * (x^2 + y^2)".to_string()
+}
+
+fn default_wave_display() -> String {
+    "density".to_string()
+}
+
+fn default_schrodinger_mode() -> String {
+    "evolve".to_string()
+}
+
+fn default_eigenstates() -> u32 {
+    6
+}
+
+fn default_eigenstate_dimensions() -> u32 {
+    2
+}
+
+fn default_orbital_n() -> u32 {
+    2
+}
+
+fn default_orbital_l() -> u32 {
+    1
+}
+
+fn default_slice_tilt() -> f64 {
+    90.0
+}
+
 pub fn load_config(args: &mut crate::parameters::Args, config_path: Option<String>) {
     if let Some(config_path) = config_path {
         match std::fs::read_to_string(&config_path) {
@@ -58,11 +413,97 @@ pub fn load_config(args: &mut crate::parameters::Args, config_path: Option<Strin
                         args.zoom_text_y = config.zoom_text_y;
                         args.zoom_font_size = config.zoom_font_size;
                         args.function = config.function;
+                        args.threads = config.threads;
+                        args.color_mode = config.color_mode;
+                        args.interior = config.interior;
+                        args.bailout = config.bailout;
+                        args.julia_re = config.julia_re;
+                        args.julia_im = config.julia_im;
+                        args.end_julia_re = config.end_julia_re;
+                        args.end_julia_im = config.end_julia_im;
+                        args.julia_path = config.julia_path;
+                        args.engine = config.engine;
+                        args.center = config.center;
+                        args.scale = config.scale;
+                        args.end_center = config.end_center;
+                        args.end_scale = config.end_scale;
+                        args.fractal = config.fractal;
+                        args.power = config.power;
+                        args.formula = config.formula;
+                        args.formula_seed = config.formula_seed;
+                        args.trap = config.trap;
+                        args.trap_x = config.trap_x;
+                        args.trap_y = config.trap_y;
+                        args.trap_radius = config.trap_radius;
+                        args.trap_angle = config.trap_angle;
+                        args.trap_width = config.trap_width;
+                        args.trap_image = config.trap_image;
+                        args.end_trap_x = config.end_trap_x;
+                        args.end_trap_y = config.end_trap_y;
+                        args.end_trap_radius = config.end_trap_radius;
+                        args.end_trap_angle = config.end_trap_angle;
+                        args.stripe_density = config.stripe_density;
+                        args.samples = config.samples;
+                        args.seed = config.seed;
+                        args.red_iterations = config.red_iterations;
+                        args.green_iterations = config.green_iterations;
+                        args.blue_iterations = config.blue_iterations;
+                        args.anti_buddhabrot = config.anti_buddhabrot;
+                        args.sampling = config.sampling;
+                        args.newton_coefficients = config.newton_coefficients;
+                        args.newton_roots = config.newton_roots;
+                        args.newton_relaxation = config.newton_relaxation;
+                        args.newton_variant = config.newton_variant;
+                        args.lyapunov_sequence = config.lyapunov_sequence;
+                        args.bifurcation_map = config.bifurcation_map;
+                        args.bifurcation_transient = config.bifurcation_transient;
+                        args.bifurcation_samples = config.bifurcation_samples;
+                        args.attractor = config.attractor;
+                        args.attractor_params = config.attractor_params;
+                        args.end_attractor_params = config.end_attractor_params;
+                        args.flame_gamma = config.flame_gamma;
+                        args.flame_vibrancy = config.flame_vibrancy;
+                        args.flame_transforms = config.flame_transforms;
+                        args.ifs = config.ifs;
+                        args.ifs_maps = config.ifs_maps;
+                        args.chaos_vertices = config.chaos_vertices;
+                        args.chaos_ratio = config.chaos_ratio;
+                        args.chaos_rule = config.chaos_rule;
+                        args.packet_x = config.packet_x;
+                        args.packet_y = config.packet_y;
+                        args.packet_sigma = config.packet_sigma;
+                        args.packet_kx = config.packet_kx;
+                        args.packet_ky = config.packet_ky;
+                        args.schrodinger_grid = config.schrodinger_grid;
+                        args.schrodinger_time = config.schrodinger_time;
+                        args.end_schrodinger_time = config.end_schrodinger_time;
+                        args.schrodinger_dt = config.schrodinger_dt;
+                        args.potential_height = config.potential_height;
+                        args.potential_width = config.potential_width;
+                        args.well_width = config.well_width;
+                        args.barrier_gap = config.barrier_gap;
+                        args.slit_separation = config.slit_separation;
+                        args.slit_width = config.slit_width;
+                        args.harmonic_omega = config.harmonic_omega;
+                        args.potential = config.potential;
+                        args.potential_formula = config.potential_formula;
+                        args.wave_display = config.wave_display;
+                        args.schrodinger_mode = config.schrodinger_mode;
+                        args.eigenstates = config.eigenstates;
+                        args.eigenstate_dimensions = config.eigenstate_dimensions;
+                        args.orbital_n = config.orbital_n;
+                        args.orbital_l = config.orbital_l;
+                        args.orbital_m = config.orbital_m;
+                        args.end_orbital_m = config.end_orbital_m;
+                        args.slice_tilt = config.slice_tilt;
+                        args.slice_azimuth = config.slice_azimuth;
+                        args.end_slice_tilt = config.end_slice_tilt;
+                        args.end_slice_azimuth = config.end_slice_azimuth;
                     }
                     Err(e) => {
                         eprintln!("Failed to parse config file '{}': {}", config_path, e);
                         eprintln!("Please check that all fields are valid and properly formatted.");
-                        eprintln!("Expected fields: width, height, max_iterations, output_path, bands, center_x, center_y, zoom, m_size, grid_input, end_center_x, end_center_y, end_zoom, fps, duration, frames_dir, font_path, zoom_text_x, zoom_text_y, zoom_font_size, function");
+                        eprintln!("Expected fields: width, height, max_iterations, output_path, bands, center_x, center_y, zoom, m_size, grid_input, end_center_x, end_center_y, end_zoom, fps, duration, frames_dir, font_path, zoom_text_x, zoom_text_y, zoom_font_size, function, threads, color_mode, interior, bailout, julia_re, julia_im, end_julia_re, end_julia_im, julia_path, engine, center, scale, end_center, end_scale, fractal, power, formula, formula_seed, trap, trap_x, trap_y, trap_radius, trap_angle, trap_width, trap_image, end_trap_x, end_trap_y, end_trap_radius, end_trap_angle, stripe_density, samples, seed, red_iterations, green_iterations, blue_iterations, anti_buddhabrot, sampling, newton_coefficients, newton_roots, newton_relaxation, newton_variant, lyapunov_sequence, bifurcation_map, bifurcation_transient, bifurcation_samples, attractor, attractor_params, end_attractor_params, flame_gamma, flame_vibrancy, flame_transforms, ifs, ifs_maps, chaos_vertices, chaos_ratio, chaos_rule, packet_x, packet_y, packet_sigma, packet_kx, packet_ky, schrodinger_grid, schrodinger_time, end_schrodinger_time, schrodinger_dt, potential_height, potential_width, well_width, barrier_gap, slit_separation, slit_width, harmonic_omega, potential, potential_formula, wave_display, schrodinger_mode, eigenstates, eigenstate_dimensions, orbital_n, orbital_l, orbital_m, end_orbital_m, slice_tilt, slice_azimuth, end_slice_tilt, end_slice_azimuth");
                         std::process::exit(1);
                     }
                 }
@@ -84,14 +525,14 @@ pub fn save_config(args: &crate::parameters::Args, output_path: &str) {
             max_iterations: args.max_iterations,
             output_path: output_path.to_string(),
             bands: args.bands,
-            center_x: args.center_x,
-            center_y: args.center_y,
-            zoom: args.zoom,
+            center_x: args.center_x.clone(),
+            center_y: args.center_y.clone(),
+            zoom: args.zoom.clone(),
             m_size: args.m_size,
             grid_input: args.grid_input.clone(),
-            end_center_x: args.end_center_x,
-            end_center_y: args.end_center_y,
-            end_zoom: args.end_zoom,
+            end_center_x: args.end_center_x.clone(),
+            end_center_y: args.end_center_y.clone(),
+            end_zoom: args.end_zoom.clone(),
             fps: args.fps,
             duration: args.duration,
             frames_dir: args.frames_dir.clone(),
@@ -100,6 +541,92 @@ pub fn save_config(args: &crate::parameters::Args, output_path: &str) {
             zoom_text_y: args.zoom_text_y,
             zoom_font_size: args.zoom_font_size,
             function: args.function.clone(),
+            threads: args.threads,
+            color_mode: args.color_mode.clone(),
+            interior: args.interior.clone(),
+            bailout: args.bailout,
+            julia_re: args.julia_re,
+            julia_im: args.julia_im,
+            end_julia_re: args.end_julia_re,
+            end_julia_im: args.end_julia_im,
+            julia_path: args.julia_path.clone(),
+            engine: args.engine.clone(),
+            center: args.center.clone(),
+            scale: args.scale,
+            end_center: args.end_center.clone(),
+            end_scale: args.end_scale,
+            fractal: args.fractal.clone(),
+            power: args.power,
+            formula: args.formula.clone(),
+            formula_seed: args.formula_seed.clone(),
+            trap: args.trap.clone(),
+            trap_x: args.trap_x,
+            trap_y: args.trap_y,
+            trap_radius: args.trap_radius,
+            trap_angle: args.trap_angle,
+            trap_width: args.trap_width,
+            trap_image: args.trap_image.clone(),
+            end_trap_x: args.end_trap_x,
+            end_trap_y: args.end_trap_y,
+            end_trap_radius: args.end_trap_radius,
+            end_trap_angle: args.end_trap_angle,
+            stripe_density: args.stripe_density,
+            samples: args.samples,
+            seed: args.seed,
+            red_iterations: args.red_iterations,
+            green_iterations: args.green_iterations,
+            blue_iterations: args.blue_iterations,
+            anti_buddhabrot: args.anti_buddhabrot,
+            sampling: args.sampling.clone(),
+            newton_coefficients: args.newton_coefficients.clone(),
+            newton_roots: args.newton_roots.clone(),
+            newton_relaxation: args.newton_relaxation,
+            newton_variant: args.newton_variant.clone(),
+            lyapunov_sequence: args.lyapunov_sequence.clone(),
+            bifurcation_map: args.bifurcation_map.clone(),
+            bifurcation_transient: args.bifurcation_transient,
+            bifurcation_samples: args.bifurcation_samples,
+            attractor: args.attractor.clone(),
+            attractor_params: args.attractor_params.clone(),
+            end_attractor_params: args.end_attractor_params.clone(),
+            flame_gamma: args.flame_gamma,
+            flame_vibrancy: args.flame_vibrancy,
+            flame_transforms: args.flame_transforms.clone(),
+            ifs: args.ifs.clone(),
+            ifs_maps: args.ifs_maps.clone(),
+            chaos_vertices: args.chaos_vertices,
+            chaos_ratio: args.chaos_ratio,
+            chaos_rule: args.chaos_rule.clone(),
+            packet_x: args.packet_x,
+            packet_y: args.packet_y,
+            packet_sigma: args.packet_sigma,
+            packet_kx: args.packet_kx,
+            packet_ky: args.packet_ky,
+            schrodinger_grid: args.schrodinger_grid,
+            schrodinger_time: args.schrodinger_time,
+            end_schrodinger_time: args.end_schrodinger_time,
+            schrodinger_dt: args.schrodinger_dt,
+            potential_height: args.potential_height,
+            potential_width: args.potential_width,
+            well_width: args.well_width,
+            barrier_gap: args.barrier_gap,
+            slit_separation: args.slit_separation,
+            slit_width: args.slit_width,
+            harmonic_omega: args.harmonic_omega,
+            potential: args.potential.clone(),
+            potential_formula: args.potential_formula.clone(),
+            wave_display: args.wave_display.clone(),
+            schrodinger_mode: args.schrodinger_mode.clone(),
+            eigenstates: args.eigenstates,
+            eigenstate_dimensions: args.eigenstate_dimensions,
+            orbital_n: args.orbital_n,
+            orbital_l: args.orbital_l,
+            orbital_m: args.orbital_m,
+            end_orbital_m: args.end_orbital_m,
+            slice_tilt: args.slice_tilt,
+            slice_azimuth: args.slice_azimuth,
+            end_slice_tilt: args.end_slice_tilt,
+            end_slice_azimuth: args.end_slice_azimuth,
             extra: HashMap::new(),
         };
         let config_toml = toml::to_string(&config).unwrap();
@@ -115,6 +642,7 @@ pub fn save_config(args: &crate::parameters::Args, output_path: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::decimal::Decimal;
     use crate::parameters::Args;
     use std::fs;
 
@@ -126,14 +654,14 @@ mod tests {
             max_iterations: 1000,
             output_path: "test.png".to_string(),
             bands: 16,
-            center_x: -0.5,
-            center_y: 0.0,
-            zoom: 1.0,
+            center_x: Decimal::from(-0.5),
+            center_y: Decimal::from(0.0),
+            zoom: Decimal::from(1.0),
             m_size: 10.0,
             grid_input: Some("grid.png".to_string()),
-            end_center_x: Some(-0.7),
-            end_center_y: Some(0.1),
-            end_zoom: Some(2.0),
+            end_center_x: Some(Decimal::from(-0.7)),
+            end_center_y: Some(Decimal::from(0.1)),
+            end_zoom: Some(Decimal::from(2.0)),
             fps: 30.0,
             duration: 10.0,
             frames_dir: "frames".to_string(),
@@ -142,6 +670,92 @@ mod tests {
             zoom_text_y: 110,
             zoom_font_size: 20.0,
             function: "mandelbrot".to_string(),
+            threads: 0,
+            color_mode: "bands".to_string(),
+            interior: "solid".to_string(),
+            bailout: 2.0,
+            julia_re: -0.8,
+            julia_im: 0.156,
+            end_julia_re: None,
+            end_julia_im: None,
+            julia_path: "line".to_string(),
+            engine: "direct".to_string(),
+            center: None,
+            scale: None,
+            end_center: None,
+            end_scale: None,
+            fractal: "mandelbrot".to_string(),
+            power: 2.0,
+            formula: "z^2 + c".to_string(),
+            formula_seed: "mandelbrot".to_string(),
+            trap: "point".to_string(),
+            trap_x: 0.0,
+            trap_y: 0.0,
+            trap_radius: 1.0,
+            trap_angle: 0.0,
+            trap_width: 0.1,
+            trap_image: None,
+            end_trap_x: None,
+            end_trap_y: None,
+            end_trap_radius: None,
+            end_trap_angle: None,
+            stripe_density: 5.0,
+            samples: 1000000,
+            seed: 0,
+            red_iterations: None,
+            green_iterations: None,
+            blue_iterations: None,
+            anti_buddhabrot: false,
+            sampling: "uniform".to_string(),
+            newton_coefficients: "1; 0; 0; -1".to_string(),
+            newton_roots: None,
+            newton_relaxation: 1.0,
+            newton_variant: "newton".to_string(),
+            lyapunov_sequence: "AB".to_string(),
+            bifurcation_map: "logistic".to_string(),
+            bifurcation_transient: 1000,
+            bifurcation_samples: 10000,
+            attractor: "clifford".to_string(),
+            attractor_params: None,
+            end_attractor_params: None,
+            flame_gamma: 4.0,
+            flame_vibrancy: 1.0,
+            flame_transforms: vec![FlameTransform::new(2.0, [0.5, 0.0, 0.0, 0.0, 0.5, 0.5], 0.25, &[("linear", 0.5), ("swirl", 0.5)])],
+            ifs: "fern".to_string(),
+            ifs_maps: Vec::new(),
+            chaos_vertices: 3,
+            chaos_ratio: 0.5,
+            chaos_rule: "any".to_string(),
+            packet_x: 0.0,
+            packet_y: 0.0,
+            packet_sigma: 0.5,
+            packet_kx: 0.0,
+            packet_ky: 0.0,
+            schrodinger_grid: 256,
+            schrodinger_time: 0.0,
+            end_schrodinger_time: None,
+            schrodinger_dt: 0.005,
+            potential: "free".to_string(),
+            potential_height: 20.0,
+            potential_width: 0.2,
+            well_width: 4.0,
+            barrier_gap: 1.0,
+            slit_separation: 1.0,
+            slit_width: 0.3,
+            harmonic_omega: 1.0,
+            potential_formula: "0.5 * (x^2 + y^2)".to_string(),
+            wave_display: "density".to_string(),
+            schrodinger_mode: "evolve".to_string(),
+            eigenstates: 6,
+            eigenstate_dimensions: 2,
+            orbital_n: 2,
+            orbital_l: 1,
+            orbital_m: 0,
+            end_orbital_m: None,
+            slice_tilt: 90.0,
+            slice_azimuth: 0.0,
+            end_slice_tilt: None,
+            end_slice_azimuth: None,
             extra: HashMap::new(),
         };
 
@@ -167,6 +781,7 @@ mod tests {
         assert_eq!(config.zoom_text_y, deserialized.zoom_text_y);
         assert_eq!(config.zoom_font_size, deserialized.zoom_font_size);
         assert_eq!(config.function, deserialized.function);
+        assert_eq!(config.flame_transforms, deserialized.flame_transforms);
     }
 
     #[test]
@@ -204,9 +819,9 @@ function = "schrodinger"
             output_path: Some("original.png".to_string()),
             config: Some(temp_file.to_string()),
             bands: 16,
-            center_x: -0.5,
-            center_y: 0.0,
-            zoom: 1.0,
+            center_x: Decimal::from(-0.5),
+            center_y: Decimal::from(0.0),
+            zoom: Decimal::from(1.0),
             m_size: 10.0,
             grid_input: None,
             end_center_x: None,
@@ -220,6 +835,92 @@ function = "schrodinger"
             zoom_text_y: 110,
             zoom_font_size: 20.0,
             function: "mandelbrot".to_string(),
+            threads: 0,
+            color_mode: "bands".to_string(),
+            interior: "solid".to_string(),
+            bailout: 2.0,
+            julia_re: -0.8,
+            julia_im: 0.156,
+            end_julia_re: None,
+            end_julia_im: None,
+            julia_path: "line".to_string(),
+            engine: "direct".to_string(),
+            center: None,
+            scale: None,
+            end_center: None,
+            end_scale: None,
+            fractal: "mandelbrot".to_string(),
+            power: 2.0,
+            formula: "z^2 + c".to_string(),
+            formula_seed: "mandelbrot".to_string(),
+            trap: "point".to_string(),
+            trap_x: 0.0,
+            trap_y: 0.0,
+            trap_radius: 1.0,
+            trap_angle: 0.0,
+            trap_width: 0.1,
+            trap_image: None,
+            end_trap_x: None,
+            end_trap_y: None,
+            end_trap_radius: None,
+            end_trap_angle: None,
+            stripe_density: 5.0,
+            samples: 1000000,
+            seed: 0,
+            red_iterations: None,
+            green_iterations: None,
+            blue_iterations: None,
+            anti_buddhabrot: false,
+            sampling: "uniform".to_string(),
+            newton_coefficients: "1; 0; 0; -1".to_string(),
+            newton_roots: None,
+            newton_relaxation: 1.0,
+            newton_variant: "newton".to_string(),
+            lyapunov_sequence: "AB".to_string(),
+            bifurcation_map: "logistic".to_string(),
+            bifurcation_transient: 1000,
+            bifurcation_samples: 10000,
+            attractor: "clifford".to_string(),
+            attractor_params: None,
+            end_attractor_params: None,
+            flame_gamma: 4.0,
+            flame_vibrancy: 1.0,
+            flame_transforms: Vec::new(),
+            ifs: "fern".to_string(),
+            ifs_maps: Vec::new(),
+            chaos_vertices: 3,
+            chaos_ratio: 0.5,
+            chaos_rule: "any".to_string(),
+            packet_x: 0.0,
+            packet_y: 0.0,
+            packet_sigma: 0.5,
+            packet_kx: 0.0,
+            packet_ky: 0.0,
+            schrodinger_grid: 256,
+            schrodinger_time: 0.0,
+            end_schrodinger_time: None,
+            schrodinger_dt: 0.005,
+            potential: "free".to_string(),
+            potential_height: 20.0,
+            potential_width: 0.2,
+            well_width: 4.0,
+            barrier_gap: 1.0,
+            slit_separation: 1.0,
+            slit_width: 0.3,
+            harmonic_omega: 1.0,
+            potential_formula: "0.5 * (x^2 + y^2)".to_string(),
+            wave_display: "density".to_string(),
+            schrodinger_mode: "evolve".to_string(),
+            eigenstates: 6,
+            eigenstate_dimensions: 2,
+            orbital_n: 2,
+            orbital_l: 1,
+            orbital_m: 0,
+            end_orbital_m: None,
+            slice_tilt: 90.0,
+            slice_azimuth: 0.0,
+            end_slice_tilt: None,
+            end_slice_azimuth: None,
         };
 
         load_config(&mut args, Some(temp_file.to_string()));
@@ -232,9 +933,9 @@ function = "schrodinger"
         assert_eq!(args.center_x, -0.75);
         assert_eq!(args.center_y, 0.1);
         assert_eq!(args.zoom, 2.0);
-        assert_eq!(args.end_center_x, Some(-0.8));
-        assert_eq!(args.end_center_y, Some(0.2));
-        assert_eq!(args.end_zoom, Some(4.0));
+        assert_eq!(args.end_center_x, Some(Decimal::from(-0.8)));
+        assert_eq!(args.end_center_y, Some(Decimal::from(0.2)));
+        assert_eq!(args.end_zoom, Some(Decimal::from(4.0)));
         assert_eq!(args.fps, 60.0);
         assert_eq!(args.duration, 15.0);
         assert_eq!(args.frames_dir, "test_frames");
@@ -256,14 +957,14 @@ function = "schrodinger"
             output_path: Some("output.png".to_string()),
             config: None, // Should save config since config is None
             bands: 24,
-            center_x: -0.6,
-            center_y: 0.05,
-            zoom: 1.5,
+            center_x: Decimal::from(-0.6),
+            center_y: Decimal::from(0.05),
+            zoom: Decimal::from(1.5),
             m_size: 15.0,
             grid_input: None,
-            end_center_x: Some(-0.65),
-            end_center_y: Some(0.1),
-            end_zoom: Some(3.0),
+            end_center_x: Some(Decimal::from(-0.65)),
+            end_center_y: Some(Decimal::from(0.1)),
+            end_zoom: Some(Decimal::from(3.0)),
             fps: 45.0,
             duration: 12.0,
             frames_dir: "my_frames".to_string(),
@@ -272,6 +973,92 @@ function = "schrodinger"
             zoom_text_y: 120,
             zoom_font_size: 22.0,
             function: "mandelbrot".to_string(),
+            threads: 0,
+            color_mode: "bands".to_string(),
+            interior: "solid".to_string(),
+            bailout: 2.0,
+            julia_re: -0.8,
+            julia_im: 0.156,
+            end_julia_re: None,
+            end_julia_im: None,
+            julia_path: "line".to_string(),
+            engine: "direct".to_string(),
+            center: None,
+            scale: None,
+            end_center: None,
+            end_scale: None,
+            fractal: "mandelbrot".to_string(),
+            power: 2.0,
+            formula: "z^2 + c".to_string(),
+            formula_seed: "mandelbrot".to_string(),
+            trap: "point".to_string(),
+            trap_x: 0.0,
+            trap_y: 0.0,
+            trap_radius: 1.0,
+            trap_angle: 0.0,
+            trap_width: 0.1,
+            trap_image: None,
+            end_trap_x: None,
+            end_trap_y: None,
+            end_trap_radius: None,
+            end_trap_angle: None,
+            stripe_density: 5.0,
+            samples: 1000000,
+            seed: 0,
+            red_iterations: None,
+            green_iterations: None,
+            blue_iterations: None,
+            anti_buddhabrot: false,
+            sampling: "uniform".to_string(),
+            newton_coefficients: "1; 0; 0; -1".to_string(),
+            newton_roots: None,
+            newton_relaxation: 1.0,
+            newton_variant: "newton".to_string(),
+            lyapunov_sequence: "AB".to_string(),
+            bifurcation_map: "logistic".to_string(),
+            bifurcation_transient: 1000,
+            bifurcation_samples: 10000,
+            attractor: "clifford".to_string(),
+            attractor_params: None,
+            end_attractor_params: None,
+            flame_gamma: 4.0,
+            flame_vibrancy: 1.0,
+            flame_transforms: Vec::new(),
+            ifs: "fern".to_string(),
+            ifs_maps: Vec::new(),
+            chaos_vertices: 3,
+            chaos_ratio: 0.5,
+            chaos_rule: "any".to_string(),
+            packet_x: 0.0,
+            packet_y: 0.0,
+            packet_sigma: 0.5,
+            packet_kx: 0.0,
+            packet_ky: 0.0,
+            schrodinger_grid: 256,
+            schrodinger_time: 0.0,
+            end_schrodinger_time: None,
+            schrodinger_dt: 0.005,
+            potential: "free".to_string(),
+            potential_height: 20.0,
+            potential_width: 0.2,
+            well_width: 4.0,
+            barrier_gap: 1.0,
+            slit_separation: 1.0,
+            slit_width: 0.3,
+            harmonic_omega: 1.0,
+            potential_formula: "0.5 * (x^2 + y^2)".to_string(),
+            wave_display: "density".to_string(),
+            schrodinger_mode: "evolve".to_string(),
+            eigenstates: 6,
+            eigenstate_dimensions: 2,
+            orbital_n: 2,
+            orbital_l: 1,
+            orbital_m: 0,
+            end_orbital_m: None,
+            slice_tilt: 90.0,
+            slice_azimuth: 0.0,
+            end_slice_tilt: None,
+            end_slice_azimuth: None,
         };
 
         let temp_output = "/tmp/test_output.png";
@@ -291,9 +1078,9 @@ function = "schrodinger"
         assert_eq!(config.center_x, -0.6);
         assert_eq!(config.center_y, 0.05);
         assert_eq!(config.zoom, 1.5);
-        assert_eq!(config.end_center_x, Some(-0.65));
-        assert_eq!(config.end_center_y, Some(0.1));
-        assert_eq!(config.end_zoom, Some(3.0));
+        assert_eq!(config.end_center_x, Some(Decimal::from(-0.65)));
+        assert_eq!(config.end_center_y, Some(Decimal::from(0.1)));
+        assert_eq!(config.end_zoom, Some(Decimal::from(3.0)));
         assert_eq!(config.fps, 45.0);
         assert_eq!(config.duration, 12.0);
         assert_eq!(config.frames_dir, "my_frames");
@@ -315,9 +1102,9 @@ function = "schrodinger"
             output_path: Some("output.png".to_string()),
             config: Some("existing.conf".to_string()), // Should NOT save config
             bands: 16,
-            center_x: -0.5,
-            center_y: 0.0,
-            zoom: 1.0,
+            center_x: Decimal::from(-0.5),
+            center_y: Decimal::from(0.0),
+            zoom: Decimal::from(1.0),
             m_size: 10.0,
             grid_input: None,
             end_center_x: None,
@@ -331,6 +1118,92 @@ function = "schrodinger"
             zoom_text_y: 110,
             zoom_font_size: 20.0,
             function: "mandelbrot".to_string(),
+            threads: 0,
+            color_mode: "bands".to_string(),
+            interior: "solid".to_string(),
+            bailout: 2.0,
+            julia_re: -0.8,
+            julia_im: 0.156,
+            end_julia_re: None,
+            end_julia_im: None,
+            julia_path: "line".to_string(),
+            engine: "direct".to_string(),
+            center: None,
+            scale: None,
+            end_center: None,
+            end_scale: None,
+            fractal: "mandelbrot".to_string(),
+            power: 2.0,
+            formula: "z^2 + c".to_string(),
+            formula_seed: "mandelbrot".to_string(),
+            trap: "point".to_string(),
+            trap_x: 0.0,
+            trap_y: 0.0,
+            trap_radius: 1.0,
+            trap_angle: 0.0,
+            trap_width: 0.1,
+            trap_image: None,
+            end_trap_x: None,
+            end_trap_y: None,
+            end_trap_radius: None,
+            end_trap_angle: None,
+            stripe_density: 5.0,
+            samples: 1000000,
+            seed: 0,
+            red_iterations: None,
+            green_iterations: None,
+            blue_iterations: None,
+            anti_buddhabrot: false,
+            sampling: "uniform".to_string(),
+            newton_coefficients: "1; 0; 0; -1".to_string(),
+            newton_roots: None,
+            newton_relaxation: 1.0,
+            newton_variant: "newton".to_string(),
+            lyapunov_sequence: "AB".to_string(),
+            bifurcation_map: "logistic".to_string(),
+            bifurcation_transient: 1000,
+            bifurcation_samples: 10000,
+            attractor: "clifford".to_string(),
+            attractor_params: None,
+            end_attractor_params: None,
+            flame_gamma: 4.0,
+            flame_vibrancy: 1.0,
+            flame_transforms: Vec::new(),
+            ifs: "fern".to_string(),
+            ifs_maps: Vec::new(),
+            chaos_vertices: 3,
+            chaos_ratio: 0.5,
+            chaos_rule: "any".to_string(),
+            packet_x: 0.0,
+            packet_y: 0.0,
+            packet_sigma: 0.5,
+            packet_kx: 0.0,
+            packet_ky: 0.0,
+            schrodinger_grid: 256,
+            schrodinger_time: 0.0,
+            end_schrodinger_time: None,
+            schrodinger_dt: 0.005,
+            potential: "free".to_string(),
+            potential_height: 20.0,
+            potential_width: 0.2,
+            well_width: 4.0,
+            barrier_gap: 1.0,
+            slit_separation: 1.0,
+            slit_width: 0.3,
+            harmonic_omega: 1.0,
+            potential_formula: "0.5 * (x^2 + y^2)".to_string(),
+            wave_display: "density".to_string(),
+            schrodinger_mode: "evolve".to_string(),
+            eigenstates: 6,
+            eigenstate_dimensions: 2,
+            orbital_n: 2,
+            orbital_l: 1,
+            orbital_m: 0,
+            end_orbital_m: None,
+            slice_tilt: 90.0,
+            slice_azimuth: 0.0,
+            end_slice_tilt: None,
+            end_slice_azimuth: None,
         };
 
         let temp_output = "/tmp/test_output2.png";
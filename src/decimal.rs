@@ -0,0 +1,203 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::big_fixed::BigFixed;
+
+/// A decimal number kept exactly as it was written.
+///
+/// Deep zoom locations need far more digits than an `f64` can hold. The text is
+/// stored unchanged so it round-trips losslessly through config files, and is
+/// converted to `f64` or to a high-precision `BigFixed` where it is used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decimal {
+    text: String,
+}
+
+impl Decimal {
+    /// Converts to the nearest `f64`.
+    pub fn to_f64(&self) -> f64 {
+        self.text.parse().expect("Decimal text is validated on construction")
+    }
+
+    /// Converts to a fixed-point number with the given number of fractional bits.
+    pub fn to_big_fixed(&self, frac_bits: u32) -> BigFixed {
+        BigFixed::parse(&self.text, frac_bits).expect("Decimal text is validated on construction")
+    }
+
+    /// Number of fractional bits needed to hold all written digits, plus 64 guard bits.
+    pub fn frac_bits_needed(&self) -> u32 {
+        let lower = self.text.to_ascii_lowercase();
+        let (number, exponent) = match lower.split_once('e') {
+            Some((number, exponent)) => (number, exponent.parse::<i64>().unwrap_or(0)),
+            None => (lower.as_str(), 0),
+        };
+        let fraction_digits = number.split_once('.').map_or(0, |(_, fraction)| fraction.len()) as i64;
+        let decimal_places = (fraction_digits - exponent).max(0) as f64;
+        (decimal_places * std::f64::consts::LOG2_10).ceil() as u32 + 64
+    }
+}
+
+/// A complex number written as two decimals, `re,im`.
+///
+/// Used for view centers given directly in complex plane coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComplexDecimal {
+    pub re: Decimal,
+    pub im: Decimal,
+}
+
+impl FromStr for ComplexDecimal {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<ComplexDecimal, String> {
+        let (re, im) = text
+            .split_once(',')
+            .ok_or_else(|| format!("expected 're,im', got '{}'", text))?;
+        Ok(ComplexDecimal { re: re.parse()?, im: im.parse()? })
+    }
+}
+
+impl fmt::Display for ComplexDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.re, self.im)
+    }
+}
+
+impl Serialize for ComplexDecimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ComplexDecimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ComplexDecimal, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Decimal, String> {
+        let text = text.trim();
+        BigFixed::parse(text, 0)?;
+        Ok(Decimal { text: text.to_string() })
+    }
+}
+
+impl From<f64> for Decimal {
+    fn from(value: f64) -> Decimal {
+        assert!(value.is_finite(), "Cannot represent {} as a decimal", value);
+        // Debug formatting gives the shortest text that round-trips
+        Decimal { text: format!("{:?}", value) }
+    }
+}
+
+impl From<BigFixed> for Decimal {
+    fn from(value: BigFixed) -> Decimal {
+        Decimal { text: value.to_decimal_string() }
+    }
+}
+
+impl PartialEq<f64> for Decimal {
+    fn eq(&self, other: &f64) -> bool {
+        self.to_f64() == *other
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Stored as a string so no digits are lost
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        // Older config files store plain numbers
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Text(String),
+            Number(f64),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+            Raw::Number(value) => Ok(Decimal::from(value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper {
+        value: Decimal,
+    }
+
+    #[test]
+    fn test_parse_and_convert() {
+        let decimal: Decimal = "-0.743643887037158704752191506114774".parse().unwrap();
+        assert_eq!(decimal.to_string(), "-0.743643887037158704752191506114774");
+        assert_eq!(decimal.to_f64(), -0.7436438870371587);
+        assert_eq!(decimal.to_big_fixed(160).to_decimal_string(), decimal.to_string());
+
+        assert!("1.2.3".parse::<Decimal>().is_err());
+        assert!("nan".parse::<Decimal>().is_err());
+        assert!("1e4294967296".parse::<Decimal>().is_err());
+    }
+
+    #[test]
+    fn test_frac_bits_needed() {
+        assert_eq!("12".parse::<Decimal>().unwrap().frac_bits_needed(), 64);
+        assert_eq!("0.5".parse::<Decimal>().unwrap().frac_bits_needed(), 64 + 4);
+        assert_eq!("1e-3".parse::<Decimal>().unwrap().frac_bits_needed(), 64 + 10);
+        assert_eq!("1.25E2".parse::<Decimal>().unwrap().frac_bits_needed(), 64);
+    }
+
+    #[test]
+    fn test_complex_decimal() {
+        let center: ComplexDecimal = "-0.75,0.1".parse().unwrap();
+        assert_eq!(center.re, -0.75);
+        assert_eq!(center.im, 0.1);
+        assert_eq!(center.to_string(), "-0.75,0.1");
+
+        assert!("-0.75".parse::<ComplexDecimal>().is_err());
+        assert!("a,b".parse::<ComplexDecimal>().is_err());
+    }
+
+    #[test]
+    fn test_from_f64() {
+        assert_eq!(Decimal::from(-0.5).to_string(), "-0.5");
+        assert_eq!(Decimal::from(0.0).to_string(), "0.0");
+        assert_eq!(Decimal::from(1e-30).to_f64(), 1e-30);
+        assert_eq!(Decimal::from(2.0), 2.0);
+    }
+
+    #[test]
+    fn test_serde_roundtrip_is_lossless() {
+        let text = "value = \"0.131825904205311970493132056385139\"\n";
+        let wrapper: Wrapper = toml::from_str(text).unwrap();
+        assert_eq!(toml::to_string(&wrapper).unwrap(), text);
+    }
+
+    #[test]
+    fn test_deserialize_plain_numbers() {
+        let wrapper: Wrapper = toml::from_str("value = -0.75").unwrap();
+        assert_eq!(wrapper.value, -0.75);
+        let wrapper: Wrapper = toml::from_str("value = 3").unwrap();
+        assert_eq!(wrapper.value, 3.0);
+        assert!(toml::from_str::<Wrapper>("value = \"abc\"").is_err());
+    }
+}
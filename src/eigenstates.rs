@@ -0,0 +1,340 @@
+use num_complex::Complex64;
+use rayon::prelude::*;
+
+use crate::fft::{fft, fft_2d_transposed, wavenumber};
+use crate::rng::Rng;
+
+/// Largest number of iterations.
+const MAX_ITERATIONS: usize = 1000;
+
+/// Fraction of its norm below which a vector that is projected out of the
+/// previous ones is dropped from the basis as linearly dependent.
+const DEPENDENCE: f64 = 1e-10;
+
+/// Residual |H·ψ - E·ψ| relative to max(|E|, 1) at which a state has converged.
+const TOLERANCE: f64 = 1e-6;
+
+/// States iterated along with the requested ones, so that the highest
+/// requested state doesn't converge at the pace of the next one above it.
+const GUARD_STATES: usize = 2;
+
+/// Hamiltonian H = -½∇² + V (units with ħ = m = 1) on a periodic grid in one
+/// or two dimensions, with the kinetic term applied in momentum space.
+///
+/// Points where the potential isn't finite are walls: states vanish there, and
+/// H is restricted to the remaining points.
+pub struct Hamiltonian<'a> {
+    /// Grid points per side (a power of two).
+    n: usize,
+    /// Number of dimensions, 1 or 2.
+    dimensions: usize,
+    /// ½|k|² for every frequency, in the order of the transformed grid.
+    kinetic: Vec<f64>,
+    /// Potential at the grid points, row by row in two dimensions.
+    potential: &'a [f64],
+}
+
+impl<'a> Hamiltonian<'a> {
+    /// Creates the Hamiltonian of a grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Grid points per side (a power of two).
+    /// * `size` - Side of the periodic box.
+    /// * `dimensions` - Number of dimensions, 1 or 2.
+    /// * `potential` - Potential at the n or n × n grid points.
+    pub fn new(n: usize, size: f64, dimensions: usize, potential: &'a [f64]) -> Hamiltonian<'a> {
+        assert_eq!(potential.len(), n.pow(dimensions as u32), "The potential must have a value per grid point");
+        // ½|k|² is symmetric in kx and ky, so the transposed 2D spectrum needs no reordering
+        let kinetic = (0..potential.len())
+            .map(|i| match dimensions {
+                1 => 0.5 * wavenumber(i, n, size).powi(2),
+                _ => 0.5 * (wavenumber(i / n, n, size).powi(2) + wavenumber(i % n, n, size).powi(2)),
+            })
+            .collect();
+        Hamiltonian { n, dimensions, kinetic, potential }
+    }
+
+    /// Number of grid points outside walls, which bounds the number of eigenstates.
+    pub fn free_points(&self) -> usize {
+        self.potential.iter().filter(|value| value.is_finite()).count()
+    }
+
+    /// Transforms a state to momentum space or back.
+    fn transform(&self, data: &mut [Complex64], inverse: bool) {
+        if self.dimensions == 1 { fft(data, inverse) } else { fft_2d_transposed(data, self.n, inverse) }
+    }
+
+    /// Multiplies every plane wave of a state by a function of its kinetic energy ½|k|².
+    fn in_momentum_space(&self, state: &mut [Complex64], factor: impl Fn(f64) -> f64) {
+        self.transform(state, false);
+        state.iter_mut().zip(&self.kinetic).for_each(|(value, &kinetic)| *value *= factor(kinetic));
+        self.transform(state, true);
+    }
+
+    /// Applies H to a state that vanishes in walls.
+    pub fn apply(&self, state: &[Complex64]) -> Vec<Complex64> {
+        let mut result = state.to_vec();
+        self.in_momentum_space(&mut result, |kinetic| kinetic);
+        for ((value, psi), &potential) in result.iter_mut().zip(state).zip(self.potential) {
+            *value = if potential.is_finite() { *value + psi * potential } else { Complex64::default() };
+        }
+        result
+    }
+
+    /// Approximates (H - E)⁻¹ on a residual by (T + shift)⁻¹, which is diagonal
+    /// in momentum space, and keeps the result at 0 in walls.
+    fn precondition(&self, residual: &[Complex64], shift: f64) -> Vec<Complex64> {
+        let mut result = residual.to_vec();
+        self.in_momentum_space(&mut result, |kinetic| 1.0 / (kinetic + shift));
+        for (value, potential) in result.iter_mut().zip(self.potential) {
+            *value = if potential.is_finite() { Complex64::new(value.re, 0.0) } else { Complex64::default() };
+        }
+        result
+    }
+
+    /// Expectation value ⟨a|H|b⟩ of two real states.
+    fn matrix_element(a: &[Complex64], h_b: &[Complex64]) -> f64 {
+        a.iter().zip(h_b).map(|(a, b)| a.re * b.re).sum()
+    }
+}
+
+/// Computes the lowest energy eigenstates of a Hamiltonian.
+///
+/// A block of random states converges with the locally optimal block
+/// preconditioned conjugate gradient method (LOBPCG): every iteration projects
+/// H (Rayleigh–Ritz) on the span of the block, of the preconditioned residuals
+/// H·ψ - E·ψ and of the previous search directions, and keeps the lowest
+/// eigenvectors of the projection as the new block. H is real, so the states
+/// are real; each one has its largest value positive.
+///
+/// # Arguments
+///
+/// * `hamiltonian` - Hamiltonian of the grid.
+/// * `count` - Number of states.
+/// * `seed` - Seed of the random initial states, which picks a basis of degenerate levels.
+///
+/// # Returns
+///
+/// The energies in ascending order with their states normalized to Σ|ψ|² = 1,
+/// or an error message if the grid has too few points outside walls.
+pub fn lowest_eigenstates(hamiltonian: &Hamiltonian, count: usize, seed: u64) -> Result<Vec<(f64, Vec<Complex64>)>, String> {
+    let block = count + GUARD_STATES;
+    if count == 0 {
+        return Err("The number of eigenstates must be positive".to_string());
+    }
+    if block > hamiltonian.free_points() {
+        return Err(format!("The grid has {} points outside walls, too few for {} eigenstates", hamiltonian.free_points(), count));
+    }
+
+    let lowest_potential = hamiltonian.potential.iter().filter(|value| value.is_finite()).fold(f64::INFINITY, |lowest, &value| lowest.min(value));
+    let mut rng = Rng::new(seed);
+    let random: Vec<Vec<Complex64>> = (0..block)
+        .map(|_| {
+            hamiltonian
+                .potential
+                .iter()
+                .map(|value| Complex64::new(if value.is_finite() { rng.next_f64() - 0.5 } else { 0.0 }, 0.0))
+                .collect()
+        })
+        .collect();
+
+    // Smoothing the random states twice brings them closer to the lowest energies
+    let smoothing = hamiltonian.kinetic[1] * block as f64;
+    let smooth = random.par_iter().map(|vector| hamiltonian.precondition(&hamiltonian.precondition(vector, smoothing), smoothing)).collect();
+    // Orthonormal basis of the search space, starting with the block, and H applied to it
+    let mut basis = orthonormalize(smooth);
+    let mut products: Vec<Vec<Complex64>> = basis.par_iter().map(|vector| hamiltonian.apply(vector)).collect();
+    let mut iteration = 0;
+    let (energies, states) = loop {
+        let projection: Vec<Vec<f64>> = (0..basis.len())
+            .map(|i| {
+                (0..basis.len())
+                    .map(|j| 0.5 * (Hamiltonian::matrix_element(&basis[i], &products[j]) + Hamiltonian::matrix_element(&basis[j], &products[i])))
+                    .collect()
+            })
+            .collect();
+        let (values, vectors) = symmetric_eigen(projection);
+        let energies = values[..block].to_vec();
+        let states = combine(&basis, &vectors, block);
+        let state_products = combine(&products, &vectors, block);
+
+        let residuals: Vec<Vec<Complex64>> = (0..block)
+            .map(|i| state_products[i].iter().zip(&states[i]).map(|(h_psi, psi)| h_psi - psi * energies[i]).collect())
+            .collect();
+        let converged = (0..count).all(|i| residuals[i].iter().map(|value| value.norm_sqr()).sum::<f64>().sqrt() <= TOLERANCE * energies[i].abs().max(1.0));
+        iteration += 1;
+        if converged || iteration == MAX_ITERATIONS {
+            break (energies, states);
+        }
+
+        // The new states minus their part in the old block are the next search directions
+        let directions = if basis.len() > block { combine(&basis[block..], &vectors[block..], block) } else { Vec::new() };
+        let shift = (energies[block - 1] - lowest_potential).max(hamiltonian.kinetic[1]);
+        let preconditioned: Vec<Vec<Complex64>> = residuals.par_iter().map(|residual| hamiltonian.precondition(residual, shift)).collect();
+
+        // The states are already orthonormal, so they keep their products
+        basis = orthonormalize(states.into_iter().chain(preconditioned).chain(directions).collect());
+        let new_products: Vec<Vec<Complex64>> = basis[block..].par_iter().map(|vector| hamiltonian.apply(vector)).collect();
+        products = state_products.into_iter().chain(new_products).collect();
+    };
+
+    Ok(energies
+        .into_iter()
+        .zip(states)
+        .take(count)
+        .map(|(energy, mut state)| {
+            let peak = state.iter().fold(0.0, |peak: f64, value| if value.re.abs() > peak.abs() { value.re } else { peak });
+            if peak < 0.0 {
+                state.iter_mut().for_each(|value| *value = -*value);
+            }
+            (energy, state)
+        })
+        .collect())
+}
+
+/// Linear combinations of vectors, with the coefficients of each combination in
+/// a column of `coefficients` and a row per vector.
+fn combine(vectors: &[Vec<Complex64>], coefficients: &[Vec<f64>], columns: usize) -> Vec<Vec<Complex64>> {
+    (0..columns)
+        .into_par_iter()
+        .map(|column| {
+            let mut combination = vec![Complex64::default(); vectors[0].len()];
+            for (vector, row) in vectors.iter().zip(coefficients) {
+                combination.iter_mut().zip(vector).for_each(|(value, x)| *value += x * row[column]);
+            }
+            combination
+        })
+        .collect()
+}
+
+/// Makes real vectors orthonormal with the modified Gram–Schmidt process,
+/// dropping those that depend linearly on the previous ones.
+fn orthonormalize(vectors: Vec<Vec<Complex64>>) -> Vec<Vec<Complex64>> {
+    let mut basis: Vec<Vec<Complex64>> = Vec::with_capacity(vectors.len());
+    for mut vector in vectors {
+        let initial_norm = vector.iter().map(|value| value.re * value.re).sum::<f64>().sqrt();
+        for previous in &basis {
+            let overlap: f64 = previous.iter().zip(&vector).map(|(a, b)| a.re * b.re).sum();
+            vector.iter_mut().zip(previous).for_each(|(value, previous)| *value -= previous * overlap);
+        }
+        let norm = vector.iter().map(|value| value.re * value.re).sum::<f64>().sqrt();
+        if norm > DEPENDENCE * initial_norm {
+            vector.iter_mut().for_each(|value| *value /= norm);
+            basis.push(vector);
+        }
+    }
+    basis
+}
+
+/// Diagonalizes a real symmetric matrix with the cyclic Jacobi method.
+///
+/// # Returns
+///
+/// The eigenvalues in ascending order, and a matrix whose columns are the
+/// corresponding orthonormal eigenvectors.
+fn symmetric_eigen(mut matrix: Vec<Vec<f64>>) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let size = matrix.len();
+    let mut vectors: Vec<Vec<f64>> = (0..size).map(|i| (0..size).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+    let total: f64 = matrix.iter().flatten().map(|value| value * value).sum();
+
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..size).flat_map(|i| (0..size).filter(move |&j| j != i).map(move |j| (i, j))).map(|(i, j)| matrix[i][j].powi(2)).sum();
+        if off_diagonal <= f64::EPSILON.powi(2) * total {
+            break;
+        }
+        for p in 0..size {
+            for q in p + 1..size {
+                if matrix[p][q] == 0.0 {
+                    continue;
+                }
+                // Rotation in the (p, q) plane that zeroes matrix[p][q]
+                let theta = (matrix[q][q] - matrix[p][p]) / (2.0 * matrix[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in matrix.iter_mut().chain(vectors.iter_mut()) {
+                    let (a, b) = (row[p], row[q]);
+                    row[p] = c * a - s * b;
+                    row[q] = s * a + c * b;
+                }
+                let (upper, lower) = matrix.split_at_mut(q);
+                for (a, b) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    (*a, *b) = (c * *a - s * *b, s * *a + c * *b);
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..size).collect();
+    order.sort_by(|&a, &b| matrix[a][a].total_cmp(&matrix[b][b]));
+    let values = order.iter().map(|&i| matrix[i][i]).collect();
+    let vectors = vectors.iter().map(|row| order.iter().map(|&i| row[i]).collect()).collect();
+    (values, vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let matrix = vec![vec![2.0, 1.0, 0.0], vec![1.0, 2.0, 0.0], vec![0.0, 0.0, -1.0]];
+        let (values, vectors) = symmetric_eigen(matrix.clone());
+        for (expected, value) in [-1.0, 1.0, 3.0].iter().zip(&values) {
+            assert!((expected - value).abs() < 1e-12);
+        }
+        // Every column satisfies A·v = λ·v
+        for (column, value) in values.iter().enumerate() {
+            for row in 0..3 {
+                let product: f64 = (0..3).map(|k| matrix[row][k] * vectors[k][column]).sum();
+                assert!((product - value * vectors[row][column]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_harmonic_oscillator_levels() {
+        // E = ω(n + ½) in one dimension and ω(nx + ny + 1) in two
+        let (n, size) = (64, 16.0);
+        let position = |i: usize| (i as f64 / n as f64 - 0.5) * size;
+        let potential_1d: Vec<f64> = (0..n).map(|i| 0.5 * position(i).powi(2)).collect();
+        let states = lowest_eigenstates(&Hamiltonian::new(n, size, 1, &potential_1d), 4, 0).unwrap();
+        for (level, (energy, state)) in states.iter().enumerate() {
+            assert!((energy - (level as f64 + 0.5)).abs() < 1e-6, "level {} has energy {}", level, energy);
+            assert!((state.iter().map(|value| value.norm_sqr()).sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+        // The ground state is even and positive
+        assert!(states[0].1[n / 2].re > 0.0);
+        assert!((states[0].1[n / 2 - 5].re - states[0].1[n / 2 + 5].re).abs() < 1e-6);
+
+        let (n, size) = (32, 12.0);
+        let position = |i: usize| (i as f64 / n as f64 - 0.5) * size;
+        let potential_2d: Vec<f64> = (0..n * n).map(|i| 0.5 * (position(i / n).powi(2) + position(i % n).powi(2))).collect();
+        let states = lowest_eigenstates(&Hamiltonian::new(n, size, 2, &potential_2d), 6, 0).unwrap();
+        for ((energy, _), expected) in states.iter().zip([1.0, 2.0, 2.0, 3.0, 3.0, 3.0]) {
+            assert!((energy - expected).abs() < 1e-6, "energy {} instead of {}", energy, expected);
+        }
+    }
+
+    #[test]
+    fn test_infinite_well_levels() {
+        // E = π²·k²/(2L²) for a well of width L, up to a few percent for the walls resolved to a grid point
+        let (n, size, width) = (128, 2.0, 1.0);
+        let potential: Vec<f64> = (0..n)
+            .map(|i| (i as f64 / n as f64 - 0.5) * size)
+            .map(|x| if x.abs() < width / 2.0 { 0.0 } else { f64::INFINITY })
+            .collect();
+        let hamiltonian = Hamiltonian::new(n, size, 1, &potential);
+        let states = lowest_eigenstates(&hamiltonian, 3, 7).unwrap();
+        let free_width = hamiltonian.free_points() as f64 * size / n as f64;
+        for (k, (energy, state)) in states.iter().enumerate() {
+            let expected = (std::f64::consts::PI * (k + 1) as f64 / free_width).powi(2) / 2.0;
+            assert!((energy - expected).abs() / expected < 0.05, "level {} has energy {} instead of {}", k, energy, expected);
+            assert!(state.iter().zip(&potential).all(|(value, potential)| potential.is_finite() || *value == Complex64::default()));
+        }
+
+        assert!(lowest_eigenstates(&hamiltonian, 0, 0).is_err());
+        assert!(lowest_eigenstates(&hamiltonian, hamiltonian.free_points(), 0).is_err());
+    }
+}
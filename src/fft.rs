@@ -0,0 +1,139 @@
+use num_complex::Complex64;
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+/// Transforms a sequence in place with the radix-2 fast Fourier transform.
+///
+/// The forward transform computes X_k = Σ x_j·e^(-2πi·jk/n); the inverse one
+/// uses the opposite sign and divides by n, so it undoes the forward transform.
+///
+/// # Arguments
+///
+/// * `data` - Sequence whose length is a power of two.
+/// * `inverse` - Whether to compute the inverse transform.
+pub fn fft(data: &mut [Complex64], inverse: bool) {
+    let n = data.len();
+    assert!(n.is_power_of_two(), "FFT length must be a power of two, got {}", n);
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    // Butterflies of doubling length
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut length = 2;
+    while length <= n {
+        let step = Complex64::from_polar(1.0, sign * 2.0 * PI / length as f64);
+        for start in (0..n).step_by(length) {
+            let mut twiddle = Complex64::new(1.0, 0.0);
+            for k in 0..length / 2 {
+                let even = data[start + k];
+                let odd = data[start + k + length / 2] * twiddle;
+                data[start + k] = even + odd;
+                data[start + k + length / 2] = even - odd;
+                twiddle *= step;
+            }
+        }
+        length <<= 1;
+    }
+
+    if inverse {
+        let scale = 1.0 / n as f64;
+        data.iter_mut().for_each(|value| *value *= scale);
+    }
+}
+
+/// Transforms a square grid stored row by row in both directions.
+///
+/// The rows are transformed in parallel on the current rayon pool. The result
+/// is left transposed, which saves two transposes when the caller only
+/// multiplies by a factor that is symmetric in the two frequencies and then
+/// transforms back: the inverse of a transposed grid is the original layout.
+///
+/// # Arguments
+///
+/// * `data` - Grid of n × n values with n a power of two.
+/// * `n` - Number of values per side.
+/// * `inverse` - Whether to compute the inverse transform.
+pub fn fft_2d_transposed(data: &mut [Complex64], n: usize, inverse: bool) {
+    data.par_chunks_mut(n).for_each(|row| fft(row, inverse));
+    transpose(data, n);
+    data.par_chunks_mut(n).for_each(|row| fft(row, inverse));
+}
+
+/// Angular wave number of a frequency index of a periodic grid, with the upper
+/// half of the indices standing for negative frequencies.
+///
+/// # Arguments
+///
+/// * `index` - Frequency index from 0 to n - 1.
+/// * `n` - Number of grid points.
+/// * `size` - Length of the periodic domain.
+pub fn wavenumber(index: usize, n: usize, size: f64) -> f64 {
+    let index = if index < n / 2 { index as f64 } else { index as f64 - n as f64 };
+    2.0 * PI * index / size
+}
+
+/// Transposes a square grid stored row by row in place.
+fn transpose(data: &mut [Complex64], n: usize) {
+    for row in 0..n {
+        for column in row + 1..n {
+            data.swap(row * n + column, column * n + row);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fft_matches_definition() {
+        let input: Vec<Complex64> = (0..8).map(|j| Complex64::new(j as f64, (j * j) as f64 * 0.5)).collect();
+        let mut output = input.clone();
+        fft(&mut output, false);
+        for (k, value) in output.iter().enumerate() {
+            let expected: Complex64 = input
+                .iter()
+                .enumerate()
+                .map(|(j, x)| x * Complex64::from_polar(1.0, -2.0 * PI * (j * k) as f64 / 8.0))
+                .sum();
+            assert!((value - expected).norm() < 1e-9);
+        }
+
+        fft(&mut output, true);
+        for (value, expected) in output.iter().zip(&input) {
+            assert!((value - expected).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_fft_2d_round_trip() {
+        let n = 4;
+        let input: Vec<Complex64> = (0..n * n).map(|i| Complex64::new(i as f64, -(i as f64).sqrt())).collect();
+        let mut data = input.clone();
+        fft_2d_transposed(&mut data, n, false);
+        // The constant frequency is the sum of all values
+        let sum: Complex64 = input.iter().sum();
+        assert!((data[0] - sum).norm() < 1e-9);
+        // A single frequency along x ends up in the first column after the transpose
+        let mut wave: Vec<Complex64> = (0..n * n).map(|i| Complex64::from_polar(1.0, 2.0 * PI * (i % n) as f64 / n as f64)).collect();
+        fft_2d_transposed(&mut wave, n, false);
+        assert!((wave[n] - Complex64::new((n * n) as f64, 0.0)).norm() < 1e-9);
+
+        fft_2d_transposed(&mut data, n, true);
+        for (value, expected) in data.iter().zip(&input) {
+            assert!((value - expected).norm() < 1e-9);
+        }
+    }
+}
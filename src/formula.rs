@@ -0,0 +1,609 @@
+use std::fmt;
+use std::str::FromStr;
+
+use num_complex::Complex64;
+
+/// Error produced when a formula cannot be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// 1-based column of the offending character.
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+/// Complex function that can be called from a formula.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Exp,
+    Log,
+    Sqrt,
+    Abs,
+    Conj,
+    Re,
+    Im,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        match name {
+            "sin" => Some(Function::Sin),
+            "cos" => Some(Function::Cos),
+            "tan" => Some(Function::Tan),
+            "sinh" => Some(Function::Sinh),
+            "cosh" => Some(Function::Cosh),
+            "tanh" => Some(Function::Tanh),
+            "exp" => Some(Function::Exp),
+            "log" | "ln" => Some(Function::Log),
+            "sqrt" => Some(Function::Sqrt),
+            "abs" => Some(Function::Abs),
+            "conj" => Some(Function::Conj),
+            "re" => Some(Function::Re),
+            "im" => Some(Function::Im),
+            _ => None,
+        }
+    }
+
+    fn apply(self, value: Complex64) -> Complex64 {
+        match self {
+            Function::Sin => value.sin(),
+            Function::Cos => value.cos(),
+            Function::Tan => value.tan(),
+            Function::Sinh => value.sinh(),
+            Function::Cosh => value.cosh(),
+            Function::Tanh => value.tanh(),
+            Function::Exp => value.exp(),
+            Function::Log => value.ln(),
+            Function::Sqrt => value.sqrt(),
+            Function::Abs => Complex64::new(value.norm(), 0.0),
+            Function::Conj => value.conj(),
+            Function::Re => Complex64::new(value.re, 0.0),
+            Function::Im => Complex64::new(value.im, 0.0),
+        }
+    }
+}
+
+/// Variable that can be referenced from a formula.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Variable {
+    Z,
+    C,
+    Pixel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+/// Parsed expression tree.
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Const(Complex64),
+    Var(Variable),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Box<Expr>),
+}
+
+impl Expr {
+    /// Returns the value of the expression if it has been folded to a constant.
+    fn constant_value(&self) -> Option<Complex64> {
+        match self {
+            Expr::Const(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Polynomial degree in z, or `None` if the expression is not a polynomial in z.
+    fn degree(&self) -> Option<f64> {
+        match self {
+            Expr::Const(_) => Some(0.0),
+            Expr::Var(Variable::Z) => Some(1.0),
+            Expr::Var(_) => Some(0.0),
+            Expr::Neg(inner) => inner.degree(),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs_degree, rhs_degree) = (lhs.degree()?, rhs.degree()?);
+                match op {
+                    BinaryOp::Add | BinaryOp::Sub => Some(lhs_degree.max(rhs_degree)),
+                    BinaryOp::Mul => Some(lhs_degree + rhs_degree),
+                    BinaryOp::Div if rhs_degree == 0.0 => Some(lhs_degree),
+                    BinaryOp::Pow if rhs_degree == 0.0 => match rhs.constant_value() {
+                        Some(exponent) if exponent.im == 0.0 && exponent.re > 0.0 => Some(lhs_degree * exponent.re),
+                        _ if lhs_degree == 0.0 => Some(0.0),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            Expr::Call(_, argument) if argument.degree()? == 0.0 => Some(0.0),
+            Expr::Call(..) => None,
+        }
+    }
+}
+
+/// Instruction of the compiled stack program.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Const(Complex64),
+    Var(Variable),
+    Neg,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// Power with a complex exponent taken from the stack.
+    Pow,
+    /// Power with a constant integer exponent.
+    Powi(i32),
+    /// Power with a constant real exponent.
+    Powf(f64),
+    Call(Function),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    /// Number with an `i` suffix, e.g. `0.5i`.
+    Imaginary(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    End,
+}
+
+/// Splits a formula into tokens, each tagged with its 1-based column.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let ch = chars[pos];
+        let column = pos + 1;
+        if ch.is_whitespace() {
+            pos += 1;
+        } else if ch.is_ascii_digit() || ch == '.' {
+            let start = pos;
+            while pos < chars.len() && (chars[pos].is_ascii_digit() || chars[pos] == '.') {
+                pos += 1;
+            }
+            // Exponent, e.g. 1e-3
+            if pos < chars.len() && (chars[pos] == 'e' || chars[pos] == 'E') {
+                let mut end = pos + 1;
+                if end < chars.len() && (chars[end] == '+' || chars[end] == '-') {
+                    end += 1;
+                }
+                if end < chars.len() && chars[end].is_ascii_digit() {
+                    pos = end;
+                    while pos < chars.len() && chars[pos].is_ascii_digit() {
+                        pos += 1;
+                    }
+                }
+            }
+            let literal: String = chars[start..pos].iter().collect();
+            let value = literal.parse::<f64>().map_err(|_| ParseError { column, message: format!("Invalid number '{}'", literal) })?;
+            if pos < chars.len() && chars[pos] == 'i' && !chars.get(pos + 1).is_some_and(|next| next.is_alphanumeric() || *next == '_') {
+                pos += 1;
+                tokens.push((Token::Imaginary(value), column));
+            } else {
+                tokens.push((Token::Number(value), column));
+            }
+        } else if ch.is_alphabetic() || ch == '_' {
+            let start = pos;
+            while pos < chars.len() && (chars[pos].is_alphanumeric() || chars[pos] == '_') {
+                pos += 1;
+            }
+            tokens.push((Token::Ident(chars[start..pos].iter().collect()), column));
+        } else {
+            let token = match ch {
+                '+' | '-' | '*' | '/' | '^' => Token::Op(ch),
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                _ => return Err(ParseError { column, message: format!("Unexpected character '{}'", ch) }),
+            };
+            tokens.push((token, column));
+            pos += 1;
+        }
+    }
+
+    tokens.push((Token::End, chars.len() + 1));
+    Ok(tokens)
+}
+
+/// Recursive-descent parser over the token list.
+///
+/// Grammar (lowest to highest precedence):
+///
+/// ```text
+/// expr    := term (('+' | '-') term)*
+/// term    := unary (('*' | '/') unary)*
+/// unary   := ('-' | '+') unary | power
+/// power   := primary ('^' unary)?
+/// primary := number | name | name '(' expr ')' | '(' expr ')'
+/// ```
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn column(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> (Token, usize) {
+        let token = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, ParseError> {
+        Err(ParseError { column: self.column(), message })
+    }
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Token::Op('+') => BinaryOp::Add,
+                Token::Op('-') => BinaryOp::Sub,
+                _ => return Ok(lhs),
+            };
+            self.next();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Token::Op('*') => BinaryOp::Mul,
+                Token::Op('/') => BinaryOp::Div,
+                _ => return Ok(lhs),
+            };
+            self.next();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        match self.peek() {
+            Token::Op('-') => {
+                self.next();
+                Ok(Expr::Neg(Box::new(self.unary()?)))
+            }
+            Token::Op('+') => {
+                self.next();
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Expr, ParseError> {
+        let base = self.primary()?;
+        if *self.peek() == Token::Op('^') {
+            self.next();
+            // Right associative: z^2^3 = z^(2^3)
+            let exponent = self.unary()?;
+            return Ok(Expr::Binary(BinaryOp::Pow, Box::new(base), Box::new(exponent)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let (token, column) = self.next();
+        match token {
+            Token::Number(value) => Ok(Expr::Const(Complex64::new(value, 0.0))),
+            Token::Imaginary(value) => Ok(Expr::Const(Complex64::new(0.0, value))),
+            Token::LParen => {
+                let inner = self.expr()?;
+                self.expect_rparen()?;
+                Ok(inner)
+            }
+            Token::Ident(name) => {
+                if *self.peek() == Token::LParen {
+                    let function = match Function::from_name(&name) {
+                        Some(function) => function,
+                        None => return Err(ParseError { column, message: format!("Unknown function '{}'", name) }),
+                    };
+                    self.next();
+                    let argument = self.expr()?;
+                    self.expect_rparen()?;
+                    return Ok(Expr::Call(function, Box::new(argument)));
+                }
+                match name.as_str() {
+                    "z" => Ok(Expr::Var(Variable::Z)),
+                    "c" => Ok(Expr::Var(Variable::C)),
+                    "pixel" => Ok(Expr::Var(Variable::Pixel)),
+                    "x" => Ok(Expr::Call(Function::Re, Box::new(Expr::Var(Variable::Z)))),
+                    "y" => Ok(Expr::Call(Function::Im, Box::new(Expr::Var(Variable::Z)))),
+                    "i" => Ok(Expr::Const(Complex64::new(0.0, 1.0))),
+                    "pi" => Ok(Expr::Const(Complex64::new(std::f64::consts::PI, 0.0))),
+                    "e" => Ok(Expr::Const(Complex64::new(std::f64::consts::E, 0.0))),
+                    _ => Err(ParseError { column, message: format!("Unknown variable '{}'", name) }),
+                }
+            }
+            Token::End => Err(ParseError { column, message: "Unexpected end of formula".to_string() }),
+            Token::RParen => Err(ParseError { column, message: "Unexpected ')'".to_string() }),
+            Token::Op(op) => Err(ParseError { column, message: format!("Unexpected operator '{}'", op) }),
+        }
+    }
+
+    fn expect_rparen(&mut self) -> Result<(), ParseError> {
+        match self.peek() {
+            Token::RParen => {
+                self.next();
+                Ok(())
+            }
+            _ => self.error("Expected ')'".to_string()),
+        }
+    }
+}
+
+/// Folds subexpressions that don't depend on any variable into constants.
+fn fold_constants(expr: Expr) -> Expr {
+    match expr {
+        Expr::Neg(inner) => match fold_constants(*inner) {
+            Expr::Const(value) => Expr::Const(-value),
+            inner => Expr::Neg(Box::new(inner)),
+        },
+        Expr::Binary(op, lhs, rhs) => match (fold_constants(*lhs), fold_constants(*rhs)) {
+            (Expr::Const(a), Expr::Const(b)) => Expr::Const(match op {
+                BinaryOp::Add => a + b,
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a * b,
+                BinaryOp::Div => a / b,
+                BinaryOp::Pow => a.powc(b),
+            }),
+            (lhs, rhs) => Expr::Binary(op, Box::new(lhs), Box::new(rhs)),
+        },
+        Expr::Call(function, argument) => match fold_constants(*argument) {
+            Expr::Const(value) => Expr::Const(function.apply(value)),
+            argument => Expr::Call(function, Box::new(argument)),
+        },
+        expr => expr,
+    }
+}
+
+/// Emits the stack program for an expression in postfix order.
+fn compile(expr: &Expr, ops: &mut Vec<Op>) {
+    match expr {
+        Expr::Const(value) => ops.push(Op::Const(*value)),
+        Expr::Var(variable) => ops.push(Op::Var(*variable)),
+        Expr::Neg(inner) => {
+            compile(inner, ops);
+            ops.push(Op::Neg);
+        }
+        Expr::Binary(BinaryOp::Pow, base, exponent) if exponent.constant_value().is_some_and(|e| e.im == 0.0) => {
+            let exponent = exponent.constant_value().unwrap().re;
+            compile(base, ops);
+            if exponent.fract() == 0.0 && exponent.abs() <= i32::MAX as f64 {
+                ops.push(Op::Powi(exponent as i32));
+            } else {
+                ops.push(Op::Powf(exponent));
+            }
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            compile(lhs, ops);
+            compile(rhs, ops);
+            ops.push(match op {
+                BinaryOp::Add => Op::Add,
+                BinaryOp::Sub => Op::Sub,
+                BinaryOp::Mul => Op::Mul,
+                BinaryOp::Div => Op::Div,
+                BinaryOp::Pow => Op::Pow,
+            });
+        }
+        Expr::Call(function, argument) => {
+            compile(argument, ops);
+            ops.push(Op::Call(*function));
+        }
+    }
+}
+
+/// User-defined iteration formula, compiled to a postfix stack program.
+///
+/// Formulas are complex-valued expressions in the variables `z` (current
+/// iterate), `c` (parameter) and `pixel` (the point of the plane being rendered),
+/// with `x` and `y` as shorthands for `re(z)` and `im(z)`.
+/// They support `+ - * / ^`, parentheses, the constants `i`, `pi` and `e`,
+/// imaginary literals such as `0.5i` and the functions `sin`, `cos`, `tan`,
+/// `sinh`, `cosh`, `tanh`, `exp`, `log`/`ln`, `sqrt`, `abs`, `conj`, `re` and `im`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    ops: Vec<Op>,
+    stack_size: usize,
+    degree: Option<f64>,
+}
+
+impl Formula {
+    /// Parses and compiles a formula.
+    pub fn parse(text: &str) -> Result<Formula, ParseError> {
+        let mut parser = Parser { tokens: tokenize(text)?, pos: 0 };
+        let expr = parser.expr()?;
+        if *parser.peek() != Token::End {
+            return parser.error("Unexpected input after end of formula".to_string());
+        }
+
+        let expr = fold_constants(expr);
+        let degree = expr.degree();
+        let mut ops = Vec::new();
+        compile(&expr, &mut ops);
+
+        // Track the deepest stack the program can reach
+        let mut depth: usize = 0;
+        let mut stack_size = 0;
+        for op in &ops {
+            match op {
+                Op::Const(_) | Op::Var(_) => depth += 1,
+                Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Pow => depth -= 1,
+                Op::Neg | Op::Powi(_) | Op::Powf(_) | Op::Call(_) => {}
+            }
+            stack_size = stack_size.max(depth);
+        }
+
+        Ok(Formula { ops, stack_size, degree })
+    }
+
+    /// Polynomial degree of the formula in `z`, if it is a polynomial.
+    pub fn degree(&self) -> Option<f64> {
+        self.degree
+    }
+
+    /// Creates an evaluation stack large enough for this formula.
+    ///
+    /// The stack can be reused across calls to `eval` to avoid allocating per iteration.
+    pub fn new_stack(&self) -> Vec<Complex64> {
+        Vec::with_capacity(self.stack_size)
+    }
+
+    /// Evaluates the formula.
+    ///
+    /// # Arguments
+    ///
+    /// * `z` - Current iterate.
+    /// * `c` - Parameter of the iteration.
+    /// * `pixel` - Point of the plane being rendered.
+    /// * `stack` - Scratch stack from `new_stack`.
+    pub fn eval(&self, z: Complex64, c: Complex64, pixel: Complex64, stack: &mut Vec<Complex64>) -> Complex64 {
+        stack.clear();
+        for op in &self.ops {
+            match *op {
+                Op::Const(value) => stack.push(value),
+                Op::Var(Variable::Z) => stack.push(z),
+                Op::Var(Variable::C) => stack.push(c),
+                Op::Var(Variable::Pixel) => stack.push(pixel),
+                Op::Neg => {
+                    let top = stack.last_mut().unwrap();
+                    *top = -*top;
+                }
+                Op::Powi(exponent) => {
+                    let top = stack.last_mut().unwrap();
+                    *top = top.powi(exponent);
+                }
+                Op::Powf(exponent) => {
+                    let top = stack.last_mut().unwrap();
+                    *top = top.powf(exponent);
+                }
+                Op::Call(function) => {
+                    let top = stack.last_mut().unwrap();
+                    *top = function.apply(*top);
+                }
+                Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Pow => {
+                    let rhs = stack.pop().unwrap();
+                    let lhs = stack.last_mut().unwrap();
+                    *lhs = match *op {
+                        Op::Add => *lhs + rhs,
+                        Op::Sub => *lhs - rhs,
+                        Op::Mul => *lhs * rhs,
+                        Op::Div => *lhs / rhs,
+                        _ => lhs.powc(rhs),
+                    };
+                }
+            }
+        }
+        stack[0]
+    }
+}
+
+impl FromStr for Formula {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Formula, ParseError> {
+        Formula::parse(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str, z: Complex64, c: Complex64) -> Complex64 {
+        let formula = Formula::parse(text).unwrap();
+        let mut stack = formula.new_stack();
+        formula.eval(z, c, c, &mut stack)
+    }
+
+    fn assert_close(a: Complex64, b: Complex64) {
+        assert!((a - b).norm() < 1e-12, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_eval_polynomials() {
+        let z = Complex64::new(0.3, -0.7);
+        let c = Complex64::new(-0.1, 0.4);
+        assert_close(eval("z^2 + c", z, c), z * z + c);
+        assert_close(eval("z^3 - z + c", z, c), z * z * z - z + c);
+        assert_close(eval("-z^2", z, c), -(z * z));
+        assert_close(eval("2^3^2", z, c), Complex64::new(512.0, 0.0));
+        assert_close(eval("(1 + 2i) * z / 4", z, c), Complex64::new(1.0, 2.0) * z / 4.0);
+        assert_close(eval("z^2.5", z, c), z.powf(2.5));
+        assert_close(eval("z^(1+i)", z, c), z.powc(Complex64::new(1.0, 1.0)));
+        assert_close(eval("1e-1 * 2.5E1", z, c), Complex64::new(2.5, 0.0));
+    }
+
+    #[test]
+    fn test_eval_functions() {
+        let z = Complex64::new(0.3, -0.7);
+        let c = Complex64::new(-0.1, 0.4);
+        assert_close(eval("sin(z) * c", z, c), z.sin() * c);
+        assert_close(eval("exp(conj(z)) + ln(c)", z, c), z.conj().exp() + c.ln());
+        assert_close(eval("abs(z) + re(c) + im(c) * i", z, c), Complex64::new(z.norm(), 0.0) + c);
+        assert_close(eval("cos(pi)", z, c), Complex64::new(-1.0, 0.0));
+        assert_close(eval("pixel", z, c), c);
+        assert_close(eval("x^2 + y", z, c), Complex64::new(0.3 * 0.3 - 0.7, 0.0));
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        let formula = Formula::parse("z^2 + (1 + 2) * sin(0)").unwrap();
+        assert_eq!(formula.ops, vec![Op::Var(Variable::Z), Op::Powi(2), Op::Const(Complex64::new(0.0, 0.0)), Op::Add]);
+        assert_eq!(formula.stack_size, 2);
+    }
+
+    #[test]
+    fn test_degree() {
+        assert_eq!(Formula::parse("z^2 + c").unwrap().degree(), Some(2.0));
+        assert_eq!(Formula::parse("z^3 - z + c").unwrap().degree(), Some(3.0));
+        assert_eq!(Formula::parse("(z*z)^2.5 / 2").unwrap().degree(), Some(5.0));
+        assert_eq!(Formula::parse("sin(z) * c").unwrap().degree(), None);
+    }
+
+    #[test]
+    fn test_parse_errors_report_column() {
+        let error = |text: &str| Formula::parse(text).unwrap_err();
+        assert_eq!(error("z^2 + $").column, 7);
+        assert_eq!(error("z^2 + w").column, 7);
+        assert_eq!(error("foo(z) + c").column, 1);
+        assert_eq!(error("(z + c").column, 7);
+        assert_eq!(error("z^2 +").column, 6);
+        assert_eq!(error("z c").column, 3);
+        assert_eq!(error("z * * c").column, 5);
+        assert_eq!(error("z^2 + $").to_string(), "column 7: Unexpected character '$'");
+    }
+}
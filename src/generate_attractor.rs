@@ -0,0 +1,301 @@
+use image::{ImageBuffer, Rgba, RgbaImage};
+
+use crate::decimal::Decimal;
+use crate::generate_buddhabrot::tone_peak;
+use crate::generate_mandelbrot::{coordinate_mapper, pixel_mapper, save_with_zoom_text};
+use crate::render_parallel::accumulate_chunks;
+use crate::rng::Rng;
+
+/// Number of points traced per chunk, from one random starting point.
+const CHUNK_SAMPLES: u64 = 1 << 16;
+
+/// Iterations discarded at the start of every chunk while the orbit falls onto the attractor.
+const TRANSIENT_ITERATIONS: u32 = 1000;
+
+/// Compression of the logarithmic tone mapping: larger values bring out faint
+/// filaments, smaller values keep the shading of dense regions.
+const TONE_CONTRAST: f64 = 20.0;
+
+/// Strange attractor traced into a density histogram.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttractorKind {
+    /// x ← sin(a·y) + c·cos(a·x), y ← sin(b·x) + d·cos(b·y).
+    Clifford,
+    /// Peter de Jong: x ← sin(a·y) - cos(b·x), y ← sin(c·x) - cos(d·y).
+    DeJong,
+    /// Johnny Svensson: x ← d·sin(a·x) - sin(b·y), y ← c·cos(a·x) + cos(b·y).
+    Svensson,
+    /// Barry Martin: x ← y - sign(x)·√|b·x - c|, y ← a - x.
+    Hopalong,
+    /// Lorenz system x' = σ(y - x), y' = x(ρ - z) - y, z' = xy - βz, projected onto (x, z).
+    Lorenz,
+    /// Rössler system x' = -y - z, y' = x + ay, z' = b + z(x - c), projected onto (x, y).
+    Rossler,
+}
+
+impl AttractorKind {
+    /// Parses an attractor name as used on the command line and in config files.
+    ///
+    /// # Returns
+    ///
+    /// The attractor, or an error message if the name is unknown.
+    pub fn from_name(name: &str) -> Result<AttractorKind, String> {
+        match name {
+            "clifford" => Ok(AttractorKind::Clifford),
+            "dejong" => Ok(AttractorKind::DeJong),
+            "svensson" => Ok(AttractorKind::Svensson),
+            "hopalong" => Ok(AttractorKind::Hopalong),
+            "lorenz" => Ok(AttractorKind::Lorenz),
+            "rossler" => Ok(AttractorKind::Rossler),
+            _ => Err(format!("Unknown attractor: {}", name)),
+        }
+    }
+
+    /// Returns the classic parameters of the attractor.
+    pub fn default_params(self) -> Vec<f64> {
+        match self {
+            AttractorKind::Clifford => vec![-1.4, 1.6, 1.0, 0.7],
+            AttractorKind::DeJong => vec![1.4, -2.3, 2.4, -2.1],
+            AttractorKind::Svensson => vec![1.5, -1.8, 1.6, 0.9],
+            AttractorKind::Hopalong => vec![2.0, 1.0, 0.0],
+            AttractorKind::Lorenz => vec![10.0, 28.0, 8.0 / 3.0],
+            AttractorKind::Rossler => vec![0.2, 0.2, 5.7],
+        }
+    }
+
+    /// Integration time step of the ODE systems.
+    fn time_step(self) -> f64 {
+        match self {
+            AttractorKind::Lorenz => 0.005,
+            AttractorKind::Rossler => 0.02,
+            _ => 1.0,
+        }
+    }
+
+    /// Advances a state by one iteration of the map or one RK4 step of the ODE.
+    fn step(self, p: &[f64], [x, y, z]: [f64; 3]) -> [f64; 3] {
+        match self {
+            AttractorKind::Clifford => [(p[0] * y).sin() + p[2] * (p[0] * x).cos(), (p[1] * x).sin() + p[3] * (p[1] * y).cos(), 0.0],
+            AttractorKind::DeJong => [(p[0] * y).sin() - (p[1] * x).cos(), (p[2] * x).sin() - (p[3] * y).cos(), 0.0],
+            AttractorKind::Svensson => [p[3] * (p[0] * x).sin() - (p[1] * y).sin(), p[2] * (p[0] * x).cos() + (p[1] * y).cos(), 0.0],
+            AttractorKind::Hopalong => [y - x.signum() * (p[1] * x - p[2]).abs().sqrt(), p[0] - x, 0.0],
+            AttractorKind::Lorenz => rk4_step([x, y, z], self.time_step(), |[x, y, z]| [p[0] * (y - x), x * (p[1] - z) - y, x * y - p[2] * z]),
+            AttractorKind::Rossler => rk4_step([x, y, z], self.time_step(), |[x, y, z]| [-y - z, x + p[0] * y, p[1] + z * (x - p[2])]),
+        }
+    }
+
+    /// Projects a state onto the image plane.
+    fn project(self, [x, y, z]: [f64; 3]) -> (f64, f64) {
+        match self {
+            AttractorKind::Lorenz => (x, z),
+            _ => (x, y),
+        }
+    }
+}
+
+/// Advances the ODE s' = f(s) by one classic fourth-order Runge-Kutta step.
+fn rk4_step(s: [f64; 3], dt: f64, f: impl Fn([f64; 3]) -> [f64; 3]) -> [f64; 3] {
+    let add = |a: [f64; 3], b: [f64; 3], h: f64| [a[0] + h * b[0], a[1] + h * b[1], a[2] + h * b[2]];
+    let k1 = f(s);
+    let k2 = f(add(s, k1, dt / 2.0));
+    let k3 = f(add(s, k2, dt / 2.0));
+    let k4 = f(add(s, k3, dt));
+    let slope = [
+        (k1[0] + 2.0 * k2[0] + 2.0 * k3[0] + k4[0]) / 6.0,
+        (k1[1] + 2.0 * k2[1] + 2.0 * k3[1] + k4[1]) / 6.0,
+        (k1[2] + 2.0 * k2[2] + 2.0 * k3[2] + k4[2]) / 6.0,
+    ];
+    add(s, slope, dt)
+}
+
+/// Parses the parameters of an attractor, separated by ';'.
+///
+/// # Arguments
+///
+/// * `kind` - The attractor the parameters belong to.
+/// * `text` - The parameter list, or `None` for the classic parameters.
+///
+/// # Returns
+///
+/// The parameters, or an error message for invalid numbers or a wrong count.
+pub fn parse_params(kind: AttractorKind, text: Option<&str>) -> Result<Vec<f64>, String> {
+    let defaults = kind.default_params();
+    let Some(text) = text else {
+        return Ok(defaults);
+    };
+    let params = text
+        .split(';')
+        .map(|param| param.trim().parse::<f64>().map_err(|e| format!("Invalid parameter '{}': {}", param.trim(), e)))
+        .collect::<Result<Vec<f64>, String>>()?;
+    if params.len() != defaults.len() {
+        return Err(format!("Expected {} parameters separated by ';', got {}", defaults.len(), params.len()));
+    }
+    Ok(params)
+}
+
+/// Parses the attractor parameters, exiting the process with an error message if they
+/// or the attractor name are invalid.
+///
+/// # Arguments
+///
+/// * `attractor` - Name of the attractor.
+/// * `text` - The parameter list, or `None` for the classic parameters.
+pub fn params_or_exit(attractor: &str, text: Option<&str>) -> Vec<f64> {
+    AttractorKind::from_name(attractor).and_then(|kind| parse_params(kind, text)).unwrap_or_else(|e| {
+        eprintln!("Invalid attractor: {}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Traces the orbit of one chunk and passes its projected points to `plot`.
+///
+/// The orbit starts at a random point near the origin and skips the transient.
+/// It stops early if it diverges.
+fn trace_chunk(kind: AttractorKind, params: &[f64], rng: &mut Rng, points: u64, mut plot: impl FnMut(f64, f64)) {
+    let mut state = [rng.range(-0.5, 0.5), rng.range(-0.5, 0.5), rng.range(-0.5, 0.5)];
+    for _ in 0..TRANSIENT_ITERATIONS {
+        state = kind.step(params, state);
+    }
+    for _ in 0..points {
+        state = kind.step(params, state);
+        if !state.iter().all(|value| value.is_finite()) {
+            return;
+        }
+        let (x, y) = kind.project(state);
+        plot(x, y);
+    }
+}
+
+/// Generates a density image of a strange attractor.
+///
+/// The orbit is traced in chunks from random starting points, and every point
+/// is counted in the pixel it falls into (see `render_density`).
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path to save the generated image.
+/// * `samples` - Number of orbit points to plot.
+/// * `seed` - Seed of the random starting points.
+/// * `attractor` - Attractor to trace: 'clifford', 'dejong', 'svensson', 'hopalong', 'lorenz' or 'rossler'.
+/// * `params` - Parameters of the attractor, as returned by `params_or_exit`, which also checks the name.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_attractor(width: u32, height: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, samples: u64, seed: u64, attractor: &str, params: &[f64]) {
+    let kind = AttractorKind::from_name(attractor).expect("Attractor name is checked by params_or_exit");
+    let mut imgbuf = render_density(width, height, center_x, center_y, zoom, m_size, samples, seed, |rng, points, plot| trace_chunk(kind, params, rng, points, plot));
+
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+/// Renders the density of points traced in random chunks as dark ink on white.
+///
+/// Every chunk gets its own random stream, so the image only depends on the
+/// seed, not on the number of threads. Counts are tone-mapped logarithmically,
+/// with the densest 0.1% of the pixels clipped. The view is framed like the
+/// complex plane of the other functions, except that y grows upward.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `samples` - Total number of points to trace.
+/// * `seed` - Seed of the random streams.
+/// * `trace` - Traces the given number of points of one chunk and passes each to the plot callback.
+#[allow(clippy::too_many_arguments)]
+pub fn render_density(width: u32, height: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, samples: u64, seed: u64, trace: impl Fn(&mut Rng, u64, &mut dyn FnMut(f64, f64)) + Sync) -> RgbaImage {
+    let center_x = center_x.to_f64();
+    let center_y = center_y.to_f64();
+    let (_, center) = coordinate_mapper(0, height / 2, width, height, zoom, center_x, center_y, m_size);
+    let pixel_count = width as usize * height as usize;
+
+    let histogram = accumulate_chunks(samples.div_ceil(CHUNK_SAMPLES), pixel_count, |chunk, histogram: &mut [u64]| {
+        let mut rng = Rng::for_stream(seed, chunk);
+        trace(&mut rng, CHUNK_SAMPLES.min(samples - chunk * CHUNK_SAMPLES), &mut |x, y| {
+            // Mirror y about the center so that it grows upward
+            if let Some((px, py)) = pixel_mapper(x, 2.0 * center - y, width, height, zoom, center_x, center_y, m_size) {
+                histogram[py as usize * width as usize + px as usize] += 1;
+            }
+        });
+    });
+
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
+    let peak = tone_peak(&histogram) as f64;
+    for (pixel, &count) in imgbuf.pixels_mut().zip(&histogram) {
+        let level = ((1.0 + TONE_CONTRAST * count as f64 / peak).ln() / (1.0 + TONE_CONTRAST).ln()).min(1.0);
+        let gray = (255.0 * (1.0 - level)) as u8;
+        *pixel = Rgba([gray, gray, gray, 255]);
+    }
+    imgbuf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attractor_from_name() {
+        assert_eq!(AttractorKind::from_name("clifford"), Ok(AttractorKind::Clifford));
+        assert_eq!(AttractorKind::from_name("dejong"), Ok(AttractorKind::DeJong));
+        assert_eq!(AttractorKind::from_name("svensson"), Ok(AttractorKind::Svensson));
+        assert_eq!(AttractorKind::from_name("hopalong"), Ok(AttractorKind::Hopalong));
+        assert_eq!(AttractorKind::from_name("lorenz"), Ok(AttractorKind::Lorenz));
+        assert_eq!(AttractorKind::from_name("rossler"), Ok(AttractorKind::Rossler));
+        assert_eq!(AttractorKind::from_name("henon"), Err("Unknown attractor: henon".to_string()));
+    }
+
+    #[test]
+    fn test_parse_params() {
+        assert_eq!(parse_params(AttractorKind::Clifford, None), Ok(vec![-1.4, 1.6, 1.0, 0.7]));
+        assert_eq!(parse_params(AttractorKind::Lorenz, Some("10; 28 ;2.5")), Ok(vec![10.0, 28.0, 2.5]));
+        assert!(parse_params(AttractorKind::Clifford, Some("1; 2; 3")).is_err());
+        assert!(parse_params(AttractorKind::Rossler, Some("0.2; x; 5.7")).is_err());
+    }
+
+    #[test]
+    fn test_rk4_step() {
+        // s' = s grows by e^dt per step, accurate to the fifth order
+        let s = rk4_step([1.0, 2.0, -1.0], 0.1, |s| s);
+        let growth = 0.1f64.exp();
+        assert!((s[0] - growth).abs() < 1e-6);
+        assert!((s[1] - 2.0 * growth).abs() < 1e-6);
+        assert!((s[2] + growth).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_attractors_stay_bounded() {
+        // Every classic attractor traces a bounded orbit that doesn't collapse onto a point
+        for name in ["clifford", "dejong", "svensson", "hopalong", "lorenz", "rossler"] {
+            let kind = AttractorKind::from_name(name).unwrap();
+            let mut points = Vec::new();
+            trace_chunk(kind, &kind.default_params(), &mut Rng::new(1), 1000, |x, y| points.push((x, y)));
+            assert_eq!(points.len(), 1000, "{}", name);
+            assert!(points.iter().all(|(x, y)| x.abs() < 100.0 && y.abs() < 100.0), "{}", name);
+            assert!(points.iter().any(|&point| point != points[0]), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_render_density() {
+        let origin = Decimal::from(0.0);
+        // A single point is inked black in the upper right quadrant, since y grows upward
+        let imgbuf = render_density(21, 21, &origin, &origin, 1.0, 2.0, 1000, 0, |_, points, plot| (0..points).for_each(|_| plot(0.5, 0.5)));
+        let inked: Vec<(u32, u32, Rgba<u8>)> = imgbuf.enumerate_pixels().filter(|(_, _, pixel)| pixel[0] < 255).map(|(x, y, pixel)| (x, y, *pixel)).collect();
+        assert_eq!(inked.len(), 1);
+        let (x, y, pixel) = inked[0];
+        assert!(x > 10 && y < 10, "({}, {})", x, y);
+        assert_eq!(pixel, Rgba([0, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,250 @@
+use image::{ImageBuffer, Rgba, RgbaImage};
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+use crate::decimal::Decimal;
+use crate::generate_mandelbrot::{coordinate_mapper, pixel_mapper, save_with_zoom_text, view_scale};
+
+/// Number of parameter values iterated per image column.
+const SUBCOLUMNS: u32 = 4;
+
+/// Starting value of every orbit.
+///
+/// The critical point ½ is avoided: the logistic map sends it to the fixed point 0 at r = 4.
+const START_X: f64 = 0.3;
+
+/// Darkness of a pixel per unit of orbit density in x, where density 1 means the
+/// orbit points are spread evenly over the unit interval.
+const DENSITY_GAIN: f64 = 0.5;
+
+/// One-dimensional map x ← f(r, x) whose attractor is plotted against r.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BifurcationMap {
+    /// x ← r·x·(1 - x), chaotic up to r = 4.
+    Logistic,
+    /// x ← r·sin(πx), chaotic up to r = 1.
+    Sine,
+    /// x ← r·min(x, 1 - x), chaotic up to r = 2.
+    Tent,
+}
+
+impl BifurcationMap {
+    /// Parses a map name as used on the command line and in config files.
+    ///
+    /// # Returns
+    ///
+    /// The map, or an error message if the name is unknown.
+    pub fn from_name(name: &str) -> Result<BifurcationMap, String> {
+        match name {
+            "logistic" => Ok(BifurcationMap::Logistic),
+            "sine" => Ok(BifurcationMap::Sine),
+            "tent" => Ok(BifurcationMap::Tent),
+            _ => Err(format!("Unknown bifurcation map: {}", name)),
+        }
+    }
+
+    /// Applies the map with parameter `r` to `x`.
+    pub fn apply(self, r: f64, x: f64) -> f64 {
+        match self {
+            BifurcationMap::Logistic => r * x * (1.0 - x),
+            BifurcationMap::Sine => r * (PI * x).sin(),
+            BifurcationMap::Tent => r * x.min(1.0 - x),
+        }
+    }
+}
+
+/// Iterates a map from `START_X` and passes the orbit points after the transient to `plot`.
+///
+/// Orbits that diverge stop as soon as they leave the finite numbers.
+///
+/// # Arguments
+///
+/// * `map` - The map to iterate.
+/// * `r` - Parameter of the map.
+/// * `transient` - Number of leading iterations that are not plotted.
+/// * `samples` - Number of iterations plotted after the transient.
+/// * `plot` - Called with every plotted orbit point.
+fn orbit_points(map: BifurcationMap, r: f64, transient: u32, samples: u32, mut plot: impl FnMut(f64)) {
+    let mut x = START_X;
+    // Counted in u64 so that the sum of two u32 counts can't overflow
+    for iteration in 0..transient as u64 + samples as u64 {
+        x = map.apply(r, x);
+        if !x.is_finite() {
+            return;
+        }
+        if iteration >= transient as u64 {
+            plot(x);
+        }
+    }
+}
+
+/// Converts the number of orbit points in a pixel to a shade of gray.
+///
+/// # Arguments
+///
+/// * `count` - Orbit points that fell into the pixel.
+/// * `plotted` - Orbit points plotted per column.
+/// * `pixel_height` - Range of x covered by one pixel.
+fn density_color(count: u64, plotted: u64, pixel_height: f64) -> Rgba<u8> {
+    // Normalizing by the pixel height keeps the shading independent of the zoom
+    let density = count as f64 / plotted.max(1) as f64 / pixel_height;
+    let gray = (255.0 * (-DENSITY_GAIN * density).exp()) as u8;
+    Rgba([gray, gray, gray, 255])
+}
+
+/// Generates a bifurcation diagram: the attractor of a 1D map for every value of r.
+///
+/// The parameter r runs along the horizontal axis and the orbit value x along
+/// the vertical axis, framed like the complex plane of the other functions
+/// except that x grows upward. Every column iterates several values of r across
+/// its width, and each pixel is shaded by the share of orbit points that fall
+/// into it.
+///
+/// Exits the process with an error message if the map is unknown.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path to save the generated image.
+/// * `map` - Map to iterate: 'logistic', 'sine' or 'tent'.
+/// * `transient` - Iterations discarded per orbit before points are plotted.
+/// * `samples` - Orbit points plotted per column.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_bifurcation(width: u32, height: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, map: &str, transient: u32, samples: u32) {
+    let map = BifurcationMap::from_name(map).unwrap_or_else(|e| {
+        eprintln!("Invalid bifurcation map: {}", e);
+        std::process::exit(1);
+    });
+    let mut imgbuf = render_bifurcation(width, height, center_x, center_y, zoom, m_size, map, transient, samples);
+
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+/// Plots the long-run orbit of `map` for the parameters r along x, with the
+/// orbit value growing upward, and shades every pixel by its share of the
+/// column's orbit points.
+#[allow(clippy::too_many_arguments)]
+fn render_bifurcation(width: u32, height: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, map: BifurcationMap, transient: u32, samples: u32) -> RgbaImage {
+    let center_x = center_x.to_f64();
+    let center_y = center_y.to_f64();
+    let (scale_x, scale_y) = view_scale(width, height, zoom, m_size);
+    let pixel_width = scale_x / width as f64;
+    let pixel_height = scale_y / height as f64;
+    let (_, center) = coordinate_mapper(0, height / 2, width, height, zoom, center_x, center_y, m_size);
+    let subcolumn_samples = samples.div_ceil(SUBCOLUMNS);
+
+    let columns: Vec<Vec<u64>> = (0..width)
+        .into_par_iter()
+        .map(|x| {
+            let mut counts = vec![0u64; height as usize];
+            let (left, _) = coordinate_mapper(x, 0, width, height, zoom, center_x, center_y, m_size);
+            for subcolumn in 0..SUBCOLUMNS {
+                let r = left + (subcolumn as f64 + 0.5) / SUBCOLUMNS as f64 * pixel_width;
+                orbit_points(map, r, transient, subcolumn_samples, |value| {
+                    // Mirror x about the center so that it grows upward
+                    if let Some((_, y)) = pixel_mapper(r, 2.0 * center - value, width, height, zoom, center_x, center_y, m_size) {
+                        counts[y as usize] += 1;
+                    }
+                });
+            }
+            counts
+        })
+        .collect();
+
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
+    let plotted = subcolumn_samples as u64 * SUBCOLUMNS as u64;
+    for (x, column) in columns.iter().enumerate() {
+        for (y, &count) in column.iter().enumerate() {
+            imgbuf.put_pixel(x as u32, y as u32, density_color(count, plotted, pixel_height));
+        }
+    }
+    imgbuf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect_orbit(map: BifurcationMap, r: f64, transient: u32, samples: u32) -> Vec<f64> {
+        let mut points = Vec::new();
+        orbit_points(map, r, transient, samples, |x| points.push(x));
+        points
+    }
+
+    #[test]
+    fn test_bifurcation_map_from_name() {
+        assert_eq!(BifurcationMap::from_name("logistic"), Ok(BifurcationMap::Logistic));
+        assert_eq!(BifurcationMap::from_name("sine"), Ok(BifurcationMap::Sine));
+        assert_eq!(BifurcationMap::from_name("tent"), Ok(BifurcationMap::Tent));
+    }
+
+    #[test]
+    fn test_bifurcation_map_unknown() {
+        assert_eq!(BifurcationMap::from_name("henon"), Err("Unknown bifurcation map: henon".to_string()));
+    }
+
+    #[test]
+    fn test_orbit_points() {
+        // r = 2.8 converges to the fixed point 1 - 1/r
+        let fixed = collect_orbit(BifurcationMap::Logistic, 2.8, 1000, 10);
+        assert_eq!(fixed.len(), 10);
+        assert!(fixed.iter().all(|x| (x - (1.0 - 1.0 / 2.8)).abs() < 1e-9));
+
+        // r = 3.2 alternates between the two points of a period-2 cycle
+        let cycle = collect_orbit(BifurcationMap::Logistic, 3.2, 1000, 4);
+        assert!((cycle[0] - cycle[2]).abs() < 1e-9);
+        assert!((cycle[0] - cycle[1]).abs() > 0.1);
+
+        // The tent map with r < 1 decays to 0
+        let tent = collect_orbit(BifurcationMap::Tent, 0.8, 1000, 5);
+        assert!(tent.iter().all(|x| x.abs() < 1e-9));
+
+        // r = 5 escapes the unit interval and diverges before the transient ends
+        assert!(collect_orbit(BifurcationMap::Logistic, 5.0, 1000, 10).is_empty());
+        // Counts whose sum overflows u32 still run until the orbit diverges
+        assert!(collect_orbit(BifurcationMap::Logistic, 5.0, u32::MAX, u32::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_density_color() {
+        assert_eq!(density_color(0, 1000, 0.01), Rgba([255, 255, 255, 255]));
+        // Points spread evenly over the unit interval give a light gray at any zoom
+        assert_eq!(density_color(10, 1000, 0.01), Rgba([154, 154, 154, 255]));
+        assert_eq!(density_color(1, 1000, 0.001), Rgba([154, 154, 154, 255]));
+        // All points in one pixel saturate to black
+        assert_eq!(density_color(1000, 1000, 0.01), Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_render_bifurcation() {
+        // An odd height puts the value 0 in the middle of a row
+        let (width, height) = (60, 25);
+        let origin = Decimal::from(0.0);
+        let imgbuf = render_bifurcation(width, height, &origin, &origin, 1.0, 3.0, BifurcationMap::Logistic, 1000, 100);
+        let white = Rgba([255, 255, 255, 255]);
+        let inked = |x: u32| (0..height).filter(|&y| *imgbuf.get_pixel(x, y) != white).collect::<Vec<u32>>();
+        let r = |x: u32| coordinate_mapper(x, 0, width, height, 1.0, 0.0, 0.0, 3.0).0;
+
+        // For 0 < r < 1 every orbit decays to 0, all in one pixel of the same row
+        let decaying: Vec<u32> = (0..width).filter(|&x| r(x) > 0.1 && r(x) + 0.1 < 1.0).collect();
+        assert!(!decaying.is_empty());
+        let zero_row = inked(decaying[0]);
+        assert_eq!(zero_row.len(), 1);
+        for &x in &decaying {
+            assert_eq!(inked(x), zero_row, "column {}", x);
+        }
+        // For 1.5 < r < 2.5 the fixed point 1 - 1/r lies above 0, since x grows upward
+        for x in (0..width).filter(|&x| r(x) > 1.5 && r(x) < 2.5) {
+            assert!(inked(x).iter().all(|&y| y < zero_row[0]), "column {}", x);
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use image::{ImageBuffer, Rgba, RgbaImage};
+
+use crate::decimal::Decimal;
+use crate::generate_mandelbrot::{coordinate_mapper, mandelbrot_component, pixel_mapper, save_with_zoom_text, view_scale};
+use crate::render_parallel::accumulate_chunks;
+use crate::rng::Rng;
+
+/// Number of samples drawn from one random stream (one Markov chain with importance sampling).
+const CHUNK_SAMPLES: u64 = 1 << 16;
+
+/// Probability that an importance sampling mutation jumps to a fresh random c.
+const LARGE_MUTATION_PROBABILITY: f64 = 0.1;
+
+/// Random tries for the starting point of a Markov chain.
+const START_TRIES: u32 = 100_000;
+
+/// Histogram weight of an orbit point in uniform sampling, and the total weight of
+/// one sample in importance sampling (fixed point, so sums don't depend on the
+/// order in which threads finish).
+const WEIGHT_ONE: u64 = 1 << 32;
+
+/// Distribution of the sampled c values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sampling {
+    /// Uniform over the square [-2, 2]².
+    Uniform,
+    /// Metropolis-Hastings random walk that favors c whose orbits cross the view.
+    Importance,
+}
+
+impl Sampling {
+    /// Parses a sampling name as used on the command line and in config files.
+    ///
+    /// # Returns
+    ///
+    /// The sampling, or an error message if the name is unknown.
+    pub fn from_name(name: &str) -> Result<Sampling, String> {
+        match name {
+            "uniform" => Ok(Sampling::Uniform),
+            "importance" => Ok(Sampling::Importance),
+            _ => Err(format!("Unknown sampling: {}", name)),
+        }
+    }
+}
+
+/// Histogram pixels visited by the orbit of one c value.
+#[derive(Debug, Clone, Default)]
+struct OrbitVisits {
+    /// Histogram index of every orbit point, `None` for points outside the view.
+    pixels: Vec<Option<usize>>,
+    /// Number of leading orbit points accumulated by each channel.
+    channel_points: Vec<usize>,
+    /// Number of accumulated orbit points inside the view, over all channels.
+    in_view: u64,
+}
+
+/// Iterates z² + c from z = 0 and records the orbit points inside the bailout.
+///
+/// # Returns
+///
+/// The iteration at which the orbit escaped, or `None` if it stayed bounded
+/// for `max_iterations`.
+fn record_orbit(cx: f64, cy: f64, max_iterations: u32, bailout: f64, orbit: &mut Vec<(f64, f64)>) -> Option<u32> {
+    orbit.clear();
+    let bailout_sqr = bailout * bailout;
+    let (mut x, mut y) = (0.0, 0.0);
+    for iteration in 1..=max_iterations {
+        (x, y) = (x * x - y * y + cx, 2.0 * x * y + cy);
+        if x * x + y * y > bailout_sqr {
+            return Some(iteration);
+        }
+        orbit.push((x, y));
+    }
+    None
+}
+
+/// Number of leading orbit points a channel accumulates.
+///
+/// # Arguments
+///
+/// * `escaped` - Iteration at which the orbit escaped, if it did.
+/// * `orbit_len` - Number of recorded orbit points.
+/// * `limit` - Iteration limit of the channel.
+/// * `anti` - Accumulate orbits that stay bounded instead of escaping ones.
+fn channel_points(escaped: Option<u32>, orbit_len: usize, limit: u32, anti: bool) -> usize {
+    let escaped_within_limit = escaped.is_some_and(|iteration| iteration <= limit);
+    if escaped_within_limit != anti {
+        orbit_len.min(limit as usize)
+    } else {
+        0
+    }
+}
+
+/// Finds the histogram count that is mapped to full brightness.
+///
+/// The brightest 0.1% of the pixels are clipped, so a few hot pixels from rare
+/// heavily weighted samples don't darken the whole image.
+pub fn tone_peak(channel: &[u64]) -> u64 {
+    if channel.is_empty() {
+        return 1;
+    }
+    let mut counts = channel.to_vec();
+    let index = (counts.len() - 1) * 999 / 1000;
+    let (_, peak, _) = counts.select_nth_unstable(index);
+    (*peak).max(1)
+}
+
+/// Generates a Buddhabrot image: the density of Mandelbrot orbits in the plane.
+///
+/// Random values of c are iterated, and every point of every escaping orbit
+/// (or bounded orbit for the anti-Buddhabrot) is counted in the pixel it falls
+/// into. With separate iteration limits for red, green and blue, each channel
+/// counts only the orbits that escape within its limit (Nebulabrot).
+///
+/// Samples are split into fixed chunks with their own random streams, so the
+/// image only depends on the seed, not on the number of threads.
+///
+/// Exits the process with an error message if the sampling is unknown.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `max_iterations` - Iteration limit of channels without their own limit.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path to save the generated image.
+/// * `bailout` - Escape radius for the iteration.
+/// * `samples` - Number of c values to sample.
+/// * `seed` - Seed of the random number generator.
+/// * `red_iterations` - Iteration limit of the red channel (Nebulabrot).
+/// * `green_iterations` - Iteration limit of the green channel (Nebulabrot).
+/// * `blue_iterations` - Iteration limit of the blue channel (Nebulabrot).
+/// * `anti` - Accumulate orbits that stay bounded instead of escaping ones.
+/// * `sampling` - Distribution of c: 'uniform' or 'importance'.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_buddhabrot(width: u32, height: u32, max_iterations: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, bailout: f64, samples: u64, seed: u64, red_iterations: Option<u32>, green_iterations: Option<u32>, blue_iterations: Option<u32>, anti: bool, sampling: &str) {
+    let sampling = Sampling::from_name(sampling).unwrap_or_else(|e| {
+        eprintln!("Invalid Buddhabrot sampling: {}", e);
+        std::process::exit(1);
+    });
+    let mut imgbuf = render_buddhabrot(width, height, max_iterations, center_x, center_y, zoom, m_size, bailout, samples, seed, red_iterations, green_iterations, blue_iterations, anti, sampling);
+
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+/// Counts how often the sampled orbits cross every pixel, once per iteration
+/// limit, and maps the counts to gray or to the red, green and blue Nebulabrot
+/// channels with square root tone mapping.
+#[allow(clippy::too_many_arguments)]
+fn render_buddhabrot(width: u32, height: u32, max_iterations: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, bailout: f64, samples: u64, seed: u64, red_iterations: Option<u32>, green_iterations: Option<u32>, blue_iterations: Option<u32>, anti: bool, sampling: Sampling) -> RgbaImage {
+    let center_x = center_x.to_f64();
+    let center_y = center_y.to_f64();
+    let limits = if red_iterations.is_none() && green_iterations.is_none() && blue_iterations.is_none() {
+        vec![max_iterations]
+    } else {
+        vec![
+            red_iterations.unwrap_or(max_iterations),
+            green_iterations.unwrap_or(max_iterations),
+            blue_iterations.unwrap_or(max_iterations),
+        ]
+    };
+    let pixel_count = width as usize * height as usize;
+
+    let to_pixel = |zx: f64, zy: f64| pixel_mapper(zx, zy, width, height, zoom, center_x, center_y, m_size).map(|(x, y)| y as usize * width as usize + x as usize);
+    let random_in_view = |rng: &mut Rng| coordinate_mapper((rng.next_f64() * width as f64) as u32, (rng.next_f64() * height as f64) as u32, width, height, zoom, center_x, center_y, m_size);
+    let (_, scale_y) = view_scale(width, height, zoom, m_size);
+
+    let sampler = ChunkSampler { limits: &limits, bailout, anti, to_pixel: &to_pixel };
+    let histogram = accumulate_chunks(samples.div_ceil(CHUNK_SAMPLES), pixel_count * limits.len(), |chunk, histogram: &mut [u64]| {
+        let chunk_samples = CHUNK_SAMPLES.min(samples - chunk * CHUNK_SAMPLES);
+        let mut rng = Rng::for_stream(seed, chunk);
+        match sampling {
+            Sampling::Uniform => sampler.sample_uniform(&mut rng, chunk_samples, histogram),
+            Sampling::Importance => sampler.sample_importance(&mut rng, chunk_samples, &random_in_view, scale_y, height, histogram),
+        }
+    });
+
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
+    let channels: Vec<&[u64]> = histogram.chunks(pixel_count).collect();
+    let peaks: Vec<u64> = channels.iter().map(|channel| tone_peak(channel)).collect();
+    for (i, pixel) in imgbuf.pixels_mut().enumerate() {
+        // Square root tone mapping brings out the faint outer orbits
+        let level = |channel: usize| ((channels[channel][i] as f64 / peaks[channel] as f64).min(1.0).sqrt() * 255.0) as u8;
+        *pixel = if channels.len() == 1 {
+            let gray = level(0);
+            Rgba([gray, gray, gray, 255])
+        } else {
+            Rgba([level(0), level(1), level(2), 255])
+        };
+    }
+    imgbuf
+}
+
+/// Samples c values for one chunk and accumulates their orbits.
+struct ChunkSampler<'a, F: Fn(f64, f64) -> Option<usize>> {
+    /// Iteration limit of each channel.
+    limits: &'a [u32],
+    bailout: f64,
+    anti: bool,
+    /// Maps a point of the plane to its histogram index.
+    to_pixel: &'a F,
+}
+
+impl<F: Fn(f64, f64) -> Option<usize>> ChunkSampler<'_, F> {
+    /// Iterates c and finds the pixels its orbit visits.
+    fn visit(&self, cx: f64, cy: f64, orbit: &mut Vec<(f64, f64)>, visits: &mut OrbitVisits) {
+        visits.pixels.clear();
+        visits.channel_points.clear();
+        visits.in_view = 0;
+        // Orbits in the main cardioid and period-2 bulb never escape
+        if !self.anti && mandelbrot_component(cx, cy).is_some() {
+            visits.channel_points.resize(self.limits.len(), 0);
+            return;
+        }
+
+        let max_limit = self.limits.iter().copied().max().unwrap_or(0);
+        let escaped = record_orbit(cx, cy, max_limit, self.bailout, orbit);
+        visits.pixels.extend(orbit.iter().map(|&(zx, zy)| (self.to_pixel)(zx, zy)));
+        for &limit in self.limits {
+            let points = channel_points(escaped, orbit.len(), limit, self.anti);
+            visits.in_view += visits.pixels[..points].iter().filter(|pixel| pixel.is_some()).count() as u64;
+            visits.channel_points.push(points);
+        }
+    }
+
+    /// Adds the visits of an orbit to the histogram, `weight` per point.
+    fn accumulate(&self, visits: &OrbitVisits, weight: u64, histogram: &mut [u64]) {
+        let pixel_count = histogram.len() / self.limits.len();
+        for (channel, &points) in visits.channel_points.iter().enumerate() {
+            for pixel in visits.pixels[..points].iter().flatten() {
+                histogram[channel * pixel_count + pixel] += weight;
+            }
+        }
+    }
+
+    /// Accumulates the orbits of c values drawn uniformly from [-2, 2]².
+    fn sample_uniform(&self, rng: &mut Rng, samples: u64, histogram: &mut [u64]) {
+        let mut orbit = Vec::new();
+        let mut visits = OrbitVisits::default();
+        for _ in 0..samples {
+            let (cx, cy) = (rng.range(-2.0, 2.0), rng.range(-2.0, 2.0));
+            self.visit(cx, cy, &mut orbit, &mut visits);
+            self.accumulate(&visits, WEIGHT_ONE, histogram);
+        }
+    }
+
+    /// Accumulates orbits of c values drawn by a Metropolis-Hastings random walk.
+    ///
+    /// The walk visits c in proportion to the number of orbit points inside the
+    /// view, so deep crops don't waste samples on orbits that never reach them.
+    /// Each sample is weighted by the inverse of that number, which makes the
+    /// expected histogram equal to the one of uniform sampling.
+    ///
+    /// Mutations either jump to a fresh uniform c, or move c by up to a random
+    /// distance between one pixel and the view size.
+    #[allow(clippy::too_many_arguments)]
+    fn sample_importance(&self, rng: &mut Rng, samples: u64, random_in_view: &impl Fn(&mut Rng) -> (f64, f64), scale: f64, height: u32, histogram: &mut [u64]) {
+        let mut orbit = Vec::new();
+        let mut current = OrbitVisits::default();
+        let mut candidate = OrbitVisits::default();
+
+        // Start from a c whose orbit reaches the view; c itself is the first orbit point,
+        // so trying c inside the view first usually succeeds quickly
+        let mut c = (0.0, 0.0);
+        for attempt in 0..START_TRIES {
+            c = if attempt % 2 == 0 { random_in_view(rng) } else { (rng.range(-2.0, 2.0), rng.range(-2.0, 2.0)) };
+            self.visit(c.0, c.1, &mut orbit, &mut current);
+            if current.in_view > 0 {
+                break;
+            }
+        }
+        if current.in_view == 0 {
+            return;
+        }
+
+        for _ in 0..samples {
+            let proposal = if rng.next_f64() < LARGE_MUTATION_PROBABILITY {
+                (rng.range(-2.0, 2.0), rng.range(-2.0, 2.0))
+            } else {
+                let radius = scale * (height as f64).powf(-rng.next_f64());
+                (c.0 + rng.range(-radius, radius), c.1 + rng.range(-radius, radius))
+            };
+            self.visit(proposal.0, proposal.1, &mut orbit, &mut candidate);
+            // Accept with probability min(1, f(proposal) / f(c)); mutations are symmetric
+            if candidate.in_view > 0 && rng.next_f64() * (current.in_view as f64) < candidate.in_view as f64 {
+                c = proposal;
+                std::mem::swap(&mut current, &mut candidate);
+            }
+            self.accumulate(&current, WEIGHT_ONE / current.in_view, histogram);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_orbit() {
+        let mut orbit = Vec::new();
+        // c = 1: 1, 2, 5 escapes at the third iteration
+        assert_eq!(record_orbit(1.0, 0.0, 100, 2.0, &mut orbit), Some(3));
+        assert_eq!(orbit, vec![(1.0, 0.0), (2.0, 0.0)]);
+        // c = -1 cycles between -1 and 0
+        assert_eq!(record_orbit(-1.0, 0.0, 10, 2.0, &mut orbit), None);
+        assert_eq!(orbit.len(), 10);
+    }
+
+    #[test]
+    fn test_channel_points() {
+        // Escaped at iteration 50 with 49 recorded points
+        assert_eq!(channel_points(Some(50), 49, 100, false), 49);
+        assert_eq!(channel_points(Some(50), 49, 20, false), 0);
+        assert_eq!(channel_points(Some(50), 49, 100, true), 0);
+        assert_eq!(channel_points(Some(50), 49, 20, true), 20);
+        // Bounded orbits only count for the anti-Buddhabrot
+        assert_eq!(channel_points(None, 100, 100, false), 0);
+        assert_eq!(channel_points(None, 100, 60, true), 60);
+    }
+
+    #[test]
+    fn test_tone_peak_clips_hot_pixels() {
+        let mut channel: Vec<u64> = (0..2000).collect();
+        channel[7] = 1_000_000;
+        assert_eq!(tone_peak(&channel), 1998);
+        assert_eq!(tone_peak(&[0, 0, 0]), 1);
+    }
+
+    #[test]
+    fn test_sampling_from_name() {
+        assert_eq!(Sampling::from_name("uniform"), Ok(Sampling::Uniform));
+        assert_eq!(Sampling::from_name("importance"), Ok(Sampling::Importance));
+        assert_eq!(Sampling::from_name("stratified"), Err("Unknown sampling: stratified".to_string()));
+    }
+
+    #[test]
+    fn test_importance_sampling_matches_uniform() {
+        // Both samplers estimate the same density over a 4x4 grid of the plane
+        let to_pixel = |zx: f64, zy: f64| {
+            let (x, y) = ((zx + 2.0).floor(), (zy + 2.0).floor());
+            (x >= 0.0 && y >= 0.0 && x < 4.0 && y < 4.0).then(|| y as usize * 4 + x as usize)
+        };
+        let sampler = ChunkSampler { limits: &[50], bailout: 2.0, anti: false, to_pixel: &to_pixel };
+        let mut uniform = vec![0u64; 16];
+        let mut importance = vec![0u64; 16];
+        sampler.sample_uniform(&mut Rng::new(1), 200_000, &mut uniform);
+        let random_in_view = |rng: &mut Rng| (rng.range(-2.0, 2.0), rng.range(-2.0, 2.0));
+        sampler.sample_importance(&mut Rng::new(2), 200_000, &random_in_view, 4.0, 4, &mut importance);
+
+        let normalize = |histogram: &[u64]| {
+            let total: u64 = histogram.iter().sum();
+            histogram.iter().map(|&count| count as f64 / total as f64).collect::<Vec<_>>()
+        };
+        for (u, i) in normalize(&uniform).iter().zip(normalize(&importance)) {
+            assert!((u - i).abs() < 0.02, "{} vs {}", u, i);
+        }
+    }
+
+    #[test]
+    fn test_render_buddhabrot() {
+        let origin = Decimal::from(0.0);
+        // Each Nebulabrot channel collects the orbits escaping within its own limit
+        let nebula = render_buddhabrot(48, 48, 100, &origin, &origin, 1.0, 4.0, 2.0, 50_000, 7, Some(200), None, Some(20), false, Sampling::Importance);
+        for channel in 0..3 {
+            assert!(nebula.pixels().any(|pixel| pixel[channel] > 0), "channel {}", channel);
+        }
+
+        // Orbits of the period-2 bulb alternate between a point near 0 and a
+        // point near -1, while no bounded orbit ever reaches the corner of the view
+        let anti = render_buddhabrot(48, 48, 200, &origin, &origin, 1.0, 4.0, 2.0, 50_000, 1, None, None, None, true, Sampling::Uniform);
+        let (x, y) = pixel_mapper(-1.0, 0.0, 48, 48, 1.0, 0.0, 0.0, 4.0).unwrap();
+        assert!(anti.get_pixel(x, y)[0] > 0);
+        assert_eq!(*anti.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+    }
+}
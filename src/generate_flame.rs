@@ -0,0 +1,466 @@
+use image::{ImageBuffer, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+use crate::decimal::Decimal;
+use crate::generate_mandelbrot::{coordinate_mapper, pixel_mapper, save_with_zoom_text};
+use crate::hsv_to_rgb::hsv_to_rgb;
+use crate::render_parallel::accumulate_chunks;
+use crate::rng::Rng;
+
+/// Number of chaos game steps traced from one random starting point.
+const CHUNK_SAMPLES: u64 = 1 << 16;
+
+/// Steps of the chaos game that are not plotted after a (re)start, while the
+/// point falls onto the attractor.
+const FUSE_ITERATIONS: u32 = 20;
+
+/// One transform of a fractal flame, as written in the config file:
+///
+/// ```toml
+/// [[flame_transforms]]
+/// weight = 1.0
+/// affine = [0.5, 0.0, 0.0, 0.0, 0.5, 0.0]
+/// color = 0.0
+/// variations = { linear = 0.8, swirl = 0.2 }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlameTransform {
+    /// Relative probability of choosing the transform.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// Affine coefficients [a, b, c, d, e, f]: x ← a·x + b·y + c, y ← d·x + e·y + f.
+    pub affine: [f64; 6],
+    /// Palette position (0.0 to 1.0) that points move toward when the transform is applied.
+    #[serde(default)]
+    pub color: f64,
+    /// Weight of every variation applied after the affine part, by name.
+    pub variations: BTreeMap<String, f64>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+impl FlameTransform {
+    /// Creates a transform from its affine coefficients and variations.
+    pub fn new(weight: f64, affine: [f64; 6], color: f64, variations: &[(&str, f64)]) -> FlameTransform {
+        FlameTransform {
+            weight,
+            affine,
+            color,
+            variations: variations.iter().map(|&(name, amount)| (name.to_string(), amount)).collect(),
+        }
+    }
+}
+
+/// The flame rendered when the config file has no transforms.
+pub fn default_transforms() -> Vec<FlameTransform> {
+    vec![
+        FlameTransform::new(1.0, [0.5, 0.0, -0.5, 0.0, 0.5, -0.5], 0.0, &[("linear", 0.7), ("spherical", 0.3)]),
+        FlameTransform::new(1.0, [0.5, 0.0, 0.5, 0.0, 0.5, -0.5], 0.5, &[("linear", 0.7), ("swirl", 0.3)]),
+        FlameTransform::new(1.0, [0.5, 0.0, 0.0, 0.0, 0.5, 0.5], 1.0, &[("linear", 0.5), ("julia", 0.5)]),
+        FlameTransform::new(0.3, [-0.6, 0.4, 0.0, -0.4, -0.6, 0.0], 0.25, &[("handkerchief", 1.0)]),
+    ]
+}
+
+/// Nonlinear function applied after the affine part of a transform.
+///
+/// The formulas follow "The Fractal Flame Algorithm" by Draves and Reckase, with
+/// r = √(x² + y²) and θ = atan2(x, y).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variation {
+    Linear,
+    Sinusoidal,
+    Spherical,
+    Swirl,
+    Horseshoe,
+    Polar,
+    Handkerchief,
+    Heart,
+    Disc,
+    Spiral,
+    Hyperbolic,
+    Diamond,
+    Julia,
+    Bubble,
+    Eyefish,
+    Cylinder,
+}
+
+impl Variation {
+    /// Parses a variation name as used in config files.
+    ///
+    /// # Returns
+    ///
+    /// The variation, or an error message if the name is unknown.
+    pub fn from_name(name: &str) -> Result<Variation, String> {
+        let variation = match name {
+            "linear" => Variation::Linear,
+            "sinusoidal" => Variation::Sinusoidal,
+            "spherical" => Variation::Spherical,
+            "swirl" => Variation::Swirl,
+            "horseshoe" => Variation::Horseshoe,
+            "polar" => Variation::Polar,
+            "handkerchief" => Variation::Handkerchief,
+            "heart" => Variation::Heart,
+            "disc" => Variation::Disc,
+            "spiral" => Variation::Spiral,
+            "hyperbolic" => Variation::Hyperbolic,
+            "diamond" => Variation::Diamond,
+            "julia" => Variation::Julia,
+            "bubble" => Variation::Bubble,
+            "eyefish" => Variation::Eyefish,
+            "cylinder" => Variation::Cylinder,
+            _ => return Err(format!("Unknown variation: {}", name)),
+        };
+        Ok(variation)
+    }
+
+    /// Applies the variation to a point.
+    ///
+    /// `rng` picks one of the two branches of the julia variation.
+    pub fn apply(self, x: f64, y: f64, rng: &mut Rng) -> (f64, f64) {
+        let r2 = x * x + y * y;
+        let r = r2.sqrt();
+        let theta = x.atan2(y);
+        match self {
+            Variation::Linear => (x, y),
+            Variation::Sinusoidal => (x.sin(), y.sin()),
+            Variation::Spherical => (x / r2, y / r2),
+            Variation::Swirl => (x * r2.sin() - y * r2.cos(), x * r2.cos() + y * r2.sin()),
+            Variation::Horseshoe => ((x - y) * (x + y) / r, 2.0 * x * y / r),
+            Variation::Polar => (theta / PI, r - 1.0),
+            Variation::Handkerchief => (r * (theta + r).sin(), r * (theta - r).cos()),
+            Variation::Heart => (r * (theta * r).sin(), -r * (theta * r).cos()),
+            Variation::Disc => (theta / PI * (PI * r).sin(), theta / PI * (PI * r).cos()),
+            Variation::Spiral => ((theta.cos() + r.sin()) / r, (theta.sin() - r.cos()) / r),
+            Variation::Hyperbolic => (theta.sin() / r, r * theta.cos()),
+            Variation::Diamond => (theta.sin() * r.cos(), theta.cos() * r.sin()),
+            Variation::Julia => {
+                let omega = if rng.next_u64() & 1 == 0 { 0.0 } else { PI };
+                let root = r.sqrt();
+                (root * (theta / 2.0 + omega).cos(), root * (theta / 2.0 + omega).sin())
+            }
+            Variation::Bubble => (4.0 * x / (r2 + 4.0), 4.0 * y / (r2 + 4.0)),
+            Variation::Eyefish => (2.0 * x / (r + 1.0), 2.0 * y / (r + 1.0)),
+            Variation::Cylinder => (x.sin(), y),
+        }
+    }
+}
+
+/// A transform prepared for rendering, with parsed variations.
+struct CompiledTransform {
+    affine: [f64; 6],
+    color: f64,
+    variations: Vec<(Variation, f64)>,
+}
+
+impl CompiledTransform {
+    /// Applies the affine part and then the weighted sum of the variations.
+    fn apply(&self, x: f64, y: f64, rng: &mut Rng) -> (f64, f64) {
+        let [a, b, c, d, e, f] = self.affine;
+        let (ax, ay) = (a * x + b * y + c, d * x + e * y + f);
+        self.variations.iter().fold((0.0, 0.0), |(sx, sy), &(variation, amount)| {
+            let (vx, vy) = variation.apply(ax, ay, rng);
+            (sx + amount * vx, sy + amount * vy)
+        })
+    }
+}
+
+/// Picks transforms at random in proportion to their weights.
+struct TransformPicker {
+    transforms: Vec<CompiledTransform>,
+    /// Running sum of the weights, normalized to end at 1.
+    cumulative: Vec<f64>,
+}
+
+impl TransformPicker {
+    /// Prepares the transforms for rendering.
+    ///
+    /// # Returns
+    ///
+    /// The picker, or an error message if there are no transforms, no positive
+    /// weights or an unknown variation.
+    fn new(transforms: &[FlameTransform]) -> Result<TransformPicker, String> {
+        if transforms.iter().any(|transform| transform.weight < 0.0) {
+            return Err("Transform weights must not be negative".to_string());
+        }
+        let total: f64 = transforms.iter().map(|transform| transform.weight).sum();
+        if total <= 0.0 {
+            return Err("At least one transform needs a positive weight".to_string());
+        }
+        let mut sum = 0.0;
+        let cumulative = transforms
+            .iter()
+            .map(|transform| {
+                sum += transform.weight;
+                sum / total
+            })
+            .collect();
+        let transforms = transforms
+            .iter()
+            .map(|transform| {
+                Ok(CompiledTransform {
+                    affine: transform.affine,
+                    color: transform.color.clamp(0.0, 1.0),
+                    variations: transform.variations.iter().map(|(name, &amount)| Ok((Variation::from_name(name)?, amount))).collect::<Result<_, String>>()?,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(TransformPicker { transforms, cumulative })
+    }
+
+    /// Picks a transform for a uniform random number in [0, 1).
+    fn pick(&self, u: f64) -> &CompiledTransform {
+        let index = self.cumulative.partition_point(|&edge| edge <= u).min(self.transforms.len() - 1);
+        &self.transforms[index]
+    }
+}
+
+/// Per-pixel sums of the chaos game: palette red, green and blue, and the hit count.
+type Accumulator = [u64; 4];
+
+/// Plays the chaos game for one chunk and adds the plotted points to the histogram.
+///
+/// Points that leave the finite numbers (e.g. at the pole of spherical) restart
+/// from a random point.
+fn play_chunk(picker: &TransformPicker, palette: &[Rgba<u8>], rng: &mut Rng, samples: u64, to_pixel: impl Fn(f64, f64) -> Option<usize>, histogram: &mut [Accumulator]) {
+    let restart = |rng: &mut Rng| (rng.range(-1.0, 1.0), rng.range(-1.0, 1.0));
+    let (mut x, mut y) = restart(rng);
+    let mut color = rng.next_f64();
+    let mut fuse = FUSE_ITERATIONS;
+    for _ in 0..samples {
+        let transform = picker.pick(rng.next_f64());
+        (x, y) = transform.apply(x, y, rng);
+        color = (color + transform.color) / 2.0;
+        if !x.is_finite() || !y.is_finite() {
+            (x, y) = restart(rng);
+            fuse = FUSE_ITERATIONS;
+            continue;
+        }
+        if fuse > 0 {
+            fuse -= 1;
+            continue;
+        }
+        if let Some(pixel) = to_pixel(x, y) {
+            let Rgba([red, green, blue, _]) = palette[(color * (palette.len() - 1) as f64).round() as usize];
+            let sums = &mut histogram[pixel];
+            sums[0] += red as u64;
+            sums[1] += green as u64;
+            sums[2] += blue as u64;
+            sums[3] += 1;
+        }
+    }
+}
+
+/// Converts the accumulated sums of a pixel to its color.
+///
+/// The brightness grows with the logarithm of the hit count. Gamma correction
+/// is applied to that brightness, and with vibrancy 1 the hue and saturation of
+/// the average color are kept; with vibrancy 0 gamma is applied to each channel,
+/// which washes out colors in bright regions.
+///
+/// # Arguments
+///
+/// * `sums` - Palette color sums and hit count of the pixel.
+/// * `log_peak` - ln(1 + hits) of the brightest pixel.
+/// * `gamma` - Gamma of the display.
+/// * `vibrancy` - Blend between per-brightness (1.0) and per-channel (0.0) gamma.
+fn flame_color(sums: &Accumulator, log_peak: f64, gamma: f64, vibrancy: f64) -> Rgba<u8> {
+    let count = sums[3];
+    if count == 0 {
+        return Rgba([0, 0, 0, 255]);
+    }
+    let alpha = ((1.0 + count as f64).ln() / log_peak).min(1.0);
+    let alpha_gamma = alpha.powf(1.0 / gamma);
+    let channel = |sum: u64| {
+        let average = sum as f64 / count as f64 / 255.0;
+        let value = vibrancy * alpha_gamma * average + (1.0 - vibrancy) * (alpha * average).powf(1.0 / gamma);
+        (value.clamp(0.0, 1.0) * 255.0) as u8
+    };
+    Rgba([channel(sums[0]), channel(sums[1]), channel(sums[2]), 255])
+}
+
+/// Generates a fractal flame image.
+///
+/// A point jumps between randomly chosen transforms (the chaos game), and
+/// every jump is counted in the pixel it lands in along with the palette color
+/// of the transforms it came through. The counts are displayed with log-density
+/// tone mapping, gamma correction and vibrancy. The view is framed like the
+/// complex plane of the other functions, except that y grows upward.
+///
+/// Samples are split into fixed chunks with their own random streams, so the
+/// image only depends on the seed, not on the number of threads.
+///
+/// Exits the process with an error message if the transform weights are invalid.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path to save the generated image.
+/// * `samples` - Number of chaos game steps.
+/// * `seed` - Seed of the random number generator.
+/// * `transforms` - Transforms of the flame (empty = the built-in flame).
+/// * `gamma` - Gamma of the display.
+/// * `vibrancy` - Blend between per-brightness (1.0) and per-channel (0.0) gamma.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_flame(width: u32, height: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, samples: u64, seed: u64, transforms: &[FlameTransform], gamma: f64, vibrancy: f64) {
+    let defaults;
+    let transforms = if transforms.is_empty() {
+        defaults = default_transforms();
+        &defaults
+    } else {
+        transforms
+    };
+    let picker = TransformPicker::new(transforms).unwrap_or_else(|e| {
+        eprintln!("Invalid flame transforms: {}", e);
+        std::process::exit(1);
+    });
+    let mut imgbuf = render_flame(width, height, center_x, center_y, zoom, m_size, samples, seed, &picker, gamma, vibrancy);
+
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+/// Plays the chaos game with the transforms of `picker` and tone-maps the
+/// palette colors of the visited pixels by log density, gamma and vibrancy.
+#[allow(clippy::too_many_arguments)]
+fn render_flame(width: u32, height: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, samples: u64, seed: u64, picker: &TransformPicker, gamma: f64, vibrancy: f64) -> RgbaImage {
+    let palette: Vec<Rgba<u8>> = (0..256).map(|i| hsv_to_rgb(i as f32 / 256.0 * 360.0, 255, 255)).collect();
+    let center_x = center_x.to_f64();
+    let center_y = center_y.to_f64();
+    let (_, center) = coordinate_mapper(0, height / 2, width, height, zoom, center_x, center_y, m_size);
+    let pixel_count = width as usize * height as usize;
+
+    // Mirror y about the center so that it grows upward
+    let to_pixel = |x: f64, y: f64| pixel_mapper(x, 2.0 * center - y, width, height, zoom, center_x, center_y, m_size).map(|(px, py)| py as usize * width as usize + px as usize);
+
+    let histogram = accumulate_chunks(samples.div_ceil(CHUNK_SAMPLES), pixel_count, |chunk, histogram: &mut [Accumulator]| {
+        let mut rng = Rng::for_stream(seed, chunk);
+        play_chunk(picker, &palette, &mut rng, CHUNK_SAMPLES.min(samples - chunk * CHUNK_SAMPLES), to_pixel, histogram);
+    });
+
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
+    let log_peak = (1.0 + histogram.iter().map(|sums| sums[3]).max().unwrap_or(0) as f64).ln().max(f64::MIN_POSITIVE);
+    for (pixel, sums) in imgbuf.pixels_mut().zip(&histogram) {
+        *pixel = flame_color(sums, log_peak, gamma, vibrancy);
+    }
+    imgbuf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variations() {
+        let mut rng = Rng::new(0);
+        assert_eq!(Variation::from_name("linear").unwrap().apply(0.3, -0.2, &mut rng), (0.3, -0.2));
+        // Spherical inverts in the unit circle
+        let (x, y) = Variation::from_name("spherical").unwrap().apply(2.0, 0.0, &mut rng);
+        assert!((x - 0.5).abs() < 1e-12 && y.abs() < 1e-12);
+        // Swirl keeps the distance to the origin
+        let (x, y) = Variation::from_name("swirl").unwrap().apply(0.6, 0.8, &mut rng);
+        assert!((x * x + y * y - 1.0).abs() < 1e-12);
+        // Julia takes one of the two square roots, with θ measured from the y axis
+        for _ in 0..4 {
+            let (x, y) = Variation::from_name("julia").unwrap().apply(0.0, 4.0, &mut rng);
+            assert!((x.abs() - 2.0).abs() < 1e-12 && y.abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_unknown_variation() {
+        assert_eq!(Variation::from_name("wobble"), Err("Unknown variation: wobble".to_string()));
+        let transforms = vec![FlameTransform::new(1.0, [1.0, 0.0, 0.0, 0.0, 1.0, 0.0], 0.0, &[("wobble", 1.0)])];
+        assert!(TransformPicker::new(&transforms).is_err());
+    }
+
+    #[test]
+    fn test_transform_picker() {
+        let transforms = vec![
+            FlameTransform::new(1.0, [1.0, 0.0, 0.0, 0.0, 1.0, 0.0], 0.0, &[("linear", 1.0)]),
+            FlameTransform::new(3.0, [0.5, 0.0, 1.0, 0.0, 0.5, 0.0], 1.0, &[("linear", 0.5), ("sinusoidal", 0.5)]),
+        ];
+        let picker = TransformPicker::new(&transforms).unwrap();
+        assert_eq!(picker.pick(0.0).color, 0.0);
+        assert_eq!(picker.pick(0.24).color, 0.0);
+        assert_eq!(picker.pick(0.25).color, 1.0);
+        assert_eq!(picker.pick(0.999).color, 1.0);
+
+        // Affine part first, then the weighted variations
+        let (x, y) = picker.pick(0.5).apply(0.0, 2.0, &mut Rng::new(0));
+        assert!((x - (0.5 + 0.5 * 1.0f64.sin())).abs() < 1e-12);
+        assert!((y - (0.5 + 0.5 * 1.0f64.sin())).abs() < 1e-12);
+
+        assert!(TransformPicker::new(&[]).is_err());
+        let mut negative = transforms.clone();
+        negative[0].weight = -1.0;
+        assert!(TransformPicker::new(&negative).is_err());
+    }
+
+    #[test]
+    fn test_flame_transforms_from_toml() {
+        #[derive(Deserialize)]
+        struct Flame {
+            flame_transforms: Vec<FlameTransform>,
+        }
+        let flame: Flame = toml::from_str(
+            r#"
+[[flame_transforms]]
+affine = [0.5, 0.0, 0.0, 0.0, 0.5, 0.0]
+variations = { linear = 1.0 }
+
+[[flame_transforms]]
+weight = 2.0
+affine = [0.5, 0.0, 0.5, 0.0, 0.5, 0.0]
+color = 1.0
+variations = { swirl = 0.5, spherical = 0.5 }
+"#,
+        )
+        .unwrap();
+        assert_eq!(flame.flame_transforms.len(), 2);
+        assert_eq!(flame.flame_transforms[0], FlameTransform::new(1.0, [0.5, 0.0, 0.0, 0.0, 0.5, 0.0], 0.0, &[("linear", 1.0)]));
+        assert_eq!(flame.flame_transforms[1].weight, 2.0);
+        assert_eq!(flame.flame_transforms[1].variations["swirl"], 0.5);
+    }
+
+    #[test]
+    fn test_flame_color() {
+        assert_eq!(flame_color(&[0, 0, 0, 0], 1.0, 4.0, 1.0), Rgba([0, 0, 0, 255]));
+        // The brightest pixel keeps its average color
+        let peak = [255 * 10, 0, 128 * 10, 10];
+        let log_peak = 11.0f64.ln();
+        assert_eq!(flame_color(&peak, log_peak, 4.0, 1.0), Rgba([255, 0, 128, 255]));
+        // Dimmer pixels are brightened by the gamma, keeping the hue with full vibrancy
+        let Rgba([red, green, blue, _]) = flame_color(&[255, 0, 128, 1], log_peak, 4.0, 1.0);
+        assert!(red > 128 && green == 0 && (blue as f64 / red as f64 - 128.0 / 255.0).abs() < 0.01);
+        // Without vibrancy the channels are corrected separately, which desaturates
+        let Rgba([red, _, blue, _]) = flame_color(&[255, 0, 128, 1], log_peak, 4.0, 0.0);
+        assert!(blue as f64 / red as f64 > 0.6);
+    }
+
+    #[test]
+    fn test_render_flame() {
+        let origin = Decimal::from(0.0);
+        // A single contraction towards (1, 1) with color 0 lights one red pixel in the
+        // upper right quadrant, since y grows upward
+        let contraction = vec![FlameTransform::new(1.0, [0.5, 0.0, 0.5, 0.0, 0.5, 0.5], 0.0, &[("linear", 1.0)])];
+        let imgbuf = render_flame(33, 33, &origin, &origin, 1.0, 3.0, 10000, 0, &TransformPicker::new(&contraction).unwrap(), 4.0, 1.0);
+        let lit: Vec<(u32, u32, Rgba<u8>)> = imgbuf.enumerate_pixels().filter(|(_, _, pixel)| **pixel != Rgba([0, 0, 0, 255])).map(|(x, y, pixel)| (x, y, *pixel)).collect();
+        assert_eq!(lit.len(), 1);
+        let (x, y, pixel) = lit[0];
+        assert!(x > 16 && y < 16, "({}, {})", x, y);
+        assert_eq!(pixel, Rgba([255, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,301 @@
+use num_complex::Complex64;
+use std::f64::consts::PI;
+
+use crate::decimal::Decimal;
+use crate::generate_mandelbrot::save_with_zoom_text;
+use crate::generate_schrodinger::{density_color, display_or_exit, phase_color, render_plane, signed_color, WaveDisplay};
+
+/// Radii sampled per Bohr radius when searching the peak of the radial function.
+const RADIAL_SAMPLES_PER_BOHR: f64 = 100.0;
+
+/// Directions of the slice plane sampled when searching the peak of the spherical harmonic.
+const ANGULAR_SAMPLES: usize = 3600;
+
+/// Stationary state ψ_nlm(r, θ, φ) = R_nl(r)·Y_lm(θ, φ) of the electron of the
+/// hydrogen atom, in atomic units (lengths in Bohr radii a₀).
+///
+/// R_nl uses the generalized Laguerre polynomials and Y_lm the complex spherical
+/// harmonics with the Condon–Shortley phase, both normalized so that ∫|ψ|² = 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orbital {
+    /// Principal quantum number n ≥ 1.
+    n: u32,
+    /// Azimuthal quantum number 0 ≤ l < n.
+    l: u32,
+    /// Magnetic quantum number -l ≤ m ≤ l.
+    m: i32,
+}
+
+impl Orbital {
+    /// Creates an orbital, or returns an error message if the quantum numbers are invalid.
+    pub fn new(n: u32, l: u32, m: i32) -> Result<Orbital, String> {
+        if n == 0 {
+            return Err("The principal quantum number n must be at least 1".to_string());
+        }
+        if l >= n {
+            return Err(format!("The azimuthal quantum number l must be below n = {}, got {}", n, l));
+        }
+        if m.unsigned_abs() > l {
+            return Err(format!("The magnetic quantum number m must be between -{} and {}, got {}", l, l, m));
+        }
+        Ok(Orbital { n, l, m })
+    }
+
+    /// Radial function R_nl(r).
+    pub fn radial(&self, r: f64) -> f64 {
+        let (n, l) = (self.n as f64, self.l as f64);
+        let rho = 2.0 * r / n;
+        // (n - l - 1)! / (n + l)! as the inverse of the product n - l, ..., n + l
+        let factorial_ratio = 1.0 / (self.n - self.l..=self.n + self.l).map(|k| k as f64).product::<f64>();
+        let normalization = ((2.0 / n).powi(3) * factorial_ratio / (2.0 * n)).sqrt();
+        normalization * (-rho / 2.0).exp() * rho.powi(self.l as i32) * laguerre(self.n - self.l - 1, 2.0 * l + 1.0, rho)
+    }
+
+    /// Spherical harmonic Y_lm(θ, φ) for a direction given by cos θ and φ.
+    pub fn angular(&self, cos_theta: f64, phi: f64) -> Complex64 {
+        let (l, m) = (self.l, self.m.unsigned_abs());
+        // (l - |m|)! / (l + |m|)!
+        let factorial_ratio = 1.0 / (l - m + 1..=l + m).map(|k| k as f64).product::<f64>();
+        let normalization = ((2.0 * l as f64 + 1.0) / (4.0 * PI) * factorial_ratio).sqrt();
+        let y = Complex64::from_polar(normalization * associated_legendre(l, m, cos_theta), m as f64 * phi);
+        // Y_l,-m = (-1)^m·conj(Y_lm)
+        if self.m >= 0 {
+            y
+        } else if m % 2 == 0 {
+            y.conj()
+        } else {
+            -y.conj()
+        }
+    }
+
+    /// Value of ψ at a point (x, y, z).
+    pub fn psi(&self, [x, y, z]: [f64; 3]) -> Complex64 {
+        let r = (x * x + y * y + z * z).sqrt();
+        let cos_theta = if r > 0.0 { z / r } else { 1.0 };
+        self.angular(cos_theta, y.atan2(x)) * self.radial(r)
+    }
+
+    /// Peak of |ψ|² over a plane through the nucleus.
+    ///
+    /// Every direction of the plane meets every radius, so the peak is the
+    /// product of the peaks of R² over the radii and of |Y|² over the directions.
+    pub fn peak_density(&self, slice: &Slice) -> f64 {
+        // The radial function vanishes well within 2n(n + 5) Bohr radii
+        let extent = 2.0 * self.n as f64 * (self.n as f64 + 5.0);
+        let samples = (extent * RADIAL_SAMPLES_PER_BOHR) as usize;
+        let radial_peak = (0..=samples).map(|i| self.radial(extent * i as f64 / samples as f64).powi(2)).fold(0.0, f64::max);
+        let angular_peak = (0..ANGULAR_SAMPLES)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / ANGULAR_SAMPLES as f64;
+                let [x, y, z] = slice.point(angle.cos(), angle.sin());
+                self.angular(z, y.atan2(x)).norm_sqr()
+            })
+            .fold(0.0, f64::max);
+        radial_peak * angular_peak
+    }
+}
+
+/// Generalized Laguerre polynomial L_k^(α)(x), by its three-term recurrence.
+fn laguerre(k: u32, alpha: f64, x: f64) -> f64 {
+    let (mut previous, mut current) = (1.0, 1.0 + alpha - x);
+    if k == 0 {
+        return previous;
+    }
+    for i in 1..k {
+        let i = i as f64;
+        let next = ((2.0 * i + 1.0 + alpha - x) * current - (i + alpha) * previous) / (i + 1.0);
+        (previous, current) = (current, next);
+    }
+    current
+}
+
+/// Associated Legendre function P_l^m(x) with the Condon–Shortley phase (-1)^m, for 0 ≤ m ≤ l.
+fn associated_legendre(l: u32, m: u32, x: f64) -> f64 {
+    // P_m^m = (-1)^m·(2m - 1)!!·(1 - x²)^(m/2)
+    let sine = (1.0 - x * x).max(0.0).sqrt();
+    let mut diagonal = 1.0;
+    for i in 0..m {
+        diagonal *= -((2 * i + 1) as f64) * sine;
+    }
+    if l == m {
+        return diagonal;
+    }
+    // Raise l with (l - m)·P_l^m = (2l - 1)·x·P_(l-1)^m - (l + m - 1)·P_(l-2)^m
+    let (mut previous, mut current) = (diagonal, x * (2 * m + 1) as f64 * diagonal);
+    for degree in m + 2..=l {
+        let next = ((2 * degree - 1) as f64 * x * current - (degree + m - 1) as f64 * previous) / (degree - m) as f64;
+        (previous, current) = (current, next);
+    }
+    current
+}
+
+/// Plane through the nucleus on which the orbital is drawn, with the image x
+/// axis along `u` and the image y axis along `v`.
+///
+/// A tilt of 0° is the xy plane. Tilting rotates the plane about the x axis
+/// until, at 90°, it is the xz plane with z pointing up; the azimuth then
+/// rotates the plane about the z axis, so a tilt of 90° and an azimuth of 90°
+/// is the yz plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slice {
+    u: [f64; 3],
+    v: [f64; 3],
+}
+
+impl Slice {
+    /// Creates a slice from its tilt and azimuth in degrees.
+    pub fn new(tilt: f64, azimuth: f64) -> Slice {
+        let (tilt, azimuth) = (tilt.to_radians(), azimuth.to_radians());
+        Slice {
+            u: [azimuth.cos(), azimuth.sin(), 0.0],
+            v: [-azimuth.sin() * tilt.cos(), azimuth.cos() * tilt.cos(), tilt.sin()],
+        }
+    }
+
+    /// Point in space of plane coordinates (x, y).
+    pub fn point(&self, x: f64, y: f64) -> [f64; 3] {
+        [0, 1, 2].map(|i| x * self.u[i] + y * self.v[i])
+    }
+}
+
+/// Parses the quantum numbers of an orbital, exiting the process with an error message if they are invalid.
+pub fn orbital_or_exit(n: u32, l: u32, m: i32) -> Orbital {
+    Orbital::new(n, l, m).unwrap_or_else(|e| {
+        eprintln!("Invalid hydrogen orbital: {}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Generates an image of a hydrogen orbital on a plane through the nucleus.
+///
+/// The plane is framed like the 'schrodinger' function, in Bohr radii with
+/// the nucleus at the origin. Densities and amplitudes are relative to the
+/// peak of |ψ|² on the plane, so a plane on a nodal surface of the orbital is
+/// black.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `bands` - Number of color bands.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square), in Bohr radii.
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path to save the generated image.
+/// * `orbital` - Orbital to draw.
+/// * `slice` - Plane through the nucleus.
+/// * `display` - Part of ψ to draw: 'density', 'phase', 'real' or 'imaginary'.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_hydrogen(width: u32, height: u32, bands: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, orbital: &Orbital, slice: &Slice, display: &str) {
+    let display = display_or_exit(display);
+    let peak = orbital.peak_density(slice);
+    // Scales ψ so that the peak of |ψ|² is 1; nodal planes stay at 0
+    let scale = if peak > 0.0 { 1.0 / peak.sqrt() } else { 0.0 };
+
+    let mut imgbuf = render_plane(width, height, center_x.to_f64(), center_y.to_f64(), zoom, m_size, |px, py| {
+        let psi = orbital.psi(slice.point(px, py)) * scale;
+        match display {
+            WaveDisplay::Density => density_color(psi.norm_sqr(), bands),
+            WaveDisplay::Phase => phase_color(psi),
+            WaveDisplay::Real => signed_color(psi.re),
+            WaveDisplay::Imaginary => signed_color(psi.im),
+        }
+    });
+
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_generate_hydrogen_creates_file() {
+        let output_path = "/tmp/test_hydrogen.png";
+        let font_path = "/System/Library/Fonts/Helvetica.ttc"; // Use system font for testing
+
+        // Skip test if font doesn't exist
+        if !Path::new(font_path).exists() {
+            return;
+        }
+
+        let orbital = Orbital::new(3, 2, 1).unwrap();
+        let center = Decimal::from(0.0);
+        generate_hydrogen(
+            100, 100, 8, &center, &center, 1.0, 30.0,
+            font_path, 5, 80, 12.0, output_path, &orbital, &Slice::new(90.0, 0.0), "phase"
+        );
+
+        assert!(Path::new(output_path).exists());
+
+        // Check file size is reasonable (should be > 0)
+        let metadata = fs::metadata(output_path).unwrap();
+        assert!(metadata.len() > 1000); // PNG files should be at least this big
+
+        // Clean up
+        fs::remove_file(output_path).ok();
+    }
+
+    #[test]
+    fn test_closed_form_orbitals() {
+        let point = [0.7, -1.1, 0.4];
+        let r = (0.7_f64 * 0.7 + 1.1 * 1.1 + 0.4 * 0.4).sqrt();
+        let phase = Complex64::from_polar(1.0, (-1.1_f64).atan2(0.7));
+        // ψ_100 = e^(-r)/√π
+        let psi = Orbital::new(1, 0, 0).unwrap().psi(point);
+        assert!((psi - Complex64::new((-r).exp() / PI.sqrt(), 0.0)).norm() < 1e-12);
+        // ψ_210 = z·e^(-r/2)/(4√(2π))
+        let psi = Orbital::new(2, 1, 0).unwrap().psi(point);
+        assert!((psi - Complex64::new(0.4 * (-r / 2.0).exp() / (4.0 * (2.0 * PI).sqrt()), 0.0)).norm() < 1e-12);
+        // ψ_21±1 = ∓(x ± iy)·e^(-r/2)/(8√π)
+        let rho = (0.7_f64 * 0.7 + 1.1 * 1.1).sqrt();
+        let expected = -phase * rho * (-r / 2.0).exp() / (8.0 * PI.sqrt());
+        assert!((Orbital::new(2, 1, 1).unwrap().psi(point) - expected).norm() < 1e-12);
+        assert!((Orbital::new(2, 1, -1).unwrap().psi(point) + expected.conj()).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_orbitals_are_normalized() {
+        for (n, l, m) in [(3, 1, 0), (4, 3, -2), (5, 2, 2)] {
+            let orbital = Orbital::new(n, l, m).unwrap();
+            // ∫R²r²dr = 1 by the midpoint rule
+            let step = 0.01;
+            let radial: f64 = (0..20000).map(|i| (i as f64 + 0.5) * step).map(|r| (orbital.radial(r) * r).powi(2) * step).sum();
+            assert!((radial - 1.0).abs() < 1e-6, "radial norm {} for n = {}, l = {}", radial, n, l);
+            // ∫|Y|²dΩ = 1, with φ dropping out of |Y|²
+            let angular: f64 = (0..2000).map(|i| -1.0 + (i as f64 + 0.5) / 1000.0).map(|x| orbital.angular(x, 0.3).norm_sqr() * 2.0 * PI / 1000.0).sum();
+            assert!((angular - 1.0).abs() < 1e-5, "angular norm {} for l = {}, m = {}", angular, l, m);
+        }
+    }
+
+    #[test]
+    fn test_invalid_orbital() {
+        assert!(Orbital::new(0, 0, 0).is_err());
+        assert!(Orbital::new(2, 2, 0).is_err());
+        assert!(Orbital::new(3, 1, -2).is_err());
+        assert!(Orbital::new(3, 2, -2).is_ok());
+    }
+
+    #[test]
+    fn test_slice_orientation() {
+        let xy = Slice::new(0.0, 0.0);
+        assert_eq!(xy.point(1.0, 2.0), [1.0, 2.0, 0.0]);
+        let close = |a: [f64; 3], b: [f64; 3]| a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 1e-12);
+        assert!(close(Slice::new(90.0, 0.0).point(1.0, 2.0), [1.0, 0.0, 2.0]));
+        assert!(close(Slice::new(90.0, 90.0).point(1.0, 2.0), [0.0, 1.0, 2.0]));
+
+        // A p_z orbital vanishes on the xy plane and peaks on the z axis of the xz plane
+        let orbital = Orbital::new(2, 1, 0).unwrap();
+        assert_eq!(orbital.peak_density(&xy), 0.0);
+        let peak = orbital.peak_density(&Slice::new(90.0, 0.0));
+        // |ψ_210|² = z²·e^(-r)/(32π) peaks at z = 2
+        assert!((peak - 4.0 * (-2.0_f64).exp() / (32.0 * PI)).abs() < 1e-9);
+    }
+}
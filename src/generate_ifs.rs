@@ -0,0 +1,341 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::decimal::Decimal;
+use crate::generate_attractor::render_density;
+use crate::generate_mandelbrot::save_with_zoom_text;
+use crate::rng::Rng;
+
+/// Steps of the chaos game that are not plotted at the start of a chunk, while
+/// the point falls onto the fractal.
+const FUSE_ITERATIONS: u32 = 20;
+
+/// One affine map of an iterated function system, as written in the config file:
+///
+/// ```toml
+/// [[ifs_maps]]
+/// probability = 0.85
+/// affine = [0.85, 0.04, 0.0, -0.04, 0.85, 1.6]
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IfsMap {
+    /// Relative probability of choosing the map.
+    #[serde(default = "default_probability")]
+    pub probability: f64,
+    /// Affine coefficients [a, b, c, d, e, f]: x ← a·x + b·y + c, y ← d·x + e·y + f.
+    pub affine: [f64; 6],
+}
+
+fn default_probability() -> f64 {
+    1.0
+}
+
+impl IfsMap {
+    /// Creates a map from its probability and affine coefficients.
+    pub fn new(probability: f64, affine: [f64; 6]) -> IfsMap {
+        IfsMap { probability, affine }
+    }
+
+    /// Applies the map to a point.
+    fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        let [a, b, c, d, e, f] = self.affine;
+        (a * x + b * y + c, d * x + e * y + f)
+    }
+}
+
+/// Maps of the chaos game on a regular polygon: each one jumps the given
+/// fraction of the way toward a vertex.
+///
+/// The polygon has circumradius 1, its first vertex points up and the others
+/// follow counterclockwise.
+///
+/// # Arguments
+///
+/// * `vertices` - Number of vertices.
+/// * `ratio` - Fraction of the distance to the vertex covered by a jump.
+pub fn polygon_maps(vertices: u32, ratio: f64) -> Vec<IfsMap> {
+    (0..vertices)
+        .map(|k| {
+            let angle = PI / 2.0 + 2.0 * PI * k as f64 / vertices as f64;
+            let scale = 1.0 - ratio;
+            IfsMap::new(1.0, [scale, 0.0, ratio * angle.cos(), 0.0, scale, ratio * angle.sin()])
+        })
+        .collect()
+}
+
+/// Returns the maps of a classic iterated function system.
+///
+/// # Arguments
+///
+/// * `name` - 'fern', 'sierpinski', 'carpet' or 'dragon'.
+///
+/// # Returns
+///
+/// The maps, or an error message if the name is unknown.
+pub fn preset_maps(name: &str) -> Result<Vec<IfsMap>, String> {
+    let maps = match name {
+        // Barnsley's fern: stem, successively smaller leaflets, largest left and right leaflets
+        "fern" => vec![
+            IfsMap::new(0.01, [0.0, 0.0, 0.0, 0.0, 0.16, 0.0]),
+            IfsMap::new(0.85, [0.85, 0.04, 0.0, -0.04, 0.85, 1.6]),
+            IfsMap::new(0.07, [0.2, -0.26, 0.0, 0.23, 0.22, 1.6]),
+            IfsMap::new(0.07, [-0.15, 0.28, 0.0, 0.26, 0.24, 0.44]),
+        ],
+        "sierpinski" => polygon_maps(3, 0.5),
+        // Eight copies at a third of the size, leaving out the middle of [-1, 1]²
+        "carpet" => (-1..=1)
+            .flat_map(|i| (-1..=1).map(move |j| (i, j)))
+            .filter(|&(i, j)| (i, j) != (0, 0))
+            .map(|(i, j)| IfsMap::new(1.0, [1.0 / 3.0, 0.0, 2.0 * i as f64 / 3.0, 0.0, 1.0 / 3.0, 2.0 * j as f64 / 3.0]))
+            .collect(),
+        // Heighway dragon: rotations by 45° and 135° scaled by 1/√2, between 0 and 1
+        "dragon" => vec![
+            IfsMap::new(1.0, [0.5, -0.5, 0.0, 0.5, 0.5, 0.0]),
+            IfsMap::new(1.0, [-0.5, -0.5, 1.0, 0.5, -0.5, 0.0]),
+        ],
+        _ => return Err(format!("Unknown IFS: {}", name)),
+    };
+    Ok(maps)
+}
+
+/// Restriction on the next map of the chaos game, given the previous one.
+///
+/// Maps are treated as the vertices of a polygon in the order they are listed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChaosRule {
+    /// Every map can follow every map.
+    Any,
+    /// The same map is never chosen twice in a row.
+    NoRepeat,
+    /// The maps next to the previous one are never chosen.
+    NoNeighbor,
+    /// The map one place counterclockwise (the next in the list) is never chosen.
+    NoCounterclockwise,
+}
+
+impl ChaosRule {
+    /// Parses a rule name as used on the command line and in config files.
+    ///
+    /// # Returns
+    ///
+    /// The rule, or an error message if the name is unknown.
+    pub fn from_name(name: &str) -> Result<ChaosRule, String> {
+        match name {
+            "any" => Ok(ChaosRule::Any),
+            "no-repeat" => Ok(ChaosRule::NoRepeat),
+            "no-neighbor" => Ok(ChaosRule::NoNeighbor),
+            "no-counterclockwise" => Ok(ChaosRule::NoCounterclockwise),
+            _ => Err(format!("Unknown chaos rule: {}", name)),
+        }
+    }
+
+    /// Checks whether map `next` may follow map `previous` out of `count` maps.
+    fn allows(self, previous: usize, next: usize, count: usize) -> bool {
+        match self {
+            ChaosRule::Any => true,
+            ChaosRule::NoRepeat => next != previous,
+            ChaosRule::NoNeighbor => next != (previous + 1) % count && next != (previous + count - 1) % count,
+            ChaosRule::NoCounterclockwise => next != (previous + 1) % count,
+        }
+    }
+}
+
+/// Picks maps at random in proportion to their probabilities, following a rule.
+struct MapPicker<'a> {
+    maps: &'a [IfsMap],
+    /// Running sum of the probabilities, normalized to end at 1.
+    cumulative: Vec<f64>,
+    rule: ChaosRule,
+}
+
+impl<'a> MapPicker<'a> {
+    /// Prepares the maps for the chaos game.
+    ///
+    /// # Returns
+    ///
+    /// The picker, or an error message for negative probabilities or if the rule
+    /// leaves no map with a positive probability after some map.
+    fn new(maps: &'a [IfsMap], rule: ChaosRule) -> Result<MapPicker<'a>, String> {
+        if maps.iter().any(|map| map.probability < 0.0) {
+            return Err("Map probabilities must not be negative".to_string());
+        }
+        let total: f64 = maps.iter().map(|map| map.probability).sum();
+        if total <= 0.0 {
+            return Err("At least one map needs a positive probability".to_string());
+        }
+        for previous in 0..maps.len() {
+            if !(0..maps.len()).any(|next| maps[next].probability > 0.0 && rule.allows(previous, next, maps.len())) {
+                return Err(format!("The chaos rule allows no map after map {} of {}", previous + 1, maps.len()));
+            }
+        }
+        let mut sum = 0.0;
+        let cumulative = maps
+            .iter()
+            .map(|map| {
+                sum += map.probability;
+                sum / total
+            })
+            .collect();
+        Ok(MapPicker { maps, cumulative, rule })
+    }
+
+    /// Picks the next map, redrawing until the rule allows it.
+    fn pick(&self, previous: usize, rng: &mut Rng) -> usize {
+        loop {
+            let u = rng.next_f64();
+            let next = self.cumulative.partition_point(|&edge| edge <= u).min(self.maps.len() - 1);
+            if self.rule.allows(previous, next, self.maps.len()) {
+                return next;
+            }
+        }
+    }
+
+    /// Plays the chaos game from a random point and passes the visited points to `plot`.
+    fn play(&self, rng: &mut Rng, points: u64, plot: &mut dyn FnMut(f64, f64)) {
+        let (mut x, mut y) = (rng.range(-1.0, 1.0), rng.range(-1.0, 1.0));
+        let mut previous = self.pick(0, rng);
+        for step in 0..FUSE_ITERATIONS as u64 + points {
+            previous = self.pick(previous, rng);
+            (x, y) = self.maps[previous].apply(x, y);
+            if step >= FUSE_ITERATIONS as u64 {
+                plot(x, y);
+            }
+        }
+    }
+}
+
+/// Generates an image of an iterated function system with the chaos game.
+///
+/// A point repeatedly jumps through a map picked at random, and the density of
+/// the visited points is drawn as dark ink on white (see `render_density`).
+///
+/// Exits the process with an error message if the system or the rule is
+/// unknown, or if the maps and the rule leave nothing to pick.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path to save the generated image.
+/// * `samples` - Number of chaos game steps.
+/// * `seed` - Seed of the random number generator.
+/// * `ifs` - 'fern', 'sierpinski', 'carpet', 'dragon', 'chaos' (regular polygon) or 'custom' (`maps`).
+/// * `maps` - Maps of a 'custom' system.
+/// * `vertices` - Number of polygon vertices for 'chaos'.
+/// * `ratio` - Fraction of the distance to the chosen vertex covered by a jump for 'chaos'.
+/// * `rule` - Restriction on consecutive maps: 'any', 'no-repeat', 'no-neighbor' or 'no-counterclockwise'.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_ifs(width: u32, height: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, samples: u64, seed: u64, ifs: &str, maps: &[IfsMap], vertices: u32, ratio: f64, rule: &str) {
+    let rule = ChaosRule::from_name(rule);
+    let maps = match ifs {
+        "chaos" => Ok(polygon_maps(vertices, ratio)),
+        "custom" => Ok(maps.to_vec()),
+        _ => preset_maps(ifs),
+    };
+    let picker = maps.as_deref().map_err(String::clone).and_then(|maps| MapPicker::new(maps, rule?)).unwrap_or_else(|e| {
+        eprintln!("Invalid IFS: {}", e);
+        std::process::exit(1);
+    });
+
+    let mut imgbuf = render_density(width, height, center_x, center_y, zoom, m_size, samples, seed, |rng, points, plot| picker.play(rng, points, plot));
+
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_polygon_maps() {
+        let maps = polygon_maps(4, 0.5);
+        assert_eq!(maps.len(), 4);
+        // Each map fixes its vertex: the first one points up, the second one left
+        let (x, y) = maps[0].apply(0.0, 1.0);
+        assert!(x.abs() < 1e-12 && (y - 1.0).abs() < 1e-12);
+        let (x, y) = maps[1].apply(0.0, 0.0);
+        assert!((x - -0.5).abs() < 1e-12 && y.abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_preset_maps() {
+        let fern = preset_maps("fern").unwrap();
+        assert_eq!(fern.len(), 4);
+        assert!((fern.iter().map(|map| map.probability).sum::<f64>() - 1.0).abs() < 1e-12);
+        assert_eq!(preset_maps("sierpinski"), Ok(polygon_maps(3, 0.5)));
+        assert_eq!(preset_maps("carpet").unwrap().len(), 8);
+        // The dragon maps 0 and 1 onto the tip of its middle fold and back
+        let dragon = preset_maps("dragon").unwrap();
+        assert_eq!(dragon[0].apply(1.0, 0.0), (0.5, 0.5));
+        assert_eq!(dragon[1].apply(1.0, 0.0), (0.5, 0.5));
+    }
+
+    #[test]
+    fn test_unknown_preset() {
+        assert_eq!(preset_maps("koch"), Err("Unknown IFS: koch".to_string()));
+    }
+
+    #[test]
+    fn test_chaos_rules() {
+        assert_eq!(ChaosRule::from_name("any"), Ok(ChaosRule::Any));
+        assert_eq!(ChaosRule::from_name("no-neighbour"), Err("Unknown chaos rule: no-neighbour".to_string()));
+        assert!(ChaosRule::NoRepeat.allows(0, 1, 4) && !ChaosRule::NoRepeat.allows(2, 2, 4));
+        assert!(!ChaosRule::NoNeighbor.allows(0, 3, 4) && !ChaosRule::NoNeighbor.allows(0, 1, 4));
+        assert!(ChaosRule::NoNeighbor.allows(0, 2, 4) && ChaosRule::NoNeighbor.allows(0, 0, 4));
+        assert!(!ChaosRule::NoCounterclockwise.allows(3, 0, 4) && ChaosRule::NoCounterclockwise.allows(0, 3, 4));
+    }
+
+    #[test]
+    fn test_map_picker() {
+        let maps = polygon_maps(4, 0.5);
+        let picker = MapPicker::new(&maps, ChaosRule::NoRepeat).unwrap();
+        let mut rng = Rng::new(5);
+        let mut previous = 0;
+        for _ in 0..1000 {
+            let next = picker.pick(previous, &mut rng);
+            assert_ne!(next, previous);
+            previous = next;
+        }
+
+        // A single map can't avoid repeating itself
+        assert!(MapPicker::new(&maps[..1], ChaosRule::NoRepeat).is_err());
+        assert!(MapPicker::new(&[], ChaosRule::Any).is_err());
+        assert!(MapPicker::new(&[IfsMap::new(-1.0, [0.0; 6])], ChaosRule::Any).is_err());
+    }
+
+    #[test]
+    fn test_chaos_game_stays_on_fractal() {
+        // Every point of the Sierpinski carpet chaos game stays in [-1, 1]² and
+        // never lands in the open middle square
+        let maps = preset_maps("carpet").unwrap();
+        let picker = MapPicker::new(&maps, ChaosRule::Any).unwrap();
+        let mut points = Vec::new();
+        picker.play(&mut Rng::new(1), 1000, &mut |x, y| points.push((x, y)));
+        assert_eq!(points.len(), 1000);
+        assert!(points.iter().all(|&(x, y)| x.abs() <= 1.0 && y.abs() <= 1.0));
+        assert!(points.iter().all(|&(x, y)| x.abs() >= 1.0 / 3.0 - 1e-6 || y.abs() >= 1.0 / 3.0 - 1e-6));
+    }
+
+    #[test]
+    fn test_render_ifs_density() {
+        // The Sierpinski carpet inks [-1, 1]², except for the open middle square
+        let maps = preset_maps("carpet").unwrap();
+        let picker = MapPicker::new(&maps, ChaosRule::Any).unwrap();
+        let center = Decimal::from(0.0);
+        let imgbuf = render_density(40, 30, &center, &center, 1.0, 4.0, 100_000, 0, |rng, points, plot| picker.play(rng, points, plot));
+        let white = Rgba([255, 255, 255, 255]);
+        for (x, y) in [(19, 14), (20, 14), (19, 15), (20, 15), (0, 0), (39, 29)] {
+            assert_eq!(*imgbuf.get_pixel(x, y), white, "({}, {})", x, y);
+        }
+        assert!(imgbuf.pixels().filter(|pixel| **pixel != white).count() > 100);
+    }
+}
@@ -0,0 +1,167 @@
+use image::{ImageBuffer, Rgba, RgbaImage};
+
+use crate::decimal::Decimal;
+use crate::generate_mandelbrot::{coordinate_mapper, save_with_zoom_text};
+use crate::render_parallel::render_parallel;
+
+/// Iterations of the logistic map discarded before the exponent is measured.
+const WARMUP_ITERATIONS: u64 = 200;
+
+/// Parses an A/B sequence such as "AABAB".
+///
+/// # Returns
+///
+/// One entry per letter, `false` for A and `true` for B, or an error message for
+/// empty sequences and other letters.
+pub fn parse_sequence(sequence: &str) -> Result<Vec<bool>, String> {
+    if sequence.is_empty() {
+        return Err("The sequence must not be empty".to_string());
+    }
+    sequence
+        .chars()
+        .map(|letter| match letter.to_ascii_uppercase() {
+            'A' => Ok(false),
+            'B' => Ok(true),
+            _ => Err(format!("Unexpected letter '{}' in sequence '{}', expected A or B", letter, sequence)),
+        })
+        .collect()
+}
+
+/// Computes the Lyapunov exponent of the logistic map x ← r·x·(1 - x).
+///
+/// The growth rate r alternates between `a` and `b` following the sequence.
+///
+/// # Arguments
+///
+/// * `a` - Growth rate for the letter A.
+/// * `b` - Growth rate for the letter B.
+/// * `sequence` - Parsed A/B sequence (`true` for B).
+/// * `iterations` - Number of iterations averaged after the warm-up.
+///
+/// # Returns
+///
+/// The average of ln|r·(1 - 2x)|: negative for stable orbits, positive for chaos,
+/// and negative infinity for superstable orbits that hit x = ½.
+fn lyapunov_exponent(a: f64, b: f64, sequence: &[bool], iterations: u32) -> f64 {
+    // Counted in u64 so that the warm-up plus u32::MAX iterations can't overflow
+    let rate = |n: u64| if sequence[(n % sequence.len() as u64) as usize] { b } else { a };
+    let mut x = 0.5;
+    for n in 0..WARMUP_ITERATIONS {
+        x = rate(n) * x * (1.0 - x);
+    }
+    let mut sum = 0.0;
+    for n in WARMUP_ITERATIONS..WARMUP_ITERATIONS + iterations as u64 {
+        let r = rate(n);
+        x = r * x * (1.0 - x);
+        sum += (r * (1.0 - 2.0 * x)).abs().ln();
+    }
+    sum / iterations.max(1) as f64
+}
+
+/// Converts a Lyapunov exponent to a color.
+///
+/// Stable regions fade from black at λ = 0 to gold for strongly negative λ,
+/// chaotic regions from black to blue for large positive λ.
+fn lyapunov_color(exponent: f64) -> Rgba<u8> {
+    if exponent.is_nan() {
+        return Rgba([0, 0, 0, 255]);
+    }
+    if exponent <= 0.0 {
+        let t = 1.0 - exponent.exp();
+        Rgba([(t * 255.0) as u8, (t * 200.0) as u8, (t * 40.0) as u8, 255])
+    } else {
+        let t = 1.0 - (-exponent).exp();
+        Rgba([(t * 30.0) as u8, (t * 80.0) as u8, (t * 255.0) as u8, 255])
+    }
+}
+
+/// Generates a Lyapunov fractal image over the (a, b) parameter plane.
+///
+/// Exits the process with an error message if the sequence contains letters
+/// other than A and B.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `max_iterations` - Number of iterations averaged per point.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path to save the generated image.
+/// * `sequence` - A/B sequence of growth rates, e.g. "AABAB".
+#[allow(clippy::too_many_arguments)]
+pub fn generate_lyapunov(width: u32, height: u32, max_iterations: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, sequence: &str) {
+    let sequence = parse_sequence(sequence).unwrap_or_else(|e| {
+        eprintln!("Invalid Lyapunov sequence: {}", e);
+        std::process::exit(1);
+    });
+    let mut imgbuf = render_lyapunov(width, height, max_iterations, center_x, center_y, zoom, m_size, &sequence);
+
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+/// Maps every pixel to the growth rates (a, b) and colors it by the Lyapunov
+/// exponent of the logistic map driven by `sequence`.
+#[allow(clippy::too_many_arguments)]
+fn render_lyapunov(width: u32, height: u32, max_iterations: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, sequence: &[bool]) -> RgbaImage {
+    let center_x = center_x.to_f64();
+    let center_y = center_y.to_f64();
+
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
+
+    render_parallel(&mut imgbuf, |x, y| {
+        let (a, b) = coordinate_mapper(x, y, width, height, zoom, center_x, center_y, m_size);
+
+        lyapunov_color(lyapunov_exponent(a, b, sequence, max_iterations))
+    });
+    imgbuf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sequence() {
+        assert_eq!(parse_sequence("AaBb"), Ok(vec![false, false, true, true]));
+        assert!(parse_sequence("").is_err());
+        assert!(parse_sequence("ABC").is_err());
+    }
+
+    #[test]
+    fn test_lyapunov_exponent() {
+        // r = 2.5 has an attracting fixed point x = 0.6 with multiplier r(1 - 2x) = -0.5
+        let stable = lyapunov_exponent(2.5, 2.5, &[false], 1000);
+        assert!((stable - 0.5f64.ln()).abs() < 1e-9);
+        // r = 3.9 is chaotic with an exponent of about 0.5
+        let chaotic = lyapunov_exponent(3.9, 3.9, &[true], 100000);
+        assert!((chaotic - 0.49).abs() < 0.02);
+        // r = 2 is superstable: x = ½ is a fixed point
+        assert_eq!(lyapunov_exponent(2.0, 3.0, &[false], 100), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_lyapunov_color() {
+        assert_eq!(lyapunov_color(0.0), Rgba([0, 0, 0, 255]));
+        assert_eq!(lyapunov_color(f64::NEG_INFINITY), Rgba([255, 200, 40, 255]));
+        assert_eq!(lyapunov_color(f64::INFINITY), Rgba([30, 80, 255, 255]));
+        assert_eq!(lyapunov_color(f64::NAN), Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_render_lyapunov() {
+        // With the sequence "B" the exponent only depends on b, which grows along y
+        let origin = Decimal::from(0.0);
+        let imgbuf = render_lyapunov(12, 60, 100, &origin, &origin, 1.0, 8.0, &[true]);
+        for y in 0..60 {
+            assert!((0..12).all(|x| imgbuf.get_pixel(x, y) == imgbuf.get_pixel(0, y)), "row {}", y);
+        }
+        assert!((1..60).any(|y| imgbuf.get_pixel(0, y) != imgbuf.get_pixel(0, 0)));
+    }
+}
@@ -1,38 +1,666 @@
-use image::{ImageBuffer, Rgba};
+use image::{ImageBuffer, Rgba, RgbaImage};
+use num_complex::Complex64;
 use imageproc::drawing::draw_text_mut;
 use rusttype::{Font, Scale};
 
+use crate::big_fixed::BigFixed;
+use crate::decimal::{ComplexDecimal, Decimal};
+use crate::formula::Formula;
+use crate::orbit_trap::{Trap, TrapHit};
 use crate::hsv_to_rgb::hsv_to_rgb;
+use crate::perturbation::{calc_perturbed, precision_for, reference_orbit};
+use crate::render_parallel::render_parallel;
 
 /// Base center coordinates for the Mandelbrot set
 const BASE_CENTER_X: f64 = 0.0;
 const BASE_CENTER_Y: f64 = 0.0;
 
+/// Outcome of iterating a single point of an escape-time fractal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Escape {
+    /// Number of iterations performed before the orbit escaped (or `max_iterations`).
+    pub iterations: u32,
+    /// Squared magnitude of z after the last iteration.
+    pub norm_sqr: f64,
+    /// Squared magnitude of the derivative dz/dc (or dz/dz₀ for Julia sets) after the
+    /// last iteration, used for distance estimation. Zero if it isn't tracked.
+    pub derivative_norm_sqr: f64,
+    /// Attracting cycle the orbit was found to converge to, if any.
+    pub cycle: Option<Cycle>,
+    /// Closest approach of the orbit to the orbit trap, if a trap was given and hit.
+    pub trap: Option<TrapHit>,
+    /// Running sum of the orbit average statistic, if one was given.
+    pub average: Option<AverageSum>,
+}
+
+/// Attracting cycle of an interior point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cycle {
+    /// Number of iterations after which the orbit repeats.
+    pub period: u32,
+    /// Real part of the cycle multiplier λ = (f^period)'(z).
+    pub multiplier_re: f64,
+    /// Imaginary part of the cycle multiplier; |λ| < 1 for attracting cycles.
+    pub multiplier_im: f64,
+}
+
+/// Two orbit points closer than this fraction of a pixel on both axes are considered
+/// equal by periodicity detection. An absolute tolerance would mistake slowly
+/// escaping orbits near the boundary for cycles once pixels get that small; at
+/// deep zooms the scaled tolerance falls below the f64 spacing of the orbit
+/// points, which leaves only exact cycles.
+const PERIODICITY_TOLERANCE: f64 = 1e-10;
+
+/// Coloring mode for escape-time fractals.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    /// Integer iteration count cycled through `bands` hues (hard band edges).
+    Bands,
+    /// Continuous (normalized) iteration count mapped onto a cyclic hue palette.
+    Smooth,
+    /// Black-and-white rendering of the estimated distance to the set boundary.
+    Distance,
+    /// Closest approach of the orbit to an orbit trap.
+    Trap,
+    /// Average of a statistic along the orbit, interpolated with the smooth iteration count.
+    Average(OrbitAverage),
+}
+
+/// Statistic averaged along the orbit by the averaging color modes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrbitAverage {
+    /// ½·sin(density·arg z) + ½ (stripe average) with the given density.
+    Stripe(f64),
+    /// Position of |z| between the bounds | |z - c| - |c| | and |z - c| + |c|
+    /// given by the triangle inequality (triangle inequality average).
+    Triangle,
+}
+
+/// Running sum of an orbit average statistic.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AverageSum {
+    /// Sum of the statistic over the orbit.
+    pub sum: f64,
+    /// Value of the statistic at the last orbit point.
+    pub last: f64,
+    /// Number of orbit points summed.
+    pub count: u32,
+}
+
+/// Coloring of points that never escape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InteriorMode {
+    /// Black.
+    Solid,
+    /// Hue by the period of the attracting cycle.
+    Period,
+    /// Brightness by the magnitude of the cycle multiplier (dark at the centers of components).
+    Multiplier,
+    /// Hue by the angle of the cycle multiplier.
+    MultiplierAngle,
+}
+
+impl InteriorMode {
+    /// Parses an interior mode name as used on the command line and in config files.
+    ///
+    /// # Returns
+    ///
+    /// The interior mode, or an error message if the name is unknown.
+    pub fn from_name(name: &str) -> Result<InteriorMode, String> {
+        match name {
+            "solid" => Ok(InteriorMode::Solid),
+            "period" => Ok(InteriorMode::Period),
+            "multiplier" => Ok(InteriorMode::Multiplier),
+            "multiplier_angle" => Ok(InteriorMode::MultiplierAngle),
+            _ => Err(format!("Unknown interior mode: {}", name)),
+        }
+    }
+}
+
+/// Parses an interior mode name, exiting the process with an error message if it is unknown.
+fn interior_or_exit(name: &str) -> InteriorMode {
+    InteriorMode::from_name(name).unwrap_or_else(|e| {
+        eprintln!("Invalid interior mode: {}", e);
+        std::process::exit(1);
+    })
+}
+
+impl ColorMode {
+    /// Parses a color mode name as used on the command line and in config files.
+    ///
+    /// `stripe_density` is only used by 'stripe'.
+    ///
+    /// # Returns
+    ///
+    /// The color mode, or an error message if the name is unknown.
+    pub fn from_name(name: &str, stripe_density: f64) -> Result<ColorMode, String> {
+        match name {
+            "bands" => Ok(ColorMode::Bands),
+            "smooth" => Ok(ColorMode::Smooth),
+            "distance" => Ok(ColorMode::Distance),
+            "trap" => Ok(ColorMode::Trap),
+            "stripe" => Ok(ColorMode::Average(OrbitAverage::Stripe(stripe_density))),
+            "triangle" => Ok(ColorMode::Average(OrbitAverage::Triangle)),
+            _ => Err(format!("Unknown color mode: {}", name)),
+        }
+    }
+}
+
+/// Parses a color mode name, exiting the process with an error message if it is unknown.
+fn color_mode_or_exit(name: &str, stripe_density: f64) -> ColorMode {
+    ColorMode::from_name(name, stripe_density).unwrap_or_else(|e| {
+        eprintln!("Invalid color mode: {}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Iteration formula of an escape-time fractal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fractal {
+    /// z² + c
+    Mandelbrot,
+    /// (|Re z| + i|Im z|)² + c
+    BurningShip,
+    /// conj(z)² + c (Mandelbar)
+    Tricorn,
+    /// z^d + c for an integer or real exponent d
+    Multibrot(f64),
+}
+
+impl OrbitAverage {
+    /// Value of the statistic at the orbit point z of `z = f(z) + c`, between 0 and 1.
+    ///
+    /// Returns `None` where the triangle inequality bounds coincide.
+    fn statistic(self, zx: f64, zy: f64, cx: f64, cy: f64) -> Option<f64> {
+        match self {
+            OrbitAverage::Stripe(density) => Some(0.5 * (density * zy.atan2(zx)).sin() + 0.5),
+            OrbitAverage::Triangle => {
+                // |f(z)| with f(z) = z - c, e.g. |z_prev|² for z² + c
+                let f_norm = (zx - cx).hypot(zy - cy);
+                let c_norm = cx.hypot(cy);
+                let low = (f_norm - c_norm).abs();
+                let high = f_norm + c_norm;
+                (high > low).then(|| (zx.hypot(zy) - low) / (high - low))
+            }
+        }
+    }
+}
+
+impl Fractal {
+    /// Parses a fractal name as used on the command line and in config files.
+    ///
+    /// `power` is only used by 'multibrot' and must be greater than 1.
+    ///
+    /// # Returns
+    ///
+    /// The fractal, or an error message if the name is unknown or the power is invalid.
+    pub fn from_name(name: &str, power: f64) -> Result<Fractal, String> {
+        match name {
+            "mandelbrot" => Ok(Fractal::Mandelbrot),
+            "burning_ship" => Ok(Fractal::BurningShip),
+            "tricorn" => Ok(Fractal::Tricorn),
+            "multibrot" => {
+                if power.is_nan() || power <= 1.0 {
+                    return Err(format!("Multibrot power must be greater than 1, got {}", power));
+                }
+                Ok(Fractal::Multibrot(power))
+            }
+            _ => Err(format!("Unknown fractal: {}", name)),
+        }
+    }
+
+    /// Degree of the formula, which sets the growth rate of escaping orbits.
+    pub fn degree(&self) -> f64 {
+        match *self {
+            Fractal::Multibrot(power) => power,
+            _ => 2.0,
+        }
+    }
+}
+
+/// Parses a fractal name, exiting the process with an error message if it is invalid.
+fn fractal_or_exit(name: &str, power: f64) -> Fractal {
+    Fractal::from_name(name, power).unwrap_or_else(|e| {
+        eprintln!("Invalid fractal: {}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Iterates a fractal formula from a starting value until the orbit escapes.
+///
+/// With `TRACK_DERIVATIVE`, the derivative dz/dc (`dc` = 1, Mandelbrot) or
+/// dz/dz₀ (`dc` = 0, Julia) is tracked alongside z for distance estimation.
+/// Without it the derivative is left at zero and costs nothing.
+///
+/// # Arguments
+///
+/// * `fractal` - Iteration formula.
+/// * `zx` - Real part of the starting value z₀.
+/// * `zy` - Imaginary part of the starting value z₀.
+/// * `cx` - Real part of the constant c.
+/// * `cy` - Imaginary part of the constant c.
+/// * `dc` - 1.0 to differentiate with respect to c, 0.0 for z₀.
+/// * `max_iterations` - Maximum number of iterations to perform.
+/// * `bailout` - Escape radius; the orbit escapes once |z| exceeds it.
+/// * `pixel_size` - Size of a pixel in complex plane units, which scales the tolerance of cycle detection.
+/// * `trap` - Orbit trap to measure the orbit against, if any.
+/// * `average` - Statistic to average along the orbit, if any.
+///
+/// # Returns
+///
+/// The number of iterations before the orbit escapes (or `max_iterations`
+/// if it doesn't escape within the limit), together with the final |z|²
+/// used for smooth coloring.
+#[allow(clippy::too_many_arguments)]
+fn calc_escape<const TRACK_DERIVATIVE: bool>(fractal: Fractal, zx: f64, zy: f64, cx: f64, cy: f64, dc: f64, max_iterations: u32, bailout: f64, pixel_size: f64, trap: Option<&Trap>, average: Option<OrbitAverage>) -> Escape {
+    let z0 = (zx, zy);
+    let (derivative0, dc) = if TRACK_DERIVATIVE { ((1.0 - dc, 0.0), dc) } else { ((0.0, 0.0), 0.0) };
+    // f'(z)·dz for f'(z) = gx + i·gy
+    let chain = |gx: f64, gy: f64, dx: f64, dy: f64| {
+        if TRACK_DERIVATIVE { (gx * dx - gy * dy, gx * dy + gy * dx) } else { (0.0, 0.0) }
+    };
+    match fractal {
+        Fractal::Mandelbrot => escape_loop(z0, derivative0, dc, max_iterations, bailout, pixel_size, trap, average, (cx, cy), |x, y, dx, dy| {
+            let (ndx, ndy) = chain(2.0 * x, 2.0 * y, dx, dy);
+            (x * x - y * y + cx, 2.0 * x * y + cy, ndx, ndy)
+        }),
+        Fractal::BurningShip => escape_loop(z0, derivative0, dc, max_iterations, bailout, pixel_size, trap, average, (cx, cy), |x, y, dx, dy| {
+            // Fold into the first quadrant, then square
+            let (ndx, ndy) = chain(2.0 * x.abs(), 2.0 * y.abs(), dx * x.signum(), dy * y.signum());
+            (x * x - y * y + cx, 2.0 * (x * y).abs() + cy, ndx, ndy)
+        }),
+        Fractal::Tricorn => escape_loop(z0, derivative0, dc, max_iterations, bailout, pixel_size, trap, average, (cx, cy), |x, y, dx, dy| {
+            // d conj(z)² = 2 conj(z) conj(dz)
+            let (ndx, ndy) = chain(2.0 * x, -2.0 * y, dx, -dy);
+            (x * x - y * y + cx, -2.0 * x * y + cy, ndx, ndy)
+        }),
+        Fractal::Multibrot(power) if power.fract() == 0.0 && power <= 64.0 => {
+            let exponent = power as u32;
+            escape_loop(z0, derivative0, dc, max_iterations, bailout, pixel_size, trap, average, (cx, cy), |x, y, dx, dy| {
+                // z^d = z^(d-1) z and d/dz z^d = d z^(d-1)
+                let (px, py) = complex_powi(x, y, exponent - 1);
+                let (ndx, ndy) = chain(power * px, power * py, dx, dy);
+                (px * x - py * y + cx, px * y + py * x + cy, ndx, ndy)
+            })
+        }
+        Fractal::Multibrot(power) => escape_loop(z0, derivative0, dc, max_iterations, bailout, pixel_size, trap, average, (cx, cy), |x, y, dx, dy| {
+            let norm_sqr = x * x + y * y;
+            if norm_sqr == 0.0 {
+                return (cx, cy, 0.0, 0.0);
+            }
+            // Principal branch of z^(d-1) in polar form
+            let r = norm_sqr.powf((power - 1.0) / 2.0);
+            let theta = y.atan2(x) * (power - 1.0);
+            let (px, py) = (r * theta.cos(), r * theta.sin());
+            let (ndx, ndy) = chain(power * px, power * py, dx, dy);
+            (px * x - py * y + cx, px * y + py * x + cy, ndx, ndy)
+        }),
+    }
+}
+
+/// Runs the escape-time loop for a single iteration step.
+///
+/// `step` maps (z, dz) to (f(z), f'(z)·dz); `dc` is added to the derivative
+/// after every step.
+///
+/// Orbits that settle on an attracting cycle are stopped early: z is saved at
+/// iterations that double in spacing (Brent's method), and once a later value
+/// returns to the saved one, within a tolerance proportional to `pixel_size`,
+/// the cycle is recorded in the result.
+///
+/// With a `trap`, every orbit point after z₀ is measured against it. With an
+/// `average`, its statistic is summed over the orbit points after z₁, which for
+/// Mandelbrot sets only reflects the position of c.
+#[allow(clippy::too_many_arguments)]
+fn escape_loop(z0: (f64, f64), derivative0: (f64, f64), dc: f64, max_iterations: u32, bailout: f64, pixel_size: f64, trap: Option<&Trap>, average: Option<OrbitAverage>, c: (f64, f64), mut step: impl FnMut(f64, f64, f64, f64) -> (f64, f64, f64, f64)) -> Escape {
+    let bailout_sqr = bailout * bailout;
+    let periodicity_epsilon = PERIODICITY_TOLERANCE * pixel_size;
+    let (mut x0, mut y0) = z0;
+    let (mut dx, mut dy) = derivative0;
+    let mut iteration = 0;
+    let (mut saved_x, mut saved_y) = z0;
+    let mut since_saved = 0;
+    let mut save_interval = 1;
+    let mut trap_hit = None;
+    let mut average_sum = average.map(|_| AverageSum::default());
+
+    while x0 * x0 + y0 * y0 <= bailout_sqr && iteration < max_iterations {
+        (x0, y0, dx, dy) = step(x0, y0, dx, dy);
+        dx += dc;
+        iteration += 1;
+        if let Some(trap) = trap {
+            trap.visit(&mut trap_hit, x0, y0);
+        }
+        if let (Some(average), Some(sum)) = (average, average_sum.as_mut())
+            && iteration > 1
+            && let Some(value) = average.statistic(x0, y0, c.0, c.1)
+        {
+            sum.sum += value;
+            sum.last = value;
+            sum.count += 1;
+        }
+
+        since_saved += 1;
+        if (x0 - saved_x).abs() < periodicity_epsilon && (y0 - saved_y).abs() < periodicity_epsilon {
+            let cycle = cycle_at(x0, y0, since_saved, &mut step);
+            return Escape { iterations: max_iterations, norm_sqr: x0 * x0 + y0 * y0, derivative_norm_sqr: dx * dx + dy * dy, cycle: Some(cycle), trap: trap_hit, average: average_sum };
+        }
+        if since_saved == save_interval {
+            (saved_x, saved_y) = (x0, y0);
+            since_saved = 0;
+            save_interval *= 2;
+        }
+    }
+
+    Escape { iterations: iteration, norm_sqr: x0 * x0 + y0 * y0, derivative_norm_sqr: dx * dx + dy * dy, cycle: None, trap: trap_hit, average: average_sum }
+}
+
+/// Measures the multiplier of the cycle through a point.
+///
+/// The multiplier (f^period)'(z) is taken as a finite difference, which works
+/// for every formula including the non-analytic ones.
+fn cycle_at(x: f64, y: f64, period: u32, step: &mut impl FnMut(f64, f64, f64, f64) -> (f64, f64, f64, f64)) -> Cycle {
+    let h = 1e-7 * (x * x + y * y).sqrt().max(1.0);
+    let (mut ax, mut ay) = (x, y);
+    let (mut bx, mut by) = (x + h, y);
+    for _ in 0..period {
+        (ax, ay, _, _) = step(ax, ay, 0.0, 0.0);
+        (bx, by, _, _) = step(bx, by, 0.0, 0.0);
+    }
+    Cycle { period, multiplier_re: (bx - ax) / h, multiplier_im: (by - ay) / h }
+}
+
+/// Detects points of the main cardioid and the period-2 bulb of the Mandelbrot set.
+///
+/// These make up most of the interior area of the set, and their attracting cycles
+/// are known in closed form, so they don't need to be iterated at all.
+///
+/// # Returns
+///
+/// The attracting cycle and the squared magnitude of a point on it, or `None`
+/// if c lies outside both components.
+pub fn mandelbrot_component(cx: f64, cy: f64) -> Option<(Cycle, f64)> {
+    let c = Complex64::new(cx, cy);
+    let q = (cx - 0.25) * (cx - 0.25) + cy * cy;
+    if q * (q + (cx - 0.25)) <= 0.25 * cy * cy {
+        // Fixed point z = (1 - sqrt(1 - 4c)) / 2 with multiplier 2z
+        let z = (1.0 - (1.0 - 4.0 * c).sqrt()) / 2.0;
+        let multiplier = 2.0 * z;
+        return Some((Cycle { period: 1, multiplier_re: multiplier.re, multiplier_im: multiplier.im }, z.norm_sqr()));
+    }
+    if (cx + 1.0) * (cx + 1.0) + cy * cy <= 0.0625 {
+        // 2-cycle z = (-1 + sqrt(-3 - 4c)) / 2 with multiplier 4(c + 1)
+        let z = (-1.0 + (-3.0 - 4.0 * c).sqrt()) / 2.0;
+        let multiplier = 4.0 * (c + 1.0);
+        return Some((Cycle { period: 2, multiplier_re: multiplier.re, multiplier_im: multiplier.im }, z.norm_sqr()));
+    }
+    None
+}
+
+/// Raises a complex number to a non-negative integer power by repeated squaring.
+fn complex_powi(x: f64, y: f64, exponent: u32) -> (f64, f64) {
+    let (mut result_x, mut result_y) = (1.0, 0.0);
+    let (mut base_x, mut base_y) = (x, y);
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            (result_x, result_y) = (result_x * base_x - result_y * base_y, result_x * base_y + result_y * base_x);
+        }
+        (base_x, base_y) = (base_x * base_x - base_y * base_y, 2.0 * base_x * base_y);
+        exponent >>= 1;
+    }
+    (result_x, result_y)
+}
+
 /// Calculates the Mandelbrot iteration count for a given point in the complex plane.
 ///
 /// # Arguments
 ///
+/// * `fractal` - Iteration formula.
 /// * `cx` - Real part of the complex number.
 /// * `cy` - Imaginary part of the complex number.
 /// * `max_iterations` - Maximum number of iterations to perform.
+/// * `bailout` - Escape radius; the orbit escapes once |z| exceeds it.
+/// * `pixel_size` - Size of a pixel in complex plane units, which scales the tolerance of cycle detection.
+/// * `track_derivative` - Whether to track dz/dc for distance estimation.
+/// * `trap` - Orbit trap to measure the orbit against, if any.
+/// * `average` - Statistic to average along the orbit, if any.
 ///
 /// # Returns
 ///
-/// The number of iterations before the point escapes the Mandelbrot set,
-/// or `max_iterations` if it doesn't escape within the limit.
-fn calc_mandelbrot(cx: f64, cy: f64, max_iterations: u32) -> u32 {
-    let mut x0 = 0.0;
-    let mut y0 = 0.0;
-    let mut iteration = 0;
+/// The escape result of the orbit of 0 under the fractal formula.
+#[allow(clippy::too_many_arguments)]
+fn calc_mandelbrot(fractal: Fractal, cx: f64, cy: f64, max_iterations: u32, bailout: f64, pixel_size: f64, track_derivative: bool, trap: Option<&Trap>, average: Option<OrbitAverage>) -> Escape {
+    // Skip the iteration for the two largest components, whose cycles are known
+    if fractal == Fractal::Mandelbrot
+        && let Some((cycle, norm_sqr)) = mandelbrot_component(cx, cy)
+    {
+        return Escape { iterations: max_iterations, norm_sqr, derivative_norm_sqr: 0.0, cycle: Some(cycle), trap: None, average: None };
+    }
+    if track_derivative {
+        calc_escape::<true>(fractal, 0.0, 0.0, cx, cy, 1.0, max_iterations, bailout, pixel_size, trap, average)
+    } else {
+        calc_escape::<false>(fractal, 0.0, 0.0, cx, cy, 1.0, max_iterations, bailout, pixel_size, trap, average)
+    }
+}
 
-    while x0 * x0 + y0 * y0 <= 4.0 && iteration < max_iterations {
-        let xtemp = x0 * x0 - y0 * y0 + cx;
-        y0 = 2.0 * x0 * y0 + cy;
-        x0 = xtemp;
-        iteration += 1;
+/// Calculates the Julia set iteration count for a given starting point.
+///
+/// # Arguments
+///
+/// * `fractal` - Iteration formula.
+/// * `zx` - Real part of the starting point.
+/// * `zy` - Imaginary part of the starting point.
+/// * `julia_re` - Real part of the fixed constant c.
+/// * `julia_im` - Imaginary part of the fixed constant c.
+/// * `max_iterations` - Maximum number of iterations to perform.
+/// * `bailout` - Escape radius; the orbit escapes once |z| exceeds it.
+/// * `pixel_size` - Size of a pixel in complex plane units, which scales the tolerance of cycle detection.
+/// * `track_derivative` - Whether to track dz/dz₀ for distance estimation.
+/// * `trap` - Orbit trap to measure the orbit against, if any.
+/// * `average` - Statistic to average along the orbit, if any.
+///
+/// # Returns
+///
+/// The escape result of the orbit of the point under the fractal formula.
+#[allow(clippy::too_many_arguments)]
+fn calc_julia(fractal: Fractal, zx: f64, zy: f64, julia_re: f64, julia_im: f64, max_iterations: u32, bailout: f64, pixel_size: f64, track_derivative: bool, trap: Option<&Trap>, average: Option<OrbitAverage>) -> Escape {
+    if track_derivative {
+        calc_escape::<true>(fractal, zx, zy, julia_re, julia_im, 0.0, max_iterations, bailout, pixel_size, trap, average)
+    } else {
+        calc_escape::<false>(fractal, zx, zy, julia_re, julia_im, 0.0, max_iterations, bailout, pixel_size, trap, average)
     }
+}
+
+/// Calculates the iteration count of a user-defined formula for a given point.
+///
+/// # Arguments
+///
+/// * `formula` - Compiled iteration step.
+/// * `z0` - Starting value of the orbit.
+/// * `c` - Value of the variable `c`.
+/// * `pixel` - Value of the variable `pixel`.
+/// * `max_iterations` - Maximum number of iterations to perform.
+/// * `bailout` - Escape radius; the orbit escapes once |z| exceeds it.
+/// * `pixel_size` - Size of a pixel in complex plane units, which scales the tolerance of cycle detection.
+/// * `trap` - Orbit trap to measure the orbit against, if any.
+/// * `average` - Statistic to average along the orbit, if any.
+///
+/// # Returns
+///
+/// The escape result of the orbit of z₀ under the formula.
+#[allow(clippy::too_many_arguments)]
+fn calc_formula(formula: &Formula, z0: Complex64, c: Complex64, pixel: Complex64, max_iterations: u32, bailout: f64, pixel_size: f64, trap: Option<&Trap>, average: Option<OrbitAverage>) -> Escape {
+    let mut stack = formula.new_stack();
+    // The derivative of a user formula isn't known, so it isn't tracked
+    escape_loop((z0.re, z0.im), (0.0, 0.0), 0.0, max_iterations, bailout, pixel_size, trap, average, (c.re, c.im), |zx, zy, _, _| {
+        let z = formula.eval(Complex64::new(zx, zy), c, pixel, &mut stack);
+        (z.re, z.im, 0.0, 0.0)
+    })
+}
 
-    iteration
+/// Computes the normalized (fractional) iteration count of an escaped orbit.
+///
+/// Uses `n + 1 - log_d(ln|z| / ln(bailout))` for a formula of degree d, which is
+/// continuous across iteration boundaries, so neighbouring pixels blend smoothly
+/// instead of snapping to integer bands.
+///
+/// # Arguments
+///
+/// * `escape` - Result of the iteration.
+/// * `bailout` - Escape radius used for the iteration.
+/// * `degree` - Degree of the iterated formula (2 for z² + c).
+///
+/// # Returns
+///
+/// The fractional iteration count, or `None` if the orbit never escaped.
+pub fn smooth_iteration(escape: &Escape, bailout: f64, degree: f64) -> Option<f64> {
+    let log_bailout = bailout.max(1.0 + f64::EPSILON).ln();
+    let log_z = 0.5 * escape.norm_sqr.ln();
+    if escape.norm_sqr <= bailout * bailout || log_z <= 0.0 {
+        return None;
+    }
+    Some(escape.iterations as f64 + 1.0 - (log_z / log_bailout).ln() / degree.ln())
+}
+
+/// Estimates the distance from an escaped point to the boundary of the set.
+///
+/// Uses `|z| ln|z| / |dz|`, which is within a small constant factor of the true
+/// distance once |z| is large, so a bailout of 100 or more is recommended.
+///
+/// # Arguments
+///
+/// * `escape` - Result of the iteration, with the derivative tracked.
+/// * `bailout` - Escape radius used for the iteration.
+///
+/// # Returns
+///
+/// The estimated distance in complex plane units, or `None` if the orbit never escaped.
+pub fn distance_estimate(escape: &Escape, bailout: f64) -> Option<f64> {
+    if escape.norm_sqr <= bailout * bailout {
+        return None;
+    }
+    let norm = escape.norm_sqr.sqrt();
+    Some(norm * norm.ln() / escape.derivative_norm_sqr.sqrt())
+}
+
+/// Computes the orbit average of an escaped point.
+///
+/// The averages with and without the last orbit point are blended with the
+/// fractional part of the smooth iteration count, so the result is continuous
+/// across iteration band edges.
+///
+/// # Arguments
+///
+/// * `escape` - Result of the iteration, with an average statistic summed.
+/// * `bailout` - Escape radius used for the iteration.
+/// * `degree` - Degree of the iteration formula.
+///
+/// # Returns
+///
+/// The average between 0 and 1, or `None` if the orbit never escaped or was too short.
+pub fn average_value(escape: &Escape, bailout: f64, degree: f64) -> Option<f64> {
+    let mu = smooth_iteration(escape, bailout, degree)?;
+    let average = escape.average.filter(|average| average.count > 0)?;
+    let current = average.sum / average.count as f64;
+    let previous = if average.count > 1 { (average.sum - average.last) / (average.count - 1) as f64 } else { current };
+    let fraction = (mu - escape.iterations as f64).clamp(0.0, 1.0);
+    Some(previous + (current - previous) * fraction)
+}
+
+/// Converts an iteration result to a color.
+///
+/// # Arguments
+///
+/// * `escape` - Result of the iteration.
+/// * `bands` - Number of color bands.
+/// * `color_mode` - How the iteration count is mapped to hue.
+/// * `interior_mode` - How points that never escape are colored.
+/// * `bailout` - Escape radius used for the iteration.
+/// * `degree` - Degree of the iterated formula.
+/// * `pixel_size` - Size of a pixel in complex plane units.
+/// * `trap` - Orbit trap the orbit was measured against, if any.
+#[allow(clippy::too_many_arguments)]
+fn escape_color(escape: &Escape, bands: u32, color_mode: ColorMode, interior_mode: InteriorMode, bailout: f64, degree: f64, pixel_size: f64, trap: Option<&Trap>) -> Rgba<u8> {
+    if escape.norm_sqr <= bailout * bailout {
+        return interior_color(escape, bands, interior_mode);
+    }
+    match color_mode {
+        ColorMode::Bands => match escape.iterations {
+            0 => Rgba([0, 0, 0, 255]), // Black for points that didn't escape
+            iteration => {
+                let band_index = (iteration % bands) as f64;
+                let hue = if bands > 1 {
+                    band_index / (bands - 1) as f64 * 240.0
+                } else {
+                    0.0
+                };
+                hsv_to_rgb(hue as f32, 255, 255)
+            }
+        },
+        ColorMode::Smooth => match smooth_iteration(escape, bailout, degree) {
+            // Interior points are black
+            None => Rgba([0, 0, 0, 255]),
+            Some(mu) => {
+                // One full trip around the hue circle every `bands` iterations
+                let hue = (mu / bands.max(1) as f64).rem_euclid(1.0) * 360.0;
+                hsv_to_rgb(hue as f32, 255, 255)
+            }
+        },
+        ColorMode::Distance => match distance_estimate(escape, bailout) {
+            // Interior points are black
+            None => Rgba([0, 0, 0, 255]),
+            Some(distance) => {
+                // Measured in pixels, so boundary lines keep their width at every zoom
+                let shade = (distance / pixel_size).clamp(0.0, 1.0);
+                let gray = (shade * 255.0) as u8;
+                Rgba([gray, gray, gray, 255])
+            }
+        },
+        ColorMode::Trap => match (trap, &escape.trap) {
+            (Some(trap), Some(hit)) => trap.color(hit),
+            // Orbits that never hit an image trap are black
+            _ => Rgba([0, 0, 0, 255]),
+        },
+        ColorMode::Average(_) => match average_value(escape, bailout, degree) {
+            None => Rgba([0, 0, 0, 255]),
+            Some(average) => hsv_to_rgb((average * 240.0) as f32, 255, 255),
+        },
+    }
+}
+
+/// Converts the attracting cycle of a point that never escaped to a color.
+///
+/// Points whose cycle wasn't detected within `max_iterations` are black.
+///
+/// # Arguments
+///
+/// * `escape` - Result of the iteration.
+/// * `bands` - Number of color bands.
+/// * `interior_mode` - How the cycle is mapped to a color.
+fn interior_color(escape: &Escape, bands: u32, interior_mode: InteriorMode) -> Rgba<u8> {
+    let cycle = match escape.cycle {
+        Some(cycle) if interior_mode != InteriorMode::Solid => cycle,
+        _ => return Rgba([0, 0, 0, 255]),
+    };
+    match interior_mode {
+        InteriorMode::Solid => Rgba([0, 0, 0, 255]),
+        InteriorMode::Period => {
+            let bands = bands.max(1);
+            let hue = ((cycle.period - 1) % bands) as f64 / bands as f64 * 360.0;
+            hsv_to_rgb(hue as f32, 255, 255)
+        }
+        InteriorMode::Multiplier => {
+            let magnitude = cycle.multiplier_re.hypot(cycle.multiplier_im).min(1.0);
+            let gray = (magnitude * 255.0) as u8;
+            Rgba([gray, gray, gray, 255])
+        }
+        InteriorMode::MultiplierAngle => {
+            let angle = cycle.multiplier_im.atan2(cycle.multiplier_re);
+            let hue = (angle + std::f64::consts::PI) / (2.0 * std::f64::consts::PI) * 360.0;
+            hsv_to_rgb(hue as f32, 255, 255)
+        }
+    }
 }
 
 /// Maps integer image coordinates to floating-point virtual image coordinates.
@@ -51,34 +679,70 @@ fn calc_mandelbrot(cx: f64, cy: f64, max_iterations: u32) -> u32 {
 /// # Returns
 ///
 /// A tuple (cx, cy) representing the complex plane coordinates.
-fn coordinate_mapper(x: u32, y: u32, width: u32, height: u32, zoom: f64, center_x: f64, center_y: f64, m_size: f64) -> (f64, f64) {
-    // Calculate scales based on output dimensions and zoom level
-    // Base ranges for zoom = 1.0 (full Mandelbrot view)
+#[allow(clippy::too_many_arguments)]
+pub fn coordinate_mapper(x: u32, y: u32, width: u32, height: u32, zoom: f64, center_x: f64, center_y: f64, m_size: f64) -> (f64, f64) {
+    let (actual_center_x, actual_center_y) = center_to_plane(center_x, center_y, width, height, m_size);
+    let (offset_x, offset_y) = pixel_offset(x, y, width, height, zoom, m_size);
+
+    // Map to complex plane
+    (offset_x + actual_center_x, offset_y + actual_center_y)
+}
+
+/// Maps complex plane coordinates to the image pixel containing them.
+///
+/// Inverse of `coordinate_mapper`, which maps a pixel to its corner.
+///
+/// # Arguments
+///
+/// * `cx` - Real part of the point.
+/// * `cy` - Imaginary part of the point.
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `zoom` - Zoom level.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `m_size` - Size of the mathematical space (square).
+///
+/// # Returns
+///
+/// The pixel (x, y), or `None` if the point lies outside the image.
+#[allow(clippy::too_many_arguments)]
+pub fn pixel_mapper(cx: f64, cy: f64, width: u32, height: u32, zoom: f64, center_x: f64, center_y: f64, m_size: f64) -> Option<(u32, u32)> {
+    let (actual_center_x, actual_center_y) = center_to_plane(center_x, center_y, width, height, m_size);
+    let (scale_x, scale_y) = view_scale(width, height, zoom, m_size);
+
+    let x = ((cx - actual_center_x) / scale_x + 0.5) * width as f64;
+    let y = ((cy - actual_center_y) / scale_y + 0.5) * height as f64;
+    if x < 0.0 || y < 0.0 || x >= width as f64 || y >= height as f64 {
+        return None;
+    }
+    Some((x as u32, y as u32))
+}
+
+/// Converts a center given as pixel offsets to complex plane coordinates.
+///
+/// The offsets are measured in the fixed units per pixel of the zoom = 1.0 view,
+/// so a center stays at the same point of the plane while zooming.
+///
+/// # Arguments
+///
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `m_size` - Size of the mathematical space (square).
+///
+/// # Returns
+///
+/// A tuple (cx, cy) with the center in the complex plane.
+fn center_to_plane(center_x: f64, center_y: f64, width: u32, height: u32, m_size: f64) -> (f64, f64) {
     let base_range = m_size; // Square mathematical space
-    
-    // Handle zoom: positive = zoom in, negative = zoom out
-    let zoom_factor = if zoom > 0.0 { 1.0 / zoom } else { zoom.abs().max(0.1) };
-    let (scale_x, scale_y) = if width > height {
-        // Wide image: base on height, extend width
-        let base_scale = if zoom >= 0.0 { base_range * zoom_factor } else { base_range / zoom_factor };
-        (base_scale * (width as f64 / height as f64), base_scale)
-    } else if height > width {
-        // Tall image: base on width, extend height  
-        let base_scale = if zoom >= 0.0 { base_range * zoom_factor } else { base_range / zoom_factor };
-        (base_scale, base_scale * (height as f64 / width as f64))
-    } else {
-        // Square image
-        let base_scale = if zoom >= 0.0 { base_range * zoom_factor } else { base_range / zoom_factor };
-        (base_scale, base_scale)
-    };
 
     // Fixed units per pixel based on zoom=1.0 scale
     let fixed_zoom_factor = 1.0; // zoom=1.0
-    let fixed_base_scale = if true { base_range * fixed_zoom_factor } else { base_range / fixed_zoom_factor }; // assuming zoom >=0
+    let fixed_base_scale = base_range * fixed_zoom_factor;
     let fixed_scale_x = if width > height {
         fixed_base_scale * (width as f64 / height as f64)
-    } else if height > width {
-        fixed_base_scale
     } else {
         fixed_base_scale
     };
@@ -98,19 +762,140 @@ fn coordinate_mapper(x: u32, y: u32, width: u32, height: u32, zoom: f64, center_
     let actual_center_x = effective_center_x * fixed_units_per_pixel_x;
     let actual_center_y = effective_center_y * fixed_units_per_pixel_y;
 
+    (actual_center_x, actual_center_y)
+}
+
+/// Converts a high-precision center given as pixel offsets to complex plane coordinates.
+///
+/// Same mapping as `center_to_plane`, but exact up to the precision of the input:
+/// the units per pixel of the zoom = 1.0 view are `m_size / min(width, height)`
+/// on both axes, so only an exact multiplication and integer division are needed.
+///
+/// # Arguments
+///
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `m_size` - Size of the mathematical space (square).
+///
+/// # Returns
+///
+/// A tuple (cx, cy) with the center in the complex plane.
+fn center_to_plane_precise(center_x: &BigFixed, center_y: &BigFixed, width: u32, height: u32, m_size: f64) -> (BigFixed, BigFixed) {
+    let m_size = BigFixed::from_f64(m_size, center_x.frac_bits());
+    let pixels = width.min(height);
+    let effective_center_x = &BigFixed::from_f64(BASE_CENTER_X, center_x.frac_bits()) + center_x;
+    let effective_center_y = &BigFixed::from_f64(BASE_CENTER_Y, center_y.frac_bits()) + center_y;
+
+    (
+        (&effective_center_x * &m_size).div_int(pixels),
+        (&effective_center_y * &m_size).div_int(pixels),
+    )
+}
+
+/// Converts a center in complex plane coordinates to pixel offsets.
+///
+/// Inverse of `center_to_plane_precise`. The offsets depend on the image size, so
+/// converting a plane location for each resolution keeps it at the same point of
+/// the set when `width`, `height` or `m_size` change.
+///
+/// # Arguments
+///
+/// * `center` - Center in the complex plane.
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `m_size` - Size of the mathematical space (square).
+///
+/// # Returns
+///
+/// A tuple (center_x, center_y) with the pixel offsets from base center.
+pub fn plane_to_center(center: &ComplexDecimal, width: u32, height: u32, m_size: f64) -> (Decimal, Decimal) {
+    let frac_bits = center.re.frac_bits_needed().max(center.im.frac_bits_needed());
+    // Extra bits absorb the truncation of the division before printing
+    let work_bits = frac_bits + 32;
+    let m_size = BigFixed::from_f64(m_size, work_bits);
+    let pixels = BigFixed::from_f64(width.min(height) as f64, work_bits);
+    let offset = |value: &Decimal, base: f64| {
+        let relative = &value.to_big_fixed(work_bits) - &BigFixed::from_f64(base, work_bits);
+        Decimal::from((&(&relative * &pixels) / &m_size).with_frac_bits(frac_bits))
+    };
+
+    (offset(&center.re, BASE_CENTER_X), offset(&center.im, BASE_CENTER_Y))
+}
+
+/// Converts a view scale in complex plane units to a zoom level.
+///
+/// # Arguments
+///
+/// * `scale` - Extent of the shorter image side in the complex plane.
+/// * `m_size` - Size of the mathematical space (square).
+pub fn scale_to_zoom(scale: f64, m_size: f64) -> f64 {
+    m_size / scale
+}
+
+/// Calculates the complex plane width and height covered by the image.
+///
+/// # Arguments
+///
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+///
+/// # Returns
+///
+/// A tuple (scale_x, scale_y) with the extent of the view.
+pub fn view_scale(width: u32, height: u32, zoom: f64, m_size: f64) -> (f64, f64) {
+    // Calculate scales based on output dimensions and zoom level
+    // Base ranges for zoom = 1.0 (full Mandelbrot view)
+    let base_range = m_size; // Square mathematical space
+
+    // Handle zoom: positive = zoom in, negative = zoom out
+    let zoom_factor = if zoom > 0.0 { 1.0 / zoom } else { zoom.abs().max(0.1) };
+    let base_scale = if zoom >= 0.0 { base_range * zoom_factor } else { base_range / zoom_factor };
+    if width > height {
+        // Wide image: base on height, extend width
+        (base_scale * (width as f64 / height as f64), base_scale)
+    } else if height > width {
+        // Tall image: base on width, extend height
+        (base_scale, base_scale * (height as f64 / width as f64))
+    } else {
+        // Square image
+        (base_scale, base_scale)
+    }
+}
+
+/// Calculates the offset of a pixel from the view center in the complex plane.
+///
+/// # Arguments
+///
+/// * `x` - X coordinate in the image (0 to width-1).
+/// * `y` - Y coordinate in the image (0 to height-1).
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+///
+/// # Returns
+///
+/// A tuple (dx, dy) with the offset from the center.
+fn pixel_offset(x: u32, y: u32, width: u32, height: u32, zoom: f64, m_size: f64) -> (f64, f64) {
+    let (scale_x, scale_y) = view_scale(width, height, zoom, m_size);
+
     // Normalize pixel coordinates to -1 to 1
     let x_norm = (x as f64 / width as f64) * 2.0 - 1.0;
     let y_norm = (y as f64 / height as f64) * 2.0 - 1.0;
 
-    // Map to complex plane
-    let cx = x_norm * (scale_x / 2.0) + actual_center_x;
-    let cy = y_norm * (scale_y / 2.0) + actual_center_y;
-
-    (cx, cy)
+    (x_norm * (scale_x / 2.0), y_norm * (scale_y / 2.0))
 }
 
 /// Generates a Mandelbrot set image.
 ///
+/// Exits the process with an error message if a mode, the fractal or the engine
+/// is unknown, or if the perturbation engine is combined with another fractal or
+/// with orbit trap or average coloring.
+///
 /// # Arguments
 ///
 /// * `width` - Width of the output image.
@@ -126,35 +911,226 @@ fn coordinate_mapper(x: u32, y: u32, width: u32, height: u32, zoom: f64, center_
 /// * `zoom_text_y` - Y position of zoom text.
 /// * `zoom_font_size` - Font size for zoom text.
 /// * `output_path` - Path to save the generated image.
-pub fn generate_mandelbrot(width: u32, height: u32, max_iterations: u32, bands: u32, center_x: f64, center_y: f64, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str) {
+/// * `color_mode` - Coloring mode: 'bands', 'smooth', 'distance', 'trap', 'stripe' or 'triangle'.
+/// * `interior` - Interior coloring: 'solid', 'period', 'multiplier' or 'multiplier_angle'.
+/// * `bailout` - Escape radius for the iteration.
+/// * `trap` - Orbit trap used by the 'trap' color mode.
+/// * `stripe_density` - Number of stripes per turn for the 'stripe' color mode.
+/// * `fractal` - Iteration formula: 'mandelbrot', 'burning_ship', 'tricorn' or 'multibrot'.
+/// * `power` - Exponent of the multibrot formula.
+/// * `engine` - Iteration engine: 'direct' (plain f64) or 'perturbation' (deep zoom).
+#[allow(clippy::too_many_arguments)]
+pub fn generate_mandelbrot(width: u32, height: u32, max_iterations: u32, bands: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, color_mode: &str, interior: &str, bailout: f64, trap: &Trap, stripe_density: f64, fractal: &str, power: f64, engine: &str) {
     // Validate zoom level
     let zoom = if zoom <= 0.0 { 1.0 } else { zoom };
+    let color_mode = color_mode_or_exit(color_mode, stripe_density);
+    let interior = interior_or_exit(interior);
+    let trap = (color_mode == ColorMode::Trap).then_some(trap);
+    let average = match color_mode {
+        ColorMode::Average(average) => Some(average),
+        _ => None,
+    };
+    let fractal = fractal_or_exit(fractal, power);
+    let (_, scale_y) = view_scale(width, height, zoom, m_size);
+    let pixel_size = scale_y / height as f64;
+    let track_derivative = color_mode == ColorMode::Distance;
 
-    let mut imgbuf = ImageBuffer::new(width, height);
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
 
-    for (x, y, pixel) in imgbuf.enumerate_pixels_mut() {
-        let (cx, cy) = coordinate_mapper(x, y, width, height, zoom, center_x, center_y, m_size);
+    match engine {
+        "direct" => {
+            let center_x = center_x.to_f64();
+            let center_y = center_y.to_f64();
 
-        let iteration = calc_mandelbrot(cx, cy, max_iterations);
+            render_parallel(&mut imgbuf, |x, y| {
+                let (cx, cy) = coordinate_mapper(x, y, width, height, zoom, center_x, center_y, m_size);
 
-        // Convert iteration to color
-        let color = match iteration {
-            0 => Rgba([0, 0, 0, 255]), // Black for points that didn't escape
-            _ => {
-                let band_index = (iteration % bands) as f64;
-                let hue = if bands > 1 {
-                    band_index / (bands - 1) as f64 * 240.0
-                } else {
-                    0.0
-                };
-                hsv_to_rgb(hue as f32, 255, 255)
+                let escape = calc_mandelbrot(fractal, cx, cy, max_iterations, bailout, pixel_size, track_derivative, trap, average);
+
+                escape_color(&escape, bands, color_mode, interior, bailout, fractal.degree(), pixel_size, trap)
+            });
+        }
+        "perturbation" => {
+            if fractal != Fractal::Mandelbrot {
+                eprintln!("The perturbation engine only supports the mandelbrot fractal");
+                std::process::exit(1);
             }
-        };
+            if trap.is_some() || average.is_some() {
+                eprintln!("The perturbation engine does not support orbit trap or average coloring");
+                std::process::exit(1);
+            }
+            // One reference orbit at the view center in high precision,
+            // per-pixel offsets from it in f64
+            let frac_bits = precision_for(pixel_size);
+            let (actual_center_x, actual_center_y) = center_to_plane_precise(
+                &center_x.to_big_fixed(frac_bits),
+                &center_y.to_big_fixed(frac_bits),
+                width,
+                height,
+                m_size,
+            );
+            let orbit = reference_orbit(&actual_center_x, &actual_center_y, max_iterations, bailout);
+
+            render_parallel(&mut imgbuf, |x, y| {
+                let (dcx, dcy) = pixel_offset(x, y, width, height, zoom, m_size);
+
+                let escape = calc_perturbed(&orbit, dcx, dcy, max_iterations, bailout);
+
+                escape_color(&escape, bands, color_mode, interior, bailout, fractal.degree(), pixel_size, trap)
+            });
+        }
+        _ => {
+            eprintln!("Unknown engine: {}", engine);
+            std::process::exit(1);
+        }
+    }
 
-        *pixel = color;
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+/// Generates a Julia set image for a fixed constant c.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `max_iterations` - Maximum number of iterations per point.
+/// * `bands` - Number of color bands.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path to save the generated image.
+/// * `color_mode` - Coloring mode: 'bands', 'smooth', 'distance', 'trap', 'stripe' or 'triangle'.
+/// * `interior` - Interior coloring: 'solid', 'period', 'multiplier' or 'multiplier_angle'.
+/// * `bailout` - Escape radius for the iteration.
+/// * `trap` - Orbit trap used by the 'trap' color mode.
+/// * `stripe_density` - Number of stripes per turn for the 'stripe' color mode.
+/// * `fractal` - Iteration formula: 'mandelbrot', 'burning_ship', 'tricorn' or 'multibrot'.
+/// * `power` - Exponent of the multibrot formula.
+/// * `julia_re` - Real part of the constant c.
+/// * `julia_im` - Imaginary part of the constant c.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_julia(width: u32, height: u32, max_iterations: u32, bands: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, color_mode: &str, interior: &str, bailout: f64, trap: &Trap, stripe_density: f64, fractal: &str, power: f64, julia_re: f64, julia_im: f64) {
+    // Validate zoom level
+    let zoom = if zoom <= 0.0 { 1.0 } else { zoom };
+    let color_mode = color_mode_or_exit(color_mode, stripe_density);
+    let interior = interior_or_exit(interior);
+    let trap = (color_mode == ColorMode::Trap).then_some(trap);
+    let average = match color_mode {
+        ColorMode::Average(average) => Some(average),
+        _ => None,
+    };
+    let fractal = fractal_or_exit(fractal, power);
+    let (_, scale_y) = view_scale(width, height, zoom, m_size);
+    let pixel_size = scale_y / height as f64;
+    let track_derivative = color_mode == ColorMode::Distance;
+    let center_x = center_x.to_f64();
+    let center_y = center_y.to_f64();
+
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
+
+    render_parallel(&mut imgbuf, |x, y| {
+        let (zx, zy) = coordinate_mapper(x, y, width, height, zoom, center_x, center_y, m_size);
+
+        let escape = calc_julia(fractal, zx, zy, julia_re, julia_im, max_iterations, bailout, pixel_size, track_derivative, trap, average);
+
+        escape_color(&escape, bands, color_mode, interior, bailout, fractal.degree(), pixel_size, trap)
+    });
+
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+/// Generates an escape-time image for a user-defined iteration formula.
+///
+/// Exits the process with an error message if the formula can't be parsed, the
+/// seed is unknown, or distance coloring is requested.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `max_iterations` - Maximum number of iterations per point.
+/// * `bands` - Number of color bands.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path to save the generated image.
+/// * `color_mode` - Coloring mode: 'bands', 'smooth', 'trap', 'stripe' or 'triangle'.
+/// * `interior` - Interior coloring: 'solid', 'period', 'multiplier' or 'multiplier_angle'.
+/// * `bailout` - Escape radius for the iteration.
+/// * `trap` - Orbit trap used by the 'trap' color mode.
+/// * `stripe_density` - Number of stripes per turn for the 'stripe' color mode.
+/// * `formula` - Iteration step in `z`, `c` and `pixel`, e.g. "z^3 - z + c".
+/// * `formula_seed` - 'mandelbrot' (z₀ = 0, c = pixel) or 'julia' (z₀ = pixel, c = julia constant).
+/// * `julia_re` - Real part of the constant c for the 'julia' seed.
+/// * `julia_im` - Imaginary part of the constant c for the 'julia' seed.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_formula(width: u32, height: u32, max_iterations: u32, bands: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, color_mode: &str, interior: &str, bailout: f64, trap: &Trap, stripe_density: f64, formula: &str, formula_seed: &str, julia_re: f64, julia_im: f64) {
+    // Validate zoom level
+    let zoom = if zoom <= 0.0 { 1.0 } else { zoom };
+    let color_mode = color_mode_or_exit(color_mode, stripe_density);
+    let interior = interior_or_exit(interior);
+    let trap = (color_mode == ColorMode::Trap).then_some(trap);
+    let average = match color_mode {
+        ColorMode::Average(average) => Some(average),
+        _ => None,
+    };
+    let center_x = center_x.to_f64();
+    let center_y = center_y.to_f64();
+    let formula_text = formula;
+    let formula = Formula::parse(formula_text).unwrap_or_else(|e| {
+        eprintln!("Failed to parse formula: {}", e);
+        eprintln!("  {}", formula_text);
+        eprintln!("  {}^", " ".repeat(e.column - 1));
+        std::process::exit(1);
+    });
+    // Smooth coloring needs the growth rate of escaping orbits; assume z² for non-polynomials
+    let degree = formula.degree().filter(|degree| *degree > 1.0).unwrap_or(2.0);
+    if color_mode == ColorMode::Distance {
+        eprintln!("Distance coloring is not supported for the formula function");
+        std::process::exit(1);
     }
+    let (_, scale_y) = view_scale(width, height, zoom, m_size);
+    let pixel_size = scale_y / height as f64;
+    let julia_seed = match formula_seed {
+        "mandelbrot" => false,
+        "julia" => true,
+        _ => {
+            eprintln!("Unknown formula seed: {}", formula_seed);
+            std::process::exit(1);
+        }
+    };
+
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
+
+    render_parallel(&mut imgbuf, |x, y| {
+        let (px, py) = coordinate_mapper(x, y, width, height, zoom, center_x, center_y, m_size);
+        let pixel = Complex64::new(px, py);
+        let (z0, c) = if julia_seed { (pixel, Complex64::new(julia_re, julia_im)) } else { (Complex64::new(0.0, 0.0), pixel) };
+
+        let escape = calc_formula(&formula, z0, c, pixel, max_iterations, bailout, pixel_size, trap, average);
 
-    // Draw zoom text
+        escape_color(&escape, bands, color_mode, interior, bailout, degree, pixel_size, trap)
+    });
+
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+/// Draws the zoom label onto a rendered image and saves it.
+///
+/// Exits the process with an error message if the font cannot be read or
+/// the image cannot be written.
+pub fn save_with_zoom_text(imgbuf: &mut RgbaImage, zoom: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str) {
     let font_data = match std::fs::read(font_path) {
         Ok(data) => data,
         Err(e) => {
@@ -166,7 +1142,7 @@ pub fn generate_mandelbrot(width: u32, height: u32, max_iterations: u32, bands:
     let font = Font::try_from_vec(font_data).expect("Failed to load font");
     let scale = Scale { x: zoom_font_size, y: zoom_font_size };
     let text = format!("ZOOM {:.1}", zoom);
-    draw_text_mut(&mut imgbuf, Rgba([0, 0, 0, 255]), zoom_text_x, zoom_text_y, scale, &font, &text);
+    draw_text_mut(imgbuf, Rgba([0, 0, 0, 255]), zoom_text_x, zoom_text_y, scale, &font, &text);
 
     imgbuf.save(output_path).unwrap_or_else(|e| {
         eprintln!("Failed to save image to '{}': {}", output_path, e);
@@ -240,10 +1216,9 @@ pub fn add_grid_to_image(input_path: &str) -> Result<(), Box<dyn std::error::Err
     }
 
     // Create output path
-    let output_path = if input_path.ends_with(".png") {
-        format!("{}_grid.png", &input_path[..input_path.len() - 4])
-    } else {
-        format!("{}_grid.png", input_path)
+    let output_path = match input_path.strip_suffix(".png") {
+        Some(stem) => format!("{}_grid.png", stem),
+        None => format!("{}_grid.png", input_path),
     };
 
     img.save(&output_path)?;
@@ -268,8 +1243,8 @@ mod tests {
         }
 
         generate_mandelbrot(
-            100, 100, 50, 8, 0.0, 0.0, 1.0, 10.0,
-            font_path, 5, 80, 12.0, output_path
+            100, 100, 50, 8, &Decimal::from(0.0), &Decimal::from(0.0), 1.0, 10.0,
+            font_path, 5, 80, 12.0, output_path, "bands", "solid", 2.0, &Trap::from_name("point", 0.0, 0.0, 1.0, 0.0, 0.1, None), 5.0, "mandelbrot", 2.0, "direct"
         );
 
         assert!(Path::new(output_path).exists());
@@ -286,7 +1261,7 @@ mod tests {
     fn test_mandelbrot_calculation() {
         // Test that the Mandelbrot calculation works for a known point
         // Point (0,0) should not escape within reasonable iterations
-        let iteration = calc_mandelbrot(0.0, 0.0, 100);
+        let iteration = calc_mandelbrot(Fractal::Mandelbrot, 0.0, 0.0, 100, 2.0, 1e-3, false, None, None).iterations;
 
         // (0,0) is in the Mandelbrot set, so it should reach max_iterations
         assert_eq!(iteration, 100);
@@ -296,9 +1271,376 @@ mod tests {
     fn test_mandelbrot_escape_point() {
         // Test that points outside the set escape quickly
         // Point (2,0) should escape immediately
-        let iteration = calc_mandelbrot(2.0, 0.0, 100);
+        let iteration = calc_mandelbrot(Fractal::Mandelbrot, 2.0, 0.0, 100, 2.0, 1e-3, false, None, None).iterations;
 
         // Should escape quickly
         assert!(iteration < 10);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_generate_julia_creates_file() {
+        let output_path = "/tmp/test_julia.png";
+        let font_path = "/System/Library/Fonts/Helvetica.ttc"; // Use system font for testing
+
+        // Skip test if font doesn't exist
+        if !Path::new(font_path).exists() {
+            return;
+        }
+
+        generate_julia(
+            100, 100, 50, 8, &Decimal::from(0.0), &Decimal::from(0.0), 1.0, 4.0,
+            font_path, 5, 80, 12.0, output_path, "bands", "solid", 2.0, &Trap::from_name("point", 0.0, 0.0, 1.0, 0.0, 0.1, None), 5.0, "mandelbrot", 2.0, -0.8, 0.156
+        );
+
+        assert!(Path::new(output_path).exists());
+
+        // Clean up
+        fs::remove_file(output_path).ok();
+    }
+
+    #[test]
+    fn test_julia_calculation() {
+        // With c = 0 the Julia set is the unit disk
+        assert_eq!(calc_julia(Fractal::Mandelbrot, 0.5, 0.5, 0.0, 0.0, 100, 2.0, 1e-3, false, None, None).iterations, 100);
+        assert!(calc_julia(Fractal::Mandelbrot, 1.5, 0.0, 0.0, 0.0, 100, 2.0, 1e-3, false, None, None).iterations < 10);
+
+        // The Julia orbit of 0 for c is the Mandelbrot orbit of c
+        assert_eq!(calc_julia(Fractal::Mandelbrot, 0.0, 0.0, -0.75, 0.1, 500, 2.0, 1e-3, false, None, None), calc_mandelbrot(Fractal::Mandelbrot, -0.75, 0.1, 500, 2.0, 1e-3, false, None, None));
+    }
+
+    #[test]
+    fn test_coordinate_mapper_splits_into_center_and_offset() {
+        let (cx, cy) = coordinate_mapper(123, 45, 800, 600, 3.0, -150.0, 20.0, 10.0);
+        let (center_x, center_y) = center_to_plane(-150.0, 20.0, 800, 600, 10.0);
+        let (dx, dy) = pixel_offset(123, 45, 800, 600, 3.0, 10.0);
+        assert_eq!((cx, cy), (center_x + dx, center_y + dy));
+
+        // The image center maps to the view center
+        assert_eq!(pixel_offset(400, 300, 800, 600, 3.0, 10.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_pixel_mapper_inverts_coordinate_mapper() {
+        let (scale_x, scale_y) = view_scale(800, 600, 3.0, 10.0);
+        for (x, y) in [(0, 0), (123, 45), (799, 599)] {
+            // A point inside the pixel, a quarter pixel from its corner
+            let (cx, cy) = coordinate_mapper(x, y, 800, 600, 3.0, -150.0, 20.0, 10.0);
+            let inside = (cx + 0.25 * scale_x / 800.0, cy + 0.25 * scale_y / 600.0);
+            assert_eq!(pixel_mapper(inside.0, inside.1, 800, 600, 3.0, -150.0, 20.0, 10.0), Some((x, y)));
+        }
+        let (cx, cy) = coordinate_mapper(0, 0, 800, 600, 3.0, -150.0, 20.0, 10.0);
+        assert_eq!(pixel_mapper(cx - scale_x / 800.0, cy, 800, 600, 3.0, -150.0, 20.0, 10.0), None);
+        assert_eq!(pixel_mapper(cx + scale_x, cy, 800, 600, 3.0, -150.0, 20.0, 10.0), None);
+    }
+
+    #[test]
+    fn test_center_to_plane_precise_matches_f64() {
+        for (width, height) in [(800, 600), (600, 800), (500, 500)] {
+            let (cx, cy) = center_to_plane(-123.25, 45.5, width, height, 10.0);
+            let (px, py) = center_to_plane_precise(&BigFixed::from_f64(-123.25, 128), &BigFixed::from_f64(45.5, 128), width, height, 10.0);
+            assert!((px.to_f64() - cx).abs() < 1e-14);
+            assert!((py.to_f64() - cy).abs() < 1e-14);
+        }
+    }
+
+    #[test]
+    fn test_plane_to_center_roundtrip() {
+        let center: ComplexDecimal = "-0.743643887037158704752191506114774,0.131825904205311970493132056385139".parse().unwrap();
+        for (width, height, m_size) in [(800, 600, 10.0), (1920, 1080, 4.0), (300, 500, 3.0)] {
+            let (center_x, center_y) = plane_to_center(&center, width, height, m_size);
+            let (re, im) = center_to_plane_precise(&center_x.to_big_fixed(200), &center_y.to_big_fixed(200), width, height, m_size);
+            assert!((&re - &center.re.to_big_fixed(200)).to_f64().abs() < 1e-30);
+            assert!((&im - &center.im.to_big_fixed(200)).to_f64().abs() < 1e-30);
+
+            // The same plane point lands at the view center for every resolution
+            let (cx, cy) = coordinate_mapper(width / 2, height / 2, width, height, 7.0, center_x.to_f64(), center_y.to_f64(), m_size);
+            assert!((cx - center.re.to_f64()).abs() < 1e-12);
+            assert!((cy - center.im.to_f64()).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_scale_to_zoom() {
+        assert_eq!(scale_to_zoom(10.0, 10.0), 1.0);
+        assert_eq!(scale_to_zoom(0.5, 4.0), 8.0);
+        let (_, scale_y) = view_scale(800, 600, scale_to_zoom(0.25, 10.0), 10.0);
+        assert!((scale_y - 0.25).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_smooth_iteration_is_continuous() {
+        // Along the real axis the escape count drops by one at some point;
+        // the smooth count must not jump there.
+        let bailout = 256.0;
+        let mut previous: Option<f64> = None;
+        let mut cx = 0.3;
+        while cx < 0.6 {
+            let mu = smooth_iteration(&calc_mandelbrot(Fractal::Mandelbrot, cx, 0.0, 1000, bailout, 1e-3, false, None, None), bailout, 2.0).unwrap();
+            if let Some(prev) = previous {
+                assert!((mu - prev).abs() < 0.5, "jump from {} to {} at {}", prev, mu, cx);
+            }
+            previous = Some(mu);
+            cx += 0.001;
+        }
+    }
+
+    #[test]
+    fn test_smooth_iteration_interior() {
+        let escape = calc_mandelbrot(Fractal::Mandelbrot, -0.1, 0.1, 200, 256.0, 1e-3, false, None, None);
+        assert_eq!(escape.iterations, 200);
+        assert_eq!(smooth_iteration(&escape, 256.0, 2.0), None);
+    }
+
+    #[test]
+    fn test_color_mode_from_name() {
+        assert_eq!(ColorMode::from_name("bands", 5.0), Ok(ColorMode::Bands));
+        assert_eq!(ColorMode::from_name("smooth", 5.0), Ok(ColorMode::Smooth));
+        assert_eq!(ColorMode::from_name("stripe", 3.0), Ok(ColorMode::Average(OrbitAverage::Stripe(3.0))));
+        assert_eq!(ColorMode::from_name("triangle", 3.0), Ok(ColorMode::Average(OrbitAverage::Triangle)));
+        assert_eq!(ColorMode::from_name("rainbow", 5.0), Err("Unknown color mode: rainbow".to_string()));
+    }
+
+    #[test]
+    fn test_average_statistics() {
+        assert_eq!(OrbitAverage::Stripe(1.0).statistic(1.0, 0.0, 0.3, 0.0), Some(0.5));
+        assert_eq!(OrbitAverage::Stripe(1.0).statistic(0.0, 1.0, 0.3, 0.0), Some(1.0));
+        // |z| = 2 reaches the upper bound |z - c| + |c| = 1 + 1
+        assert_eq!(OrbitAverage::Triangle.statistic(2.0, 0.0, 1.0, 0.0), Some(1.0));
+        assert_eq!(OrbitAverage::Triangle.statistic(0.0, 0.0, 0.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_average_is_continuous() {
+        // Crossing the iteration band edges between 7 and 5 iterations,
+        // neighbouring pixels get close averages
+        for average in [OrbitAverage::Stripe(5.0), OrbitAverage::Triangle] {
+            let mut previous: Option<f64> = None;
+            for i in 0..2000 {
+                let cx = 0.6 + i as f64 * 3e-4;
+                let escape = calc_mandelbrot(Fractal::Mandelbrot, cx, 0.6, 1000, 1000.0, 1e-3, false, None, Some(average));
+                let value = average_value(&escape, 1000.0, 2.0).unwrap();
+                assert!((0.0..=1.0).contains(&value));
+                if let Some(previous) = previous {
+                    assert!((value - previous).abs() < 0.01, "{:?} jumps at {}: {} -> {}", average, cx, previous, value);
+                }
+                previous = Some(value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fractal_from_name() {
+        assert_eq!(Fractal::from_name("mandelbrot", 5.0), Ok(Fractal::Mandelbrot));
+        assert_eq!(Fractal::from_name("burning_ship", 2.0), Ok(Fractal::BurningShip));
+        assert_eq!(Fractal::from_name("tricorn", 2.0), Ok(Fractal::Tricorn));
+        assert_eq!(Fractal::from_name("multibrot", 3.5), Ok(Fractal::Multibrot(3.5)));
+        assert_eq!(Fractal::Multibrot(3.5).degree(), 3.5);
+        assert_eq!(Fractal::Tricorn.degree(), 2.0);
+        assert!(Fractal::from_name("burning_boat", 2.0).is_err());
+    }
+
+    #[test]
+    fn test_multibrot_rejects_low_power() {
+        assert!(Fractal::from_name("multibrot", 1.0).is_err());
+        assert!(Fractal::from_name("multibrot", f64::NAN).is_err());
+    }
+
+    /// Asserts that an iterated orbit matches a `calc_mandelbrot` result, which skips the
+    /// iteration in the main cardioid and period-2 bulb: escaping orbits must match
+    /// exactly, bounded ones in the iteration count and the period of their cycle.
+    fn assert_same_escape(iterated: Escape, mandelbrot: Escape, max_iterations: u32) {
+        assert_eq!(iterated.iterations, mandelbrot.iterations);
+        if mandelbrot.iterations < max_iterations {
+            assert_eq!(iterated, mandelbrot);
+        } else {
+            assert_eq!(iterated.cycle.map(|cycle| cycle.period), mandelbrot.cycle.map(|cycle| cycle.period));
+        }
+    }
+
+    #[test]
+    fn test_fractal_families() {
+        // Multibrot with d = 2 is the Mandelbrot set, for both the integer and the real path
+        for &(cx, cy) in &[(-1.0, 0.0), (0.1, 0.1), (0.5, 0.5), (-2.5, 0.0), (0.3, 0.6), (-0.122, 0.745), (-1.755, 0.0)] {
+            let mandelbrot = calc_mandelbrot(Fractal::Mandelbrot, cx, cy, 500, 2.0, 1e-3, false, None, None);
+            assert_same_escape(calc_mandelbrot(Fractal::Multibrot(2.0), cx, cy, 500, 2.0, 1e-3, false, None, None), mandelbrot, 500);
+            let real_power = calc_mandelbrot(Fractal::Multibrot(2.0 + 1e-12), cx, cy, 500, 2.0, 1e-3, false, None, None);
+            assert!((real_power.iterations as i64 - mandelbrot.iterations as i64).abs() <= 1);
+        }
+
+        // Tricorn is symmetric under conjugation of c
+        assert_eq!(calc_mandelbrot(Fractal::Tricorn, -0.3, 0.7, 200, 2.0, 1e-3, false, None, None), calc_mandelbrot(Fractal::Tricorn, -0.3, -0.7, 200, 2.0, 1e-3, false, None, None));
+
+        // Burning Ship: -1.75 lies on the real axis inside the set, 0.5 escapes
+        assert_eq!(calc_mandelbrot(Fractal::BurningShip, -1.75, 0.0, 200, 2.0, 1e-3, false, None, None).iterations, 200);
+        assert!(calc_mandelbrot(Fractal::BurningShip, 0.5, 0.0, 200, 2.0, 1e-3, false, None, None).iterations < 200);
+        // Unlike the Mandelbrot set it is not symmetric about the real axis
+        let upper = calc_mandelbrot(Fractal::BurningShip, -0.5, 0.6, 200, 2.0, 1e-3, false, None, None);
+        let lower = calc_mandelbrot(Fractal::BurningShip, -0.5, -0.6, 200, 2.0, 1e-3, false, None, None);
+        assert_ne!(upper.iterations, lower.iterations);
+
+        // z³ + c: c = 0.5 escapes, c = 0 stays bounded
+        assert!(calc_mandelbrot(Fractal::Multibrot(3.0), 0.5, 0.0, 200, 2.0, 1e-3, false, None, None).iterations < 200);
+        assert_eq!(calc_mandelbrot(Fractal::Multibrot(3.0), 0.0, 0.0, 200, 2.0, 1e-3, false, None, None).iterations, 200);
+    }
+
+    #[test]
+    fn test_complex_powi() {
+        let (x, y) = complex_powi(1.0, 1.0, 3);
+        // (1 + i)³ = -2 + 2i
+        assert!((x + 2.0).abs() < 1e-12 && (y - 2.0).abs() < 1e-12);
+        assert_eq!(complex_powi(0.3, -0.2, 0), (1.0, 0.0));
+    }
+
+    #[test]
+    fn test_generate_formula_creates_file() {
+        let output_path = "/tmp/test_formula.png";
+        let font_path = "/System/Library/Fonts/Helvetica.ttc"; // Use system font for testing
+
+        // Skip test if font doesn't exist
+        if !Path::new(font_path).exists() {
+            return;
+        }
+
+        generate_formula(
+            100, 100, 50, 8, &Decimal::from(0.0), &Decimal::from(0.0), 1.0, 4.0,
+            font_path, 5, 80, 12.0, output_path, "smooth", "solid", 2.0, &Trap::from_name("point", 0.0, 0.0, 1.0, 0.0, 0.1, None), 5.0, "z^3 - z + c", "mandelbrot", -0.8, 0.156
+        );
+
+        assert!(Path::new(output_path).exists());
+
+        // Clean up
+        fs::remove_file(output_path).ok();
+    }
+
+    #[test]
+    fn test_formula_matches_builtin_fractals() {
+        let mandelbrot = Formula::parse("z^2 + c").unwrap();
+        let multibrot = Formula::parse("z*z*z + pixel").unwrap();
+        let zero = Complex64::new(0.0, 0.0);
+        for &(cx, cy) in &[(-1.0, 0.0), (0.1, 0.1), (0.5, 0.5), (-0.75, 0.1), (0.3, 0.6), (-0.122, 0.745), (-1.755, 0.0)] {
+            let c = Complex64::new(cx, cy);
+            assert_same_escape(calc_formula(&mandelbrot, zero, c, c, 500, 2.0, 1e-3, None, None), calc_mandelbrot(Fractal::Mandelbrot, cx, cy, 500, 2.0, 1e-3, false, None, None), 500);
+            assert_eq!(calc_formula(&multibrot, zero, c, c, 500, 2.0, 1e-3, None, None).iterations, calc_mandelbrot(Fractal::Multibrot(3.0), cx, cy, 500, 2.0, 1e-3, false, None, None).iterations);
+            // Julia seeding
+            let julia_c = Complex64::new(-0.8, 0.156);
+            assert_eq!(calc_formula(&mandelbrot, c, julia_c, c, 500, 2.0, 1e-3, None, None), calc_julia(Fractal::Mandelbrot, cx, cy, -0.8, 0.156, 500, 2.0, 1e-3, false, None, None));
+        }
+    }
+
+    #[test]
+    fn test_distance_estimate() {
+        // Julia set of c = 0 is the unit circle; the estimate for z₀ is |z₀| ln|z₀|
+        let escape = calc_julia(Fractal::Mandelbrot, 2.0, 0.0, 0.0, 0.0, 100, 1000.0, 1e-3, true, None, None);
+        let distance = distance_estimate(&escape, 1000.0).unwrap();
+        assert!((distance - 2.0 * 2f64.ln()).abs() < 1e-9);
+
+        // c = 0.5 is 0.25 away from the cusp of the main cardioid
+        let escape = calc_mandelbrot(Fractal::Mandelbrot, 0.5, 0.0, 1000, 1e6, 1e-3, true, None, None);
+        let distance = distance_estimate(&escape, 1e6).unwrap();
+        assert!(distance > 0.25 / 4.0 && distance < 0.25, "distance {}", distance);
+
+        // Interior points have no distance
+        assert_eq!(distance_estimate(&calc_mandelbrot(Fractal::Mandelbrot, -0.1, 0.1, 200, 1e6, 1e-3, true, None, None), 1e6), None);
+    }
+
+    #[test]
+    fn test_distance_coloring_is_pixel_relative() {
+        let escape = calc_mandelbrot(Fractal::Mandelbrot, 0.5, 0.0, 1000, 1e6, 1e-3, true, None, None);
+        let distance = distance_estimate(&escape, 1e6).unwrap();
+        // A point several pixels away is white, one within a pixel is gray
+        assert_eq!(escape_color(&escape, 16, ColorMode::Distance, InteriorMode::Solid, 1e6, 2.0, distance / 4.0, None), Rgba([255, 255, 255, 255]));
+        assert_eq!(escape_color(&escape, 16, ColorMode::Distance, InteriorMode::Solid, 1e6, 2.0, distance * 2.0, None), Rgba([127, 127, 127, 255]));
+    }
+
+    #[test]
+    fn test_derivative_of_families() {
+        // Multibrot with d = 2 tracks the same derivative as the Mandelbrot set
+        let mandelbrot = calc_mandelbrot(Fractal::Mandelbrot, 0.3, 0.6, 500, 100.0, 1e-3, true, None, None);
+        assert_eq!(calc_mandelbrot(Fractal::Multibrot(2.0), 0.3, 0.6, 500, 100.0, 1e-3, true, None, None), mandelbrot);
+        let real_power = calc_mandelbrot(Fractal::Multibrot(2.0 + 1e-12), 0.3, 0.6, 500, 100.0, 1e-3, true, None, None);
+        assert!((real_power.derivative_norm_sqr / mandelbrot.derivative_norm_sqr - 1.0).abs() < 1e-6);
+
+        // Tricorn and Burning Ship agree with the Mandelbrot set in the first quadrant of z
+        // for the first step from c = 1 + i, where dz/dc = 2z + 1
+        let ship = calc_mandelbrot(Fractal::BurningShip, 1.0, 1.0, 2, 100.0, 1e-3, true, None, None);
+        assert_eq!(ship.derivative_norm_sqr, 3.0 * 3.0 + 2.0 * 2.0);
+        let tricorn = calc_mandelbrot(Fractal::Tricorn, 1.0, 1.0, 2, 100.0, 1e-3, true, None, None);
+        assert_eq!(tricorn.derivative_norm_sqr, 3.0 * 3.0 + 2.0 * 2.0);
+    }
+
+    #[test]
+    fn test_mandelbrot_components() {
+        let (cycle, _) = mandelbrot_component(0.0, 0.0).unwrap();
+        assert_eq!(cycle, Cycle { period: 1, multiplier_re: 0.0, multiplier_im: 0.0 });
+        let (cycle, _) = mandelbrot_component(-1.0, 0.0).unwrap();
+        assert_eq!(cycle, Cycle { period: 2, multiplier_re: 0.0, multiplier_im: 0.0 });
+        // Cusp and the point where the bulb touches the cardioid have |λ| = 1
+        let (cycle, _) = mandelbrot_component(0.25, 0.0).unwrap();
+        assert!((cycle.multiplier_re - 1.0).abs() < 1e-12);
+        assert!(mandelbrot_component(0.3, 0.6).is_none());
+        assert!(mandelbrot_component(-0.122, 0.745).is_none());
+        assert!(mandelbrot_component(-1.3, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_periodicity_detection() {
+        // Center region of the period-3 bulb at the top of the set
+        let escape = calc_mandelbrot(Fractal::Mandelbrot, -0.122, 0.745, 10000, 2.0, 1e-3, false, None, None);
+        let cycle = escape.cycle.unwrap();
+        assert_eq!(cycle.period, 3);
+        assert_eq!(escape.iterations, 10000);
+        assert!(cycle.multiplier_re.hypot(cycle.multiplier_im) < 1.0);
+
+        // The iterated cycle matches the closed form of the cardioid
+        let iterated = calc_julia(Fractal::Mandelbrot, 0.0, 0.0, -0.5, 0.3, 10000, 2.0, 1e-3, false, None, None).cycle.unwrap();
+        let (exact, _) = mandelbrot_component(-0.5, 0.3).unwrap();
+        assert_eq!(iterated.period, 1);
+        assert!((iterated.multiplier_re - exact.multiplier_re).abs() < 1e-4);
+        assert!((iterated.multiplier_im - exact.multiplier_im).abs() < 1e-4);
+
+        // Escaping points have no cycle
+        assert_eq!(calc_mandelbrot(Fractal::Mandelbrot, 0.5, 0.5, 1000, 2.0, 1e-3, false, None, None).cycle, None);
+
+        // Just outside the cusp, orbits near z = ½ creep by less than 1e-13 per step
+        // and take millions of iterations to escape: only a cycle at a coarse pixel size
+        let cusp = |pixel_size| calc_julia(Fractal::Mandelbrot, 0.5 - 2e-7, 0.0, 0.25 + 1e-14, 0.0, 1000, 2.0, pixel_size, false, None, None);
+        assert!(cusp(1e-3).cycle.is_some());
+        let escape = cusp(1e-14);
+        assert_eq!((escape.iterations, escape.cycle), (1000, None));
+    }
+
+    #[test]
+    fn test_interior_color() {
+        assert_eq!(InteriorMode::from_name("multiplier_angle"), Ok(InteriorMode::MultiplierAngle));
+        assert_eq!(InteriorMode::from_name("hollow"), Err("Unknown interior mode: hollow".to_string()));
+        let escape = calc_mandelbrot(Fractal::Mandelbrot, -1.0, 0.0, 100, 2.0, 1e-3, false, None, None);
+        let black = Rgba([0, 0, 0, 255]);
+        assert_eq!(escape_color(&escape, 16, ColorMode::Bands, InteriorMode::Solid, 2.0, 2.0, 0.01, None), black);
+        // Period 2 is the second band
+        assert_eq!(escape_color(&escape, 16, ColorMode::Bands, InteriorMode::Period, 2.0, 2.0, 0.01, None), hsv_to_rgb(22.5, 255, 255));
+        // Superattracting center: multiplier 0
+        assert_eq!(escape_color(&escape, 16, ColorMode::Smooth, InteriorMode::Multiplier, 2.0, 2.0, 0.01, None), black);
+        // Undetected cycles are black in every mode
+        let undetected = Escape { iterations: 100, norm_sqr: 0.5, derivative_norm_sqr: 0.0, cycle: None, trap: None, average: None };
+        assert_eq!(escape_color(&undetected, 16, ColorMode::Bands, InteriorMode::Period, 2.0, 2.0, 0.01, None), black);
+    }
+
+    #[test]
+    fn test_orbit_trap() {
+        // The orbit of c = 1 is 1, 2, 5 and passes right through the trap
+        let trap = Trap::from_name("point", 2.0, 0.0, 1.0, 0.0, 0.1, None);
+        let escape = calc_mandelbrot(Fractal::Mandelbrot, 1.0, 0.0, 100, 2.0, 1e-3, false, Some(&trap), None);
+        assert_eq!(escape.trap, Some(TrapHit { distance: 0.0, x: 2.0, y: 0.0 }));
+        assert_eq!(escape_color(&escape, 16, ColorMode::Trap, InteriorMode::Solid, 2.0, 2.0, 0.01, Some(&trap)), Rgba([255, 0, 0, 255]));
+
+        // Tracking is off without a trap
+        assert_eq!(calc_mandelbrot(Fractal::Mandelbrot, 1.0, 0.0, 100, 2.0, 1e-3, false, None, None).trap, None);
+
+        // Julia and formula orbits are trapped the same way
+        let julia = calc_julia(Fractal::Mandelbrot, 0.0, 0.0, 1.0, 0.0, 100, 2.0, 1e-3, false, Some(&trap), None);
+        let formula: Formula = "z^2 + c".parse().unwrap();
+        let c = Complex64::new(1.0, 0.0);
+        assert_eq!(calc_formula(&formula, Complex64::new(0.0, 0.0), c, c, 100, 2.0, 1e-3, Some(&trap), None).trap, julia.trap);
+        assert_eq!(julia.trap, escape.trap);
+    }
+}
@@ -22,7 +22,8 @@ use rusttype::{Font, Scale};
 /// * `zoom_text_y` - Y position of zoom text.
 /// * `zoom_font_size` - Font size for zoom text.
 /// * `output_path` - Path to save the generated image.
-pub fn generate_manual(width: u32, height: u32, max_iterations: u32, bands: u32, center_x: f64, center_y: f64, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str) {
+#[allow(clippy::too_many_arguments)]
+pub fn generate_manual(width: u32, height: u32, _max_iterations: u32, _bands: u32, _center_x: f64, _center_y: f64, zoom: f64, _m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str) {
     let mut imgbuf = ImageBuffer::new(width, height);
 
     // Generate a simple gradient pattern
@@ -0,0 +1,389 @@
+use image::{ImageBuffer, Rgba, RgbaImage};
+use num_complex::Complex64;
+
+use crate::decimal::Decimal;
+use crate::formula::Formula;
+use crate::generate_mandelbrot::{coordinate_mapper, save_with_zoom_text};
+use crate::hsv_to_rgb::hsv_to_rgb;
+use crate::render_parallel::render_parallel;
+
+/// Step size below which the iteration counts as converged.
+const CONVERGENCE_TOLERANCE: f64 = 1e-9;
+
+/// Maximum distance of a converged point to the root it is attributed to.
+const ROOT_TOLERANCE: f64 = 1e-4;
+
+/// Iterations of the Durand-Kerner root finder.
+const ROOT_FINDER_ITERATIONS: u32 = 1000;
+
+/// Variant of Newton's method.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NewtonVariant {
+    /// z ← z - a·p(z)/p'(z) starting at the pixel; colored by the root reached.
+    Newton,
+    /// z ← z - a·p(z)/p'(z) + c with c = pixel, starting at z = 1.
+    Nova,
+}
+
+impl NewtonVariant {
+    /// Parses a variant name as used on the command line and in config files.
+    ///
+    /// # Returns
+    ///
+    /// The variant, or an error message if the name is unknown.
+    pub fn from_name(name: &str) -> Result<NewtonVariant, String> {
+        match name {
+            "newton" => Ok(NewtonVariant::Newton),
+            "nova" => Ok(NewtonVariant::Nova),
+            _ => Err(format!("Unknown Newton variant: {}", name)),
+        }
+    }
+}
+
+/// Polynomial with complex coefficients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    /// Coefficients, highest degree first, with a nonzero leading coefficient.
+    coefficients: Vec<Complex64>,
+}
+
+impl Polynomial {
+    /// Creates a polynomial from its coefficients, highest degree first.
+    ///
+    /// Leading zero coefficients are dropped.
+    pub fn from_coefficients(coefficients: &[Complex64]) -> Polynomial {
+        let leading = coefficients.iter().position(|c| *c != Complex64::new(0.0, 0.0)).unwrap_or(coefficients.len());
+        Polynomial { coefficients: coefficients[leading..].to_vec() }
+    }
+
+    /// Creates the monic polynomial with the given roots.
+    pub fn from_roots(roots: &[Complex64]) -> Polynomial {
+        let mut coefficients = vec![Complex64::new(1.0, 0.0)];
+        for root in roots {
+            // Multiply by (z - root)
+            coefficients.push(Complex64::new(0.0, 0.0));
+            for i in (1..coefficients.len()).rev() {
+                let previous = coefficients[i - 1];
+                coefficients[i] -= previous * root;
+            }
+        }
+        Polynomial { coefficients }
+    }
+
+    /// Degree of the polynomial (0 for constants, including zero).
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    /// Evaluates the polynomial and its derivative with Horner's scheme.
+    ///
+    /// # Returns
+    ///
+    /// A tuple (p(z), p'(z)).
+    pub fn eval(&self, z: Complex64) -> (Complex64, Complex64) {
+        let mut value = Complex64::new(0.0, 0.0);
+        let mut derivative = Complex64::new(0.0, 0.0);
+        for &coefficient in &self.coefficients {
+            derivative = derivative * z + value;
+            value = value * z + coefficient;
+        }
+        (value, derivative)
+    }
+
+    /// Finds all roots with the Durand-Kerner method.
+    pub fn roots(&self) -> Vec<Complex64> {
+        let degree = self.degree();
+        if degree == 0 {
+            return Vec::new();
+        }
+        // Standard starting points: powers of a number that is neither real nor a root of unity
+        let seed = Complex64::new(0.4, 0.9);
+        let mut roots: Vec<Complex64> = (0..degree).map(|k| seed.powu(k as u32)).collect();
+        let leading = self.coefficients[0];
+        for _ in 0..ROOT_FINDER_ITERATIONS {
+            for k in 0..degree {
+                let mut denominator = leading;
+                for j in 0..degree {
+                    if j != k {
+                        denominator *= roots[k] - roots[j];
+                    }
+                }
+                let (value, _) = self.eval(roots[k]);
+                roots[k] -= value / denominator;
+            }
+        }
+        roots
+    }
+}
+
+/// Parses a ';'-separated list of complex numbers such as "1; -0.5+0.866i; exp(2*pi*i/3)".
+///
+/// Each entry is a constant expression in the syntax of the formula function.
+pub fn parse_complex_list(text: &str) -> Result<Vec<Complex64>, String> {
+    let zero = Complex64::new(0.0, 0.0);
+    text.split(';')
+        .map(|entry| {
+            let formula = Formula::parse(entry.trim()).map_err(|e| format!("'{}': {}", entry.trim(), e))?;
+            Ok(formula.eval(zero, zero, zero, &mut formula.new_stack()))
+        })
+        .collect()
+}
+
+/// Outcome of Newton's method for a single starting point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NewtonResult {
+    /// Number of steps taken until convergence (or `max_iterations`).
+    pub iterations: u32,
+    /// Final iterate.
+    pub z: Complex64,
+    /// Whether the step size dropped below the convergence tolerance.
+    pub converged: bool,
+    /// Continuous iteration count, interpolated between the last two step sizes.
+    pub smooth_iterations: f64,
+}
+
+/// Runs Newton's method z ← z - a·p(z)/p'(z) + c until the steps become tiny.
+///
+/// # Arguments
+///
+/// * `polynomial` - Polynomial p whose roots are searched.
+/// * `z0` - Starting point.
+/// * `c` - Constant added after every step (zero for plain Newton's method).
+/// * `relaxation` - Damping factor a of the step.
+/// * `max_iterations` - Maximum number of steps.
+fn calc_newton(polynomial: &Polynomial, z0: Complex64, c: Complex64, relaxation: f64, max_iterations: u32) -> NewtonResult {
+    let mut z = z0;
+    let mut previous_step = f64::INFINITY;
+    for iteration in 1..=max_iterations {
+        let (value, derivative) = polynomial.eval(z);
+        let next = z - relaxation * value / derivative + c;
+        if !next.is_finite() {
+            break;
+        }
+        let step = (next - z).norm();
+        z = next;
+        if step < CONVERGENCE_TOLERANCE {
+            // Fraction of the last step needed to reach the tolerance on a log scale,
+            // which makes the count continuous between neighbouring pixels
+            let fraction = if previous_step.is_finite() && step > 0.0 {
+                ((previous_step / CONVERGENCE_TOLERANCE).ln() / (previous_step / step).ln()).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            return NewtonResult { iterations: iteration, z, converged: true, smooth_iterations: (iteration - 1) as f64 + fraction };
+        }
+        previous_step = step;
+    }
+    NewtonResult { iterations: max_iterations, z, converged: false, smooth_iterations: max_iterations as f64 }
+}
+
+/// Finds the index of the root a converged point belongs to.
+fn root_index(z: Complex64, roots: &[Complex64]) -> Option<usize> {
+    roots
+        .iter()
+        .enumerate()
+        .map(|(i, root)| (i, (z - root).norm()))
+        .filter(|&(_, distance)| distance < ROOT_TOLERANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+/// Converts the outcome of Newton's method to a color.
+///
+/// Newton pixels take the hue of their root and darken with the number of steps,
+/// to 1/e after `bands` steps. Nova pixels cycle through the hues every `bands`
+/// steps. Points that don't converge, or converge elsewhere than a root, are black.
+fn newton_color(result: &NewtonResult, roots: &[Complex64], variant: NewtonVariant, bands: u32) -> Rgba<u8> {
+    if !result.converged {
+        return Rgba([0, 0, 0, 255]);
+    }
+    let bands = bands.max(1) as f64;
+    match variant {
+        NewtonVariant::Newton => match root_index(result.z, roots) {
+            None => Rgba([0, 0, 0, 255]),
+            Some(index) => {
+                let hue = index as f64 / roots.len() as f64 * 360.0;
+                let shade = (-result.smooth_iterations / bands).exp();
+                hsv_to_rgb(hue as f32, 255, (shade * 255.0) as u8)
+            }
+        },
+        NewtonVariant::Nova => {
+            let hue = (result.smooth_iterations / bands).rem_euclid(1.0) * 360.0;
+            hsv_to_rgb(hue as f32, 255, 255)
+        }
+    }
+}
+
+/// Generates a Newton fractal image.
+///
+/// Each pixel is the starting point of Newton's method for a polynomial, colored
+/// by the root it converges to. The polynomial is given by its roots, or else by
+/// its coefficients.
+///
+/// Exits the process with an error message if the variant is unknown, or if the
+/// roots or coefficients can't be parsed or give a constant polynomial.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output image.
+/// * `height` - Height of the output image.
+/// * `max_iterations` - Maximum number of Newton steps per point.
+/// * `bands` - Number of steps over which the shading fades (hue cycle for 'nova').
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path to save the generated image.
+/// * `coefficients` - Polynomial coefficients, highest degree first, separated by ';'.
+/// * `roots` - Polynomial roots separated by ';' (overrides `coefficients`).
+/// * `relaxation` - Damping factor of the Newton step (1.0 = plain Newton).
+/// * `variant` - 'newton' or 'nova'.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_newton(width: u32, height: u32, max_iterations: u32, bands: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, coefficients: &str, roots: Option<&str>, relaxation: f64, variant: &str) {
+    let variant = NewtonVariant::from_name(variant).unwrap_or_else(|e| {
+        eprintln!("Invalid Newton variant: {}", e);
+        std::process::exit(1);
+    });
+    let parse = |text: &str| {
+        parse_complex_list(text).unwrap_or_else(|e| {
+            eprintln!("Failed to parse polynomial: {}", e);
+            std::process::exit(1);
+        })
+    };
+    let (polynomial, roots) = match roots {
+        Some(roots) => {
+            let roots = parse(roots);
+            (Polynomial::from_roots(&roots), roots)
+        }
+        None => {
+            let polynomial = Polynomial::from_coefficients(&parse(coefficients));
+            let roots = polynomial.roots();
+            (polynomial, roots)
+        }
+    };
+    if polynomial.degree() == 0 {
+        eprintln!("Newton's method needs a polynomial of degree 1 or more");
+        std::process::exit(1);
+    }
+    let mut imgbuf = render_newton(width, height, max_iterations, bands, center_x, center_y, zoom, m_size, &polynomial, &roots, relaxation, variant);
+
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+/// Runs Newton's method, or the Nova iteration with the pixel as c, from every
+/// pixel and colors the outcome with `newton_color`.
+#[allow(clippy::too_many_arguments)]
+fn render_newton(width: u32, height: u32, max_iterations: u32, bands: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, polynomial: &Polynomial, roots: &[Complex64], relaxation: f64, variant: NewtonVariant) -> RgbaImage {
+    let center_x = center_x.to_f64();
+    let center_y = center_y.to_f64();
+
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
+
+    render_parallel(&mut imgbuf, |x, y| {
+        let (px, py) = coordinate_mapper(x, y, width, height, zoom, center_x, center_y, m_size);
+        let pixel = Complex64::new(px, py);
+
+        let result = match variant {
+            NewtonVariant::Newton => calc_newton(polynomial, pixel, Complex64::new(0.0, 0.0), relaxation, max_iterations),
+            NewtonVariant::Nova => calc_newton(polynomial, Complex64::new(1.0, 0.0), pixel, relaxation, max_iterations),
+        };
+
+        newton_color(&result, roots, variant, bands)
+    });
+    imgbuf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complex(re: f64, im: f64) -> Complex64 {
+        Complex64::new(re, im)
+    }
+
+    #[test]
+    fn test_polynomial_from_roots() {
+        // (z - 1)(z + 1) = z² - 1
+        let polynomial = Polynomial::from_roots(&[complex(1.0, 0.0), complex(-1.0, 0.0)]);
+        assert_eq!(polynomial, Polynomial::from_coefficients(&[complex(1.0, 0.0), complex(0.0, 0.0), complex(-1.0, 0.0)]));
+        assert_eq!(polynomial.degree(), 2);
+        assert_eq!(polynomial.eval(complex(3.0, 0.0)), (complex(8.0, 0.0), complex(6.0, 0.0)));
+    }
+
+    #[test]
+    fn test_polynomial_drops_leading_zeros() {
+        let polynomial = Polynomial::from_coefficients(&[complex(0.0, 0.0), complex(2.0, 0.0), complex(1.0, 0.0)]);
+        assert_eq!(polynomial.degree(), 1);
+        assert_eq!(Polynomial::from_coefficients(&[]).degree(), 0);
+    }
+
+    #[test]
+    fn test_polynomial_roots() {
+        // z³ - 1 has the three cube roots of unity
+        let polynomial = Polynomial::from_coefficients(&[complex(1.0, 0.0), complex(0.0, 0.0), complex(0.0, 0.0), complex(-1.0, 0.0)]);
+        let roots = polynomial.roots();
+        assert_eq!(roots.len(), 3);
+        for k in 0..3 {
+            let expected = Complex64::from_polar(1.0, 2.0 * std::f64::consts::PI * k as f64 / 3.0);
+            assert!(roots.iter().any(|root| (root - expected).norm() < 1e-10), "missing {}", expected);
+        }
+    }
+
+    #[test]
+    fn test_newton_variant_from_name() {
+        assert_eq!(NewtonVariant::from_name("newton"), Ok(NewtonVariant::Newton));
+        assert_eq!(NewtonVariant::from_name("nova"), Ok(NewtonVariant::Nova));
+        assert_eq!(NewtonVariant::from_name("halley"), Err("Unknown Newton variant: halley".to_string()));
+    }
+
+    #[test]
+    fn test_parse_complex_list() {
+        let values = parse_complex_list("1; -0.5+0.25i ;2i").unwrap();
+        assert_eq!(values, vec![complex(1.0, 0.0), complex(-0.5, 0.25), complex(0.0, 2.0)]);
+        assert!(parse_complex_list("1; 2 +").is_err());
+    }
+
+    #[test]
+    fn test_newton_converges_to_nearest_root() {
+        let roots = [complex(1.0, 0.0), complex(-1.0, 0.0)];
+        let polynomial = Polynomial::from_roots(&roots);
+        let result = calc_newton(&polynomial, complex(0.8, 0.1), complex(0.0, 0.0), 1.0, 100);
+        assert!(result.converged);
+        assert_eq!(root_index(result.z, &roots), Some(0));
+        assert!(result.smooth_iterations <= result.iterations as f64 && result.smooth_iterations >= (result.iterations - 1) as f64);
+
+        // The imaginary axis is the basin boundary of z² - 1, where p'(0) = 0
+        assert!(!calc_newton(&polynomial, complex(0.0, 0.0), complex(0.0, 0.0), 1.0, 100).converged);
+
+        // Damping slows convergence down
+        let relaxed = calc_newton(&polynomial, complex(0.8, 0.1), complex(0.0, 0.0), 0.5, 100);
+        assert!(relaxed.converged && relaxed.iterations > result.iterations);
+    }
+
+    #[test]
+    fn test_newton_color() {
+        let roots = [complex(1.0, 0.0), complex(-1.0, 0.0)];
+        let converged = NewtonResult { iterations: 1, z: complex(-1.0, 0.0), converged: true, smooth_iterations: 0.0 };
+        assert_eq!(newton_color(&converged, &roots, NewtonVariant::Newton, 16), hsv_to_rgb(180.0, 255, 255));
+        let elsewhere = NewtonResult { z: complex(0.0, 5.0), ..converged };
+        assert_eq!(newton_color(&elsewhere, &roots, NewtonVariant::Newton, 16), Rgba([0, 0, 0, 255]));
+        let diverged = NewtonResult { converged: false, ..converged };
+        assert_eq!(newton_color(&diverged, &roots, NewtonVariant::Nova, 16), Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_render_newton() {
+        // The imaginary axis splits z² - 1 into the basins of -1 and 1
+        let polynomial = Polynomial::from_roots(&[complex(-1.0, 0.0), complex(1.0, 0.0)]);
+        let origin = Decimal::from(0.0);
+        let imgbuf = render_newton(32, 32, 50, 16, &origin, &origin, 1.0, 3.0, &polynomial, &polynomial.roots(), 1.0, NewtonVariant::Newton);
+        let (left, right) = (*imgbuf.get_pixel(0, 16), *imgbuf.get_pixel(31, 16));
+        assert_ne!(left, Rgba([0, 0, 0, 255]));
+        assert_ne!(right, Rgba([0, 0, 0, 255]));
+        assert_ne!(left, right);
+    }
+}
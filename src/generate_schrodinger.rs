@@ -1,18 +1,471 @@
-use image::{ImageBuffer, Rgba};
-use imageproc::drawing::draw_text_mut;
-use rusttype::{Font, Scale};
+use image::{ImageBuffer, Rgba, RgbaImage};
+use num_complex::Complex64;
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::Write;
+use std::ops::{Add, Mul};
+use std::path::Path;
 
+use crate::decimal::Decimal;
+use crate::eigenstates::{lowest_eigenstates, Hamiltonian};
+use crate::fft::{fft_2d_transposed, wavenumber};
+use crate::formula::Formula;
+use crate::generate_mandelbrot::{coordinate_mapper, save_with_zoom_text};
 use crate::hsv_to_rgb::hsv_to_rgb;
+use crate::render_parallel::render_parallel;
 
-/// Generates an image based on Schrödinger's equation (2D Gaussian wave packet).
+/// Opacity of the potential overlay where the potential reaches its largest finite value.
+const OVERLAY_OPACITY: f64 = 0.6;
+
+/// Color of impenetrable walls in the potential overlay.
+const WALL_COLOR: Rgba<u8> = Rgba([64, 64, 64, 255]);
+
+/// Fraction of the image height left free above and below the curves of 1D eigenstates.
+const CURVE_MARGIN: f64 = 0.05;
+
+/// Potential energy V(x, y) of the Schrödinger equation.
+///
+/// Barriers and slits are walls across the x axis, centered on the origin.
+/// Infinite values are impenetrable walls where ψ is kept at 0.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Potential {
+    /// V = 0 everywhere.
+    Free,
+    /// V = ½ω²(x² + y²).
+    Harmonic { omega: f64 },
+    /// V = 0 inside a square of side `width` and `height` outside.
+    SquareWell { width: f64, height: f64 },
+    /// V = 0 inside a square of side `width`, impenetrable outside.
+    InfiniteWell { width: f64 },
+    /// V = `height` in a band of thickness `width` around x = 0.
+    Barrier { width: f64, height: f64 },
+    /// Two barriers of thickness `width` with a gap of `gap` between them.
+    DoubleBarrier { width: f64, gap: f64, height: f64 },
+    /// Impenetrable wall of thickness `thickness` with two slits of width
+    /// `slit_width`, whose centers are `separation` apart.
+    DoubleSlit { thickness: f64, separation: f64, slit_width: f64 },
+    /// Real part of a formula in `x` and `y`; points where it isn't finite are walls.
+    Formula(Formula),
+}
+
+impl Potential {
+    /// Creates the potential described by the potential parameters of the command line.
+    ///
+    /// # Returns
+    ///
+    /// The potential, or an error message if the name is unknown or the potential
+    /// formula cannot be parsed.
+    pub fn from_args(args: &crate::parameters::Args) -> Result<Potential, String> {
+        let potential = match args.potential.as_str() {
+            "free" => Potential::Free,
+            "harmonic" => Potential::Harmonic { omega: args.harmonic_omega },
+            "square_well" => Potential::SquareWell { width: args.well_width, height: args.potential_height },
+            "infinite_well" => Potential::InfiniteWell { width: args.well_width },
+            "barrier" => Potential::Barrier { width: args.potential_width, height: args.potential_height },
+            "double_barrier" => Potential::DoubleBarrier { width: args.potential_width, gap: args.barrier_gap, height: args.potential_height },
+            "double_slit" => Potential::DoubleSlit { thickness: args.potential_width, separation: args.slit_separation, slit_width: args.slit_width },
+            "formula" => Potential::Formula(Formula::parse(&args.potential_formula).map_err(|e| {
+                format!("Failed to parse potential formula: {}\n  {}\n  {}^", e, args.potential_formula, " ".repeat(e.column - 1))
+            })?),
+            _ => return Err(format!("Unknown potential: {}", args.potential)),
+        };
+        Ok(potential)
+    }
+
+    /// Potential energy at a point.
+    pub fn value(&self, x: f64, y: f64) -> f64 {
+        match self {
+            Potential::Free => 0.0,
+            Potential::Harmonic { omega } => 0.5 * omega * omega * (x * x + y * y),
+            Potential::SquareWell { width, height } => {
+                if x.abs() < width / 2.0 && y.abs() < width / 2.0 { 0.0 } else { *height }
+            }
+            Potential::InfiniteWell { width } => {
+                if x.abs() < width / 2.0 && y.abs() < width / 2.0 { 0.0 } else { f64::INFINITY }
+            }
+            Potential::Barrier { width, height } => {
+                if x.abs() < width / 2.0 { *height } else { 0.0 }
+            }
+            Potential::DoubleBarrier { width, gap, height } => {
+                if (x.abs() - (gap + width) / 2.0).abs() < width / 2.0 { *height } else { 0.0 }
+            }
+            Potential::DoubleSlit { thickness, separation, slit_width } => {
+                let in_slit = (y.abs() - separation / 2.0).abs() < slit_width / 2.0;
+                if x.abs() < thickness / 2.0 && !in_slit { f64::INFINITY } else { 0.0 }
+            }
+            Potential::Formula(formula) => {
+                let z = Complex64::new(x, y);
+                let mut stack = formula.new_stack();
+                formula.eval(z, z, z, &mut stack).re
+            }
+        }
+    }
+}
+
+/// Wave function of a particle on a periodic square grid, evolved with the
+/// time-dependent Schrödinger equation i·∂ψ/∂t = -½∇²ψ + V·ψ (units with ħ = m = 1).
+///
+/// The evolution uses the split-step Fourier method with Strang splitting:
+/// every time step applies half of the potential term in position space, the
+/// kinetic term in momentum space, where it only multiplies every plane wave by
+/// a phase, and the other half of the potential term. For a free particle the
+/// kinetic step is exact, so any time span is covered in a single step.
+pub struct Simulation {
+    /// Grid points per side (a power of two).
+    n: usize,
+    /// Side of the box, which is centered on the origin.
+    size: f64,
+    /// Largest time step.
+    dt: f64,
+    /// Values of ψ row by row, starting at the bottom-left corner of the box.
+    psi: Vec<Complex64>,
+    /// Potential at the grid points, in the same order as `psi`.
+    potential: Vec<f64>,
+    /// Simulated time of `psi`.
+    time: f64,
+}
+
+impl Simulation {
+    /// Starts a simulation from a Gaussian wave packet at time 0:
+    /// ψ = exp(-|r - r₀|² / 4σ² + i·k·r), so |ψ|² has its peak 1 at r₀ and
+    /// standard deviation σ, and the packet moves with velocity k. Where the
+    /// potential is a wall, ψ starts at 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Grid points per side (a power of two).
+    /// * `size` - Side of the periodic box centered on the origin.
+    /// * `dt` - Largest time step.
+    /// * `potential` - Potential energy, sampled at the grid points.
+    /// * `x` - X coordinate of the packet center.
+    /// * `y` - Y coordinate of the packet center.
+    /// * `sigma` - Width σ of the packet.
+    /// * `kx` - Initial momentum along x.
+    /// * `ky` - Initial momentum along y.
+    ///
+    /// # Returns
+    ///
+    /// The simulation, or an error message for invalid grids, time steps and packet widths.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(n: usize, size: f64, dt: f64, potential: &Potential, x: f64, y: f64, sigma: f64, kx: f64, ky: f64) -> Result<Simulation, String> {
+        if n < 2 || !n.is_power_of_two() {
+            return Err(format!("The grid size must be a power of two, got {}", n));
+        }
+        if size <= 0.0 {
+            return Err(format!("The box size must be positive, got {}", size));
+        }
+        if dt <= 0.0 {
+            return Err(format!("The time step must be positive, got {}", dt));
+        }
+        if sigma <= 0.0 {
+            return Err(format!("The packet width must be positive, got {}", sigma));
+        }
+        let mut simulation = Simulation { n, size, dt, psi: Vec::with_capacity(n * n), potential: Vec::with_capacity(n * n), time: 0.0 };
+        for row in 0..n {
+            for column in 0..n {
+                let (px, py) = (simulation.position(column), simulation.position(row));
+                let value = potential.value(px, py);
+                let r_squared = (px - x).powi(2) + (py - y).powi(2);
+                let envelope = if value.is_finite() { (-r_squared / (4.0 * sigma * sigma)).exp() } else { 0.0 };
+                simulation.psi.push(Complex64::from_polar(envelope, kx * px + ky * py));
+                simulation.potential.push(value);
+            }
+        }
+        Ok(simulation)
+    }
+
+    /// Creates the simulation described by the wave packet and potential parameters of the command line.
+    ///
+    /// The box is the `m_size` square of the zoom = 1.0 view. Exits the process
+    /// with an error message if the parameters are invalid.
+    pub fn from_args(args: &crate::parameters::Args) -> Simulation {
+        let simulation = Potential::from_args(args).and_then(|potential| {
+            Simulation::new(args.schrodinger_grid as usize, args.m_size, args.schrodinger_dt, &potential, args.packet_x, args.packet_y, args.packet_sigma, args.packet_kx, args.packet_ky)
+        });
+        simulation.unwrap_or_else(|e| {
+            eprintln!("Invalid Schrödinger simulation: {}", e);
+            std::process::exit(1);
+        })
+    }
+
+    /// Coordinate of a grid line.
+    fn position(&self, index: usize) -> f64 {
+        (index as f64 / self.n as f64 - 0.5) * self.size
+    }
+
+    /// Evolves the wave function to a point in time, forward or backward.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - Target time.
+    pub fn advance_to(&mut self, time: f64) {
+        let span = time - self.time;
+        if span == 0.0 {
+            return;
+        }
+        let steps = if self.potential.iter().all(|&value| value == 0.0) { 1 } else { (span.abs() / self.dt).ceil() as usize };
+        let step = span / steps as f64;
+        let n = self.n;
+        // e^(-i·k²·Δt/2) is symmetric in kx and ky, so the transposed spectrum needs no reordering
+        let phases: Vec<Complex64> = (0..n * n)
+            .map(|i| {
+                let k_squared = wavenumber(i / n, n, self.size).powi(2) + wavenumber(i % n, n, self.size).powi(2);
+                Complex64::from_polar(1.0, -0.5 * k_squared * step)
+            })
+            .collect();
+        // e^(-i·V·Δt/2) for half a step of the potential, 0 in walls
+        let kicks: Vec<Complex64> = self
+            .potential
+            .iter()
+            .map(|&value| if value.is_finite() { Complex64::from_polar(1.0, -0.5 * value * step) } else { Complex64::new(0.0, 0.0) })
+            .collect();
+
+        for _ in 0..steps {
+            self.psi.par_iter_mut().zip(&kicks).for_each(|(value, kick)| *value *= kick);
+            fft_2d_transposed(&mut self.psi, n, false);
+            self.psi.par_iter_mut().zip(&phases).for_each(|(value, phase)| *value *= phase);
+            fft_2d_transposed(&mut self.psi, n, true);
+            self.psi.par_iter_mut().zip(&kicks).for_each(|(value, kick)| *value *= kick);
+        }
+        self.time = time;
+    }
+
+    /// Interpolates a quantity derived from ψ bilinearly between the grid points.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X coordinate of the point.
+    /// * `y` - Y coordinate of the point.
+    /// * `quantity` - Computes the quantity from the value of ψ at a grid point.
+    ///
+    /// # Returns
+    ///
+    /// The interpolated quantity, or `None` outside the box.
+    fn interpolate<T>(&self, x: f64, y: f64, quantity: impl Fn(Complex64) -> T) -> Option<T>
+    where
+        T: Add<Output = T> + Mul<f64, Output = T>,
+    {
+        let n = self.n;
+        let half = self.size / 2.0;
+        if x < -half || x > half || y < -half || y > half {
+            return None;
+        }
+        let grid_x = (x + half) / self.size * n as f64;
+        let grid_y = (y + half) / self.size * n as f64;
+        let (column, row) = (grid_x.floor() as usize, grid_y.floor() as usize);
+        let (fx, fy) = (grid_x - column as f64, grid_y - row as f64);
+        // The box is periodic, so the last grid line interpolates toward the first
+        let value = |column: usize, row: usize| quantity(self.psi[(row % n) * n + column % n]);
+        let bottom = value(column, row) * (1.0 - fx) + value(column + 1, row) * fx;
+        let top = value(column, row + 1) * (1.0 - fx) + value(column + 1, row + 1) * fx;
+        Some(bottom * (1.0 - fy) + top * fy)
+    }
+
+    /// Probability density |ψ|² at a point, interpolated bilinearly between
+    /// the grid points and 0 outside the box.
+    pub fn density_at(&self, x: f64, y: f64) -> f64 {
+        self.interpolate(x, y, |psi| psi.norm_sqr()).unwrap_or(0.0)
+    }
+
+    /// Value of ψ at a point, interpolated bilinearly between the grid points
+    /// and 0 outside the box.
+    pub fn psi_at(&self, x: f64, y: f64) -> Complex64 {
+        self.interpolate(x, y, |psi| psi).unwrap_or_default()
+    }
+
+    /// Potential at the grid point nearest to a point, or `None` outside the box.
+    pub fn potential_at(&self, x: f64, y: f64) -> Option<f64> {
+        let n = self.n;
+        let half = self.size / 2.0;
+        if x < -half || x > half || y < -half || y > half {
+            return None;
+        }
+        let column = ((x + half) / self.size * n as f64).round() as usize % n;
+        let row = ((y + half) / self.size * n as f64).round() as usize % n;
+        Some(self.potential[row * n + column])
+    }
+
+    /// Largest finite magnitude of the potential, which the overlay shows at full opacity.
+    fn potential_scale(&self) -> f64 {
+        potential_scale(&self.potential)
+    }
+
+    /// Potential at the grid points of the x axis, where the 1D eigenstates live.
+    fn potential_along_x(&self) -> &[f64] {
+        // Row n/2 lies at y = 0
+        &self.potential[self.n / 2 * self.n..(self.n / 2 + 1) * self.n]
+    }
+
+    /// Computes the lowest energy eigenstates of the Hamiltonian -½∇² + V of the
+    /// grid, in two dimensions, or in one along the x axis.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of states.
+    /// * `dimensions` - 2 for the whole box, or 1 for the potential along the x axis.
+    /// * `seed` - Seed of the random initial states, which picks a basis of degenerate levels.
+    ///
+    /// # Returns
+    ///
+    /// The energies in ascending order with their states, scaled so that the peak
+    /// of |ψ| is 1, or an error message.
+    pub fn eigenstates(&self, count: usize, dimensions: u32, seed: u64) -> Result<Vec<(f64, Vec<Complex64>)>, String> {
+        let potential = match dimensions {
+            1 => self.potential_along_x(),
+            2 => &self.potential,
+            _ => return Err(format!("Eigenstates have 1 or 2 dimensions, got {}", dimensions)),
+        };
+        let hamiltonian = Hamiltonian::new(self.n, self.size, dimensions as usize, potential);
+        let mut states = lowest_eigenstates(&hamiltonian, count, seed)?;
+        for (_, state) in states.iter_mut() {
+            let peak = state.iter().fold(0.0, |peak: f64, value| peak.max(value.norm()));
+            state.iter_mut().for_each(|value| *value /= peak);
+        }
+        Ok(states)
+    }
+
+    /// Copy of the simulation with another wave function on the same grid.
+    fn with_psi(&self, psi: Vec<Complex64>) -> Simulation {
+        Simulation { n: self.n, size: self.size, dt: self.dt, psi, potential: self.potential.clone(), time: self.time }
+    }
+}
+
+/// Largest finite magnitude of a potential, which the overlay shows at full opacity.
+fn potential_scale(potential: &[f64]) -> f64 {
+    potential.iter().filter(|value| value.is_finite()).fold(0.0, |scale, value| scale.max(value.abs()))
+}
+
+/// Converts a probability density to a color band.
+///
+/// # Arguments
+///
+/// * `density` - Density relative to the peak of the initial packet or stationary state.
+/// * `bands` - Number of color bands from red (0) to blue (peak).
+pub fn density_color(density: f64, bands: u32) -> Rgba<u8> {
+    let hue = if bands > 1 {
+        let band_index = (density.min(1.0) * (bands - 1) as f64).round();
+        band_index / (bands - 1) as f64 * 240.0
+    } else {
+        0.0
+    };
+    hsv_to_rgb(hue as f32, 255, 255)
+}
+
+/// Part of the wave function drawn by the 'schrodinger' and 'hydrogen' functions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WaveDisplay {
+    /// |ψ|² in color bands.
+    Density,
+    /// Domain coloring: the hue shows arg(ψ) and the brightness |ψ|.
+    Phase,
+    /// Re(ψ), red where positive and blue where negative.
+    Real,
+    /// Im(ψ), red where positive and blue where negative.
+    Imaginary,
+}
+
+impl WaveDisplay {
+    /// Parses a display name as used on the command line and in config files.
+    ///
+    /// # Returns
+    ///
+    /// The display, or an error message if the name is unknown.
+    pub fn from_name(name: &str) -> Result<WaveDisplay, String> {
+        match name {
+            "density" => Ok(WaveDisplay::Density),
+            "phase" => Ok(WaveDisplay::Phase),
+            "real" => Ok(WaveDisplay::Real),
+            "imaginary" => Ok(WaveDisplay::Imaginary),
+            _ => Err(format!("Unknown wave display: {}", name)),
+        }
+    }
+}
+
+/// Parses a display name, exiting the process with an error message if it is unknown.
+pub fn display_or_exit(name: &str) -> WaveDisplay {
+    WaveDisplay::from_name(name).unwrap_or_else(|e| {
+        eprintln!("Invalid wave display: {}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Domain coloring of a complex amplitude.
+///
+/// The hue is the phase, starting at red for positive real values and turning
+/// through yellow and green as the phase grows. The brightness grows with |ψ|,
+/// from black at 0 to full at 1, the peak of the initial packet or stationary state.
+pub fn phase_color(psi: Complex64) -> Rgba<u8> {
+    let hue = psi.arg().to_degrees().rem_euclid(360.0);
+    let value = (psi.norm().min(1.0) * 255.0).round() as u8;
+    hsv_to_rgb(hue as f32, 255, value)
+}
+
+/// Colors a real amplitude red where it is positive and blue where it is
+/// negative, from black at 0 to full at 1, the peak of the initial packet or
+/// stationary state.
+pub fn signed_color(amplitude: f64) -> Rgba<u8> {
+    let level = (amplitude.abs().min(1.0) * 255.0).round() as u8;
+    if amplitude >= 0.0 { Rgba([level, 0, 0, 255]) } else { Rgba([0, 0, level, 255]) }
+}
+
+/// Draws the potential over a pixel: walls in gray, other values as a white
+/// tint that grows with |V|.
+///
+/// # Arguments
+///
+/// * `color` - Color of the pixel without the overlay.
+/// * `potential` - Potential at the pixel.
+/// * `scale` - Magnitude of the potential drawn at full opacity (0 = no tint).
+fn overlay_potential(color: Rgba<u8>, potential: f64, scale: f64) -> Rgba<u8> {
+    if !potential.is_finite() {
+        return WALL_COLOR;
+    }
+    if scale == 0.0 {
+        return color;
+    }
+    let opacity = OVERLAY_OPACITY * (potential.abs() / scale).min(1.0);
+    let tint = |channel: u8| (channel as f64 + (255.0 - channel as f64) * opacity).round() as u8;
+    Rgba([tint(color[0]), tint(color[1]), tint(color[2]), color[3]])
+}
+
+/// Renders an image of the plane, framed like the other functions except that y grows upward.
+///
+/// # Arguments
+///
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `color_at` - Computes the color of a point (x, y) of the plane.
+#[allow(clippy::too_many_arguments)]
+pub fn render_plane(width: u32, height: u32, center_x: f64, center_y: f64, zoom: f64, m_size: f64, color_at: impl Fn(f64, f64) -> Rgba<u8> + Sync) -> RgbaImage {
+    let (_, center) = coordinate_mapper(0, height / 2, width, height, zoom, center_x, center_y, m_size);
+
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
+
+    render_parallel(&mut imgbuf, |x, y| {
+        let (px, py) = coordinate_mapper(x, y, width, height, zoom, center_x, center_y, m_size);
+
+        // Mirror y about the center so that it grows upward
+        color_at(px, 2.0 * center - py)
+    });
+    imgbuf
+}
+
+/// Generates an image of the wave function of a Schrödinger simulation, with the
+/// potential drawn over it.
+///
+/// The plane is framed like the other functions except that y grows upward.
 ///
 /// # Arguments
 ///
 /// * `width` - Width of the output image.
 /// * `height` - Height of the output image.
 /// * `bands` - Number of color bands.
-/// * `center_x` - X center coordinate.
-/// * `center_y` - Y center coordinate.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
 /// * `zoom` - Zoom level.
 /// * `m_size` - Size of the mathematical space (square).
 /// * `font_path` - Path to font file.
@@ -20,93 +473,159 @@ use crate::hsv_to_rgb::hsv_to_rgb;
 /// * `zoom_text_y` - Y position of zoom text.
 /// * `zoom_font_size` - Font size for zoom text.
 /// * `output_path` - Path to save the generated image.
-pub fn generate_schrodinger(width: u32, height: u32, bands: u32, center_x: f64, center_y: f64, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str) {
-    // Use image dimensions as buffer size for direct pixel calculation
-    let buffer_width = width;
-    let buffer_height = height;
-    let mut imgbuf = ImageBuffer::new(buffer_width, buffer_height);
-
-    // Convert normalized center coordinates to actual coordinates
-    let actual_center_x = center_x * (m_size / 2.0);
-    let actual_center_y = center_y * (m_size / 2.0);
-
-    // Calculate scales based on output dimensions and zoom level
-    // Base ranges for zoom = 1.0 (full Schrödinger view)
-    let base_range = m_size; // Square mathematical space
-    
-    // Handle zoom: positive = zoom in, negative = zoom out
-    let zoom_factor = if zoom > 0.0 { 1.0 / zoom } else { zoom.abs().max(0.1) };
-    let (scale_x, scale_y) = if (m_size - 2000.0).abs() < 0.1 && width == 800 && height == 600 {
-        // Special case: show center 800x600 rectangle of 2000x2000 mathematical space
-        (800.0, 600.0)
-    } else if width > height {
-        // Wide image: base on height, extend width
-        let base_scale = if zoom >= 0.0 { base_range / zoom_factor } else { base_range * zoom_factor };
-        (base_scale * (width as f64 / height as f64), base_scale)
-    } else if height > width {
-        // Tall image: base on width, extend height  
-        let base_scale = if zoom >= 0.0 { base_range / zoom_factor } else { base_range * zoom_factor };
-        (base_scale, base_scale * (height as f64 / width as f64))
-    } else {
-        // Square image: use base range
-        let base_scale = if zoom >= 0.0 { base_range / zoom_factor } else { base_range * zoom_factor };
-        (base_scale, base_scale)
-    };
+/// * `simulation` - Simulation whose current wave function is drawn.
+/// * `display` - Part of ψ to draw: 'density', 'phase', 'real' or 'imaginary'.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_schrodinger(width: u32, height: u32, bands: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, simulation: &Simulation, display: &str) {
+    let display = display_or_exit(display);
+    let mut imgbuf = render_wave(width, height, bands, center_x.to_f64(), center_y.to_f64(), zoom, m_size, simulation, display);
 
-    let sigma = 0.5; // Standard deviation for the Gaussian
+    save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, output_path);
+}
+
+/// Renders the current wave function of a simulation in the plane, tinted by the potential.
+#[allow(clippy::too_many_arguments)]
+fn render_wave(width: u32, height: u32, bands: u32, center_x: f64, center_y: f64, zoom: f64, m_size: f64, simulation: &Simulation, display: WaveDisplay) -> RgbaImage {
+    let potential_scale = simulation.potential_scale();
 
-    for (x, y, pixel) in imgbuf.enumerate_pixels_mut() {
-        let cx = actual_center_x + (x as f64 - buffer_width as f64 / 2.0) / buffer_width as f64 * scale_x;
-        let cy = actual_center_y + (y as f64 - buffer_height as f64 / 2.0) / buffer_height as f64 * scale_y;
+    render_plane(width, height, center_x, center_y, zoom, m_size, |px, py| {
+        let color = match display {
+            WaveDisplay::Density => density_color(simulation.density_at(px, py), bands),
+            WaveDisplay::Phase => phase_color(simulation.psi_at(px, py)),
+            WaveDisplay::Real => signed_color(simulation.psi_at(px, py).re),
+            WaveDisplay::Imaginary => signed_color(simulation.psi_at(px, py).im),
+        };
+        match simulation.potential_at(px, py) {
+            Some(potential) => overlay_potential(color, potential, potential_scale),
+            None => color,
+        }
+    })
+}
 
-        // Compute distance from center
-        let r_squared = cx * cx + cy * cy;
+/// Computes the lowest energy eigenstates of the Hamiltonian of a simulation,
+/// saves an image of each one and writes their energies to a CSV file.
+///
+/// State k, counting from 0 for the ground state, is saved next to the output
+/// path with the suffix `_state_<k>`, and the energies with the extension
+/// `.csv`. Every state is scaled so that the peak of |ψ| is 1. In two
+/// dimensions the states are drawn like the wave function of
+/// `generate_schrodinger`; in one dimension, the states of the potential along
+/// the x axis are drawn as curves over x, framed horizontally like the plane.
+///
+/// The Hamiltonian is discretized on the `--schrodinger-grid` grid of the
+/// simulation over the `m_size` box, not on the image pixels: its kinetic term
+/// is applied with FFTs, which need a power-of-two grid. `width` and `height`
+/// only set the resolution of the images, which interpolate the states between
+/// the grid points.
+///
+/// # Arguments
+///
+/// * `width` - Width of the output images.
+/// * `height` - Height of the output images.
+/// * `bands` - Number of color bands.
+/// * `center_x` - X center offset in pixels from base center.
+/// * `center_y` - Y center offset in pixels from base center.
+/// * `zoom` - Zoom level.
+/// * `m_size` - Size of the mathematical space (square).
+/// * `font_path` - Path to font file.
+/// * `zoom_text_x` - X position of zoom text.
+/// * `zoom_text_y` - Y position of zoom text.
+/// * `zoom_font_size` - Font size for zoom text.
+/// * `output_path` - Path from which the image and CSV paths are derived.
+/// * `simulation` - Simulation whose grid and potential define the Hamiltonian.
+/// * `display` - Part of ψ to draw: 'density', 'phase', 'real' or 'imaginary'.
+/// * `count` - Number of states.
+/// * `dimensions` - 2 for the whole box, or 1 for the potential along the x axis.
+/// * `seed` - Seed of the random initial states, which picks a basis of degenerate levels.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_eigenstates(width: u32, height: u32, bands: u32, center_x: &Decimal, center_y: &Decimal, zoom: f64, m_size: f64, font_path: &str, zoom_text_x: i32, zoom_text_y: i32, zoom_font_size: f32, output_path: &str, simulation: &Simulation, display: &str, count: u32, dimensions: u32, seed: u64) {
+    // Checked before the eigenstates are computed, which can take a while
+    let wave_display = display_or_exit(display);
+    let states = simulation.eigenstates(count as usize, dimensions, seed).unwrap_or_else(|e| {
+        eprintln!("Failed to compute eigenstates: {}", e);
+        std::process::exit(1);
+    });
 
-        // Probability density |ψ|^2 for Gaussian wave packet
-        let density = (-r_squared / (2.0 * sigma * sigma)).exp();
+    let stem = Path::new(output_path).with_extension("");
+    let csv_path = Path::new(output_path).with_extension("csv");
+    let energies: Vec<f64> = states.iter().map(|(energy, _)| *energy).collect();
+    let mut csv_file = File::create(&csv_path).expect("Failed to create eigenstate file");
+    csv_file.write_all(energy_csv(&energies).as_bytes()).expect("Failed to write eigenstate energies");
 
-        // Convert density to color band
-        let band_index = if bands > 1 {
-            (density * (bands - 1) as f64).round() as f64
+    let (center_x, center_y) = (center_x.to_f64(), center_y.to_f64());
+    for (index, (_, state)) in states.into_iter().enumerate() {
+        let mut imgbuf = if dimensions == 1 {
+            render_eigenstate_curve(width, height, bands, center_x, center_y, zoom, m_size, simulation, &state, wave_display)
         } else {
-            0.0
+            render_wave(width, height, bands, center_x, center_y, zoom, m_size, &simulation.with_psi(state), wave_display)
         };
+        let state_path = format!("{}_state_{}.png", stem.display(), index);
+        save_with_zoom_text(&mut imgbuf, zoom, font_path, zoom_text_x, zoom_text_y, zoom_font_size, &state_path);
+    }
+    println!("Eigenstate energies saved to {}", csv_path.display());
+}
 
-        let hue = if bands > 1 {
-            band_index / (bands - 1) as f64 * 240.0
-        } else {
-            0.0
-        };
+/// Formats eigenstate energies as CSV, one row per state counting from 0 for the ground state.
+fn energy_csv(energies: &[f64]) -> String {
+    let mut csv = String::from("State,Energy\n");
+    for (index, energy) in energies.iter().enumerate() {
+        csv.push_str(&format!("{},{:.10}\n", index, energy));
+    }
+    csv
+}
 
-        let color = hsv_to_rgb(hue as f32, 255, 255);
+/// Draws a 1D eigenstate as a filled curve over the x axis, with the potential
+/// along the x axis drawn over every column.
+///
+/// Densities rise from the bottom of the image and real or imaginary parts
+/// from its middle; the peak of |ψ| reaches the top margin.
+#[allow(clippy::too_many_arguments)]
+fn render_eigenstate_curve(width: u32, height: u32, bands: u32, center_x: f64, center_y: f64, zoom: f64, m_size: f64, simulation: &Simulation, state: &[Complex64], display: WaveDisplay) -> RgbaImage {
+    let potential = simulation.potential_along_x();
+    let potential_scale = potential_scale(potential);
+    let (n, size) = (simulation.n, simulation.size);
 
-        *pixel = color;
-    }
+    let mut imgbuf: RgbaImage = ImageBuffer::new(width, height);
 
-    // Draw zoom text
-    let font_data = match std::fs::read(font_path) {
-        Ok(data) => data,
-        Err(e) => {
-            eprintln!("Failed to read font file '{}': {}", font_path, e);
-            eprintln!("Please ensure the font file exists and the path is correct.");
-            std::process::exit(1);
+    render_parallel(&mut imgbuf, |x, y| {
+        let (px, _) = coordinate_mapper(x, y, width, height, zoom, center_x, center_y, m_size);
+        let half = size / 2.0;
+        if px < -half || px > half {
+            return Rgba([0, 0, 0, 255]);
         }
-    };
-    let font = Font::try_from_vec(font_data).expect("Failed to load font");
-    let scale = Scale { x: zoom_font_size, y: zoom_font_size };
-    let text = format!("ZOOM {:.1}", zoom);
-    draw_text_mut(&mut imgbuf, Rgba([0, 0, 0, 255]), zoom_text_x, zoom_text_y, scale, &font, &text);
-
-    imgbuf.save(output_path).unwrap_or_else(|e| {
-        eprintln!("Failed to save image to '{}': {}", output_path, e);
-        eprintln!("Please ensure the output directory exists and you have write permissions.");
-        std::process::exit(1);
+
+        // Linear interpolation on the periodic grid, like the 2D states
+        let grid_x = (px + half) / size * n as f64;
+        let column = grid_x.floor() as usize;
+        let fraction = grid_x - column as f64;
+        let psi = state[column % n] * (1.0 - fraction) + state[(column + 1) % n] * fraction;
+
+        let (value, baseline) = match display {
+            WaveDisplay::Density | WaveDisplay::Phase => (psi.norm_sqr(), 1.0 - CURVE_MARGIN),
+            WaveDisplay::Real => (psi.re, 0.5),
+            WaveDisplay::Imaginary => (psi.im, 0.5),
+        };
+        // Height of the curve and of the pixel center as fractions of the image height from the top
+        let curve = baseline - value * (baseline - CURVE_MARGIN);
+        let row = (y as f64 + 0.5) / height as f64;
+        let color = if row >= curve.min(baseline) && row <= curve.max(baseline) {
+            match display {
+                WaveDisplay::Density => density_color(value, bands),
+                WaveDisplay::Phase => phase_color(psi),
+                WaveDisplay::Real | WaveDisplay::Imaginary => signed_color(value),
+            }
+        } else {
+            Rgba([0, 0, 0, 255])
+        };
+        overlay_potential(color, potential[(grid_x.round() as usize) % n], potential_scale)
     });
+    imgbuf
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::f64::consts::PI;
     use std::fs;
     use std::path::Path;
 
@@ -120,9 +639,11 @@ mod tests {
             return;
         }
 
+        let simulation = Simulation::new(64, 10.0, 0.01, &Potential::Free, 0.0, 0.0, 0.5, 0.0, 0.0).unwrap();
+        let center = Decimal::from(0.0);
         generate_schrodinger(
-            100, 100, 8, 0.0, 0.0, 1.0, 10.0,
-            font_path, 5, 80, 12.0, output_path
+            100, 100, 8, &center, &center, 1.0, 10.0,
+            font_path, 5, 80, 12.0, output_path, &simulation, "phase"
         );
 
         assert!(Path::new(output_path).exists());
@@ -135,26 +656,197 @@ mod tests {
         fs::remove_file(output_path).ok();
     }
 
+    #[test]
+    fn test_energy_csv() {
+        assert_eq!(energy_csv(&[1.0, 2.5]), "State,Energy\n0,1.0000000000\n1,2.5000000000\n");
+        assert_eq!(energy_csv(&[]), "State,Energy\n");
+    }
+
+    #[test]
+    fn test_render_eigenstates() {
+        let simulation = Simulation::new(32, 12.0, 0.01, &Potential::Harmonic { omega: 1.0 }, 0.0, 0.0, 0.5, 0.0, 0.0).unwrap();
+
+        // The 1D ground state density peaks at x = 0, where it reaches the top margin
+        let ground = simulation.eigenstates(1, 1, 0).unwrap().remove(0).1;
+        let curve = render_eigenstate_curve(100, 100, 8, 0.0, 0.0, 1.0, 12.0, &simulation, &ground, WaveDisplay::Density);
+        let black = Rgba([0, 0, 0, 255]);
+        assert_eq!(*curve.get_pixel(50, 2), black);
+        assert!((6..95).all(|y| *curve.get_pixel(50, y) != black));
+
+        // The first excited 2D state is odd, positive (red) on one side and negative (blue) on the other
+        let excited = simulation.eigenstates(2, 2, 0).unwrap().remove(1).1;
+        let wave = render_wave(100, 100, 8, 0.0, 0.0, 1.0, 12.0, &simulation.with_psi(excited), WaveDisplay::Real);
+        assert!(wave.pixels().any(|pixel| pixel[0] as i32 - pixel[2] as i32 > 100));
+        assert!(wave.pixels().any(|pixel| pixel[2] as i32 - pixel[0] as i32 > 100));
+    }
+
+    #[test]
+    fn test_simulation_eigenstates() {
+        let simulation = Simulation::new(32, 12.0, 0.01, &Potential::Harmonic { omega: 1.0 }, 0.0, 0.0, 0.5, 0.0, 0.0).unwrap();
+        // E = ω(nx + ny + 1) on the whole box and ω(n + ½) along the x axis
+        for (dimensions, expected) in [(2, [1.0, 2.0, 2.0]), (1, [0.5, 1.5, 2.5])] {
+            let states = simulation.eigenstates(3, dimensions, 0).unwrap();
+            for ((energy, state), expected) in states.iter().zip(expected) {
+                assert!((energy - expected).abs() < 1e-6, "energy {} instead of {}", energy, expected);
+                let peak = state.iter().fold(0.0, |peak: f64, value| peak.max(value.norm()));
+                assert!((peak - 1.0).abs() < 1e-12);
+            }
+        }
+        // The ground state of the harmonic oscillator is the Gaussian packet at rest
+        let ground = simulation.with_psi(simulation.eigenstates(1, 2, 0).unwrap().remove(0).1);
+        assert!((ground.density_at(1.5, 0.0) - (-1.5_f64 * 1.5).exp()).abs() < 1e-6);
+
+        assert!(simulation.eigenstates(3, 3, 0).is_err());
+    }
+
     #[test]
     fn test_gaussian_wave_packet_calculation() {
         let sigma = 0.5_f64;
+        let simulation = Simulation::new(64, 8.0, 0.01, &Potential::Free, 0.0, 0.0, sigma, 3.0, 0.0).unwrap();
 
         // Test center point (0,0) - should have maximum density
-        let r_squared_center = 0.0_f64 * 0.0_f64 + 0.0_f64 * 0.0_f64;
-        let density_center = (-r_squared_center / (2.0_f64 * sigma * sigma)).exp();
-        assert_eq!(density_center, 1.0_f64); // exp(0) = 1
+        let density_center = simulation.density_at(0.0, 0.0);
+        assert!((density_center - 1.0).abs() < 1e-10); // exp(0) = 1
 
-        // Test point at distance sigma
-        let r_squared_sigma = sigma * sigma;
-        let density_sigma = (-r_squared_sigma / (2.0_f64 * sigma * sigma)).exp();
+        // Test point at distance sigma (on a grid point, where no interpolation happens)
+        let density_sigma = simulation.density_at(sigma, 0.0);
         let expected = (-0.5_f64).exp(); // exp(-0.5)
         assert!((density_sigma - expected).abs() < 1e-10);
 
         // Test point farther away - density should be lower
-        let r_squared_far = 4.0_f64 * sigma * sigma; // 2*sigma distance
-        let density_far = (-r_squared_far / (2.0_f64 * sigma * sigma)).exp();
+        let density_far = simulation.density_at(0.0, 2.0 * sigma);
         assert!(density_far < density_center);
         assert!(density_far < density_sigma);
+
+        // Outside the box there is no density
+        assert_eq!(simulation.density_at(4.5, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_free_packet_moves_and_spreads() {
+        let sigma = 0.5;
+        let mut simulation = Simulation::new(128, 16.0, 0.01, &Potential::Free, -2.0, 1.0, sigma, 4.0, -2.0).unwrap();
+        simulation.advance_to(0.5);
+        assert_eq!(simulation.time, 0.5);
+
+        // The center moves with velocity k and the width grows to σ·√(1 + (t/2σ²)²)
+        let spread = 1.0 + (0.5 / (2.0 * sigma * sigma)).powi(2);
+        let sigma_t = sigma * spread.sqrt();
+        let peak = simulation.density_at(0.0, 0.0);
+        assert!((peak - 1.0 / spread).abs() < 1e-6);
+        let off_center = simulation.density_at(0.5, 0.0);
+        assert!((off_center - (-0.25 / (2.0 * sigma_t * sigma_t)).exp() / spread).abs() < 1e-6);
+
+        // Evolving back restores the initial packet
+        simulation.advance_to(0.0);
+        assert!((simulation.density_at(-2.0, 1.0) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_potential_value() {
+        assert_eq!(Potential::Free.value(1.0, 2.0), 0.0);
+        assert_eq!(Potential::Harmonic { omega: 2.0 }.value(1.0, -1.0), 4.0);
+        let well = Potential::SquareWell { width: 2.0, height: 5.0 };
+        assert_eq!((well.value(0.9, -0.9), well.value(1.1, 0.0)), (0.0, 5.0));
+        let well = Potential::InfiniteWell { width: 2.0 };
+        assert_eq!((well.value(0.0, 0.9), well.value(0.0, 1.1)), (0.0, f64::INFINITY));
+        let barrier = Potential::Barrier { width: 0.2, height: 5.0 };
+        assert_eq!((barrier.value(-0.05, 3.0), barrier.value(0.15, 0.0)), (5.0, 0.0));
+        // Barriers from 0.5 to 0.7 on either side
+        let barriers = Potential::DoubleBarrier { width: 0.2, gap: 1.0, height: 5.0 };
+        assert_eq!((barriers.value(0.0, 0.0), barriers.value(0.6, 0.0), barriers.value(-0.6, 1.0), barriers.value(0.8, 0.0)), (0.0, 5.0, 5.0, 0.0));
+        // Slits from 0.3 to 0.7 on either side of the x axis
+        let slits = Potential::DoubleSlit { thickness: 0.2, separation: 1.0, slit_width: 0.4 };
+        assert_eq!((slits.value(0.0, 0.5), slits.value(0.0, -0.35), slits.value(0.0, 0.0), slits.value(0.0, 0.8), slits.value(0.2, 0.0)), (0.0, 0.0, f64::INFINITY, f64::INFINITY, 0.0));
+        let formula = Potential::Formula(Formula::parse("x^2 - y").unwrap());
+        assert_eq!(formula.value(3.0, 1.0), 8.0);
+    }
+
+    #[test]
+    fn test_potential_from_args() {
+        use clap::Parser;
+        let args = crate::parameters::Args::parse_from(["mathillu", "--potential", "barrier", "--potential-width", "0.5", "--potential-height", "3"]);
+        assert_eq!(Potential::from_args(&args), Ok(Potential::Barrier { width: 0.5, height: 3.0 }));
+
+        let args = crate::parameters::Args::parse_from(["mathillu", "--potential", "wall"]);
+        assert_eq!(Potential::from_args(&args), Err("Unknown potential: wall".to_string()));
+        let args = crate::parameters::Args::parse_from(["mathillu", "--potential", "formula", "--potential-formula", "x +"]);
+        assert!(Potential::from_args(&args).unwrap_err().starts_with("Failed to parse potential formula"));
+    }
+
+    #[test]
+    fn test_harmonic_oscillator_coherent_state() {
+        // A displaced ground state with σ = 1/√(2ω) swings to the other side in half a period without spreading
+        let omega = 1.0_f64;
+        let sigma = 1.0 / (2.0 * omega).sqrt();
+        let mut simulation = Simulation::new(64, 12.0, 0.01, &Potential::Harmonic { omega }, 2.25, 0.0, sigma, 0.0, 0.0).unwrap();
+        simulation.advance_to(PI / omega);
+        assert!((simulation.density_at(-2.25, 0.0) - 1.0).abs() < 1e-3);
+        assert!(simulation.density_at(2.25, 0.0) < 1e-6);
+    }
+
+    #[test]
+    fn test_walls_keep_psi_out() {
+        let slits = Potential::DoubleSlit { thickness: 0.5, separation: 1.0, slit_width: 0.25 };
+        let mut simulation = Simulation::new(64, 8.0, 0.01, &slits, -1.5, 0.0, 0.5, 6.0, 0.0).unwrap();
+        simulation.advance_to(0.4);
+        // Inside the wall ψ is 0, in the slits and behind the wall some of the packet came through
+        assert_eq!(simulation.density_at(0.0, 0.0), 0.0);
+        assert!(simulation.density_at(0.0, 0.5) > 1e-4);
+        assert!(simulation.density_at(1.0, 0.5) > 1e-4);
+        assert_eq!(simulation.potential_at(0.0, 0.0), Some(f64::INFINITY));
+        assert_eq!(simulation.potential_at(5.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_wave_display_from_name() {
+        assert_eq!(WaveDisplay::from_name("density"), Ok(WaveDisplay::Density));
+        assert_eq!(WaveDisplay::from_name("phase"), Ok(WaveDisplay::Phase));
+        assert_eq!(WaveDisplay::from_name("real"), Ok(WaveDisplay::Real));
+        assert_eq!(WaveDisplay::from_name("imaginary"), Ok(WaveDisplay::Imaginary));
+    }
+
+    #[test]
+    fn test_wave_display_unknown() {
+        assert_eq!(WaveDisplay::from_name("modulus"), Err("Unknown wave display: modulus".to_string()));
+    }
+
+    #[test]
+    fn test_phase_and_signed_colors() {
+        assert_eq!(phase_color(Complex64::new(1.0, 0.0)), Rgba([255, 0, 0, 255]));
+        assert_eq!(phase_color(Complex64::new(0.0, 2.0)), hsv_to_rgb(90.0, 255, 255));
+        assert_eq!(phase_color(Complex64::new(0.0, -0.5)), hsv_to_rgb(270.0, 255, 128));
+        assert_eq!(phase_color(Complex64::new(0.0, 0.0)), Rgba([0, 0, 0, 255]));
+        assert_eq!(signed_color(0.5), Rgba([128, 0, 0, 255]));
+        assert_eq!(signed_color(-2.0), Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_psi_at_follows_momentum() {
+        // The phase of a packet with momentum k grows by k·Δx
+        let simulation = Simulation::new(64, 8.0, 0.01, &Potential::Free, 0.0, 0.0, 0.5, 3.0, 0.0).unwrap();
+        assert!((simulation.psi_at(0.0, 0.0) - Complex64::new(1.0, 0.0)).norm() < 1e-12);
+        let psi = simulation.psi_at(0.5, 0.0);
+        assert!((psi.arg() - 1.5).abs() < 1e-12);
+        assert!((psi.norm_sqr() - simulation.density_at(0.5, 0.0)).abs() < 1e-12);
+        assert_eq!(simulation.psi_at(5.0, 0.0), Complex64::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_overlay_potential() {
+        let color = Rgba([255, 0, 0, 255]);
+        assert_eq!(overlay_potential(color, 0.0, 10.0), color);
+        assert_eq!(overlay_potential(color, 5.0, 0.0), color);
+        assert_eq!(overlay_potential(color, 20.0, 10.0), Rgba([255, 153, 153, 255]));
+        assert_eq!(overlay_potential(color, -5.0, 10.0), Rgba([255, 77, 77, 255]));
+        assert_eq!(overlay_potential(color, f64::INFINITY, 10.0), WALL_COLOR);
+    }
+
+    #[test]
+    fn test_invalid_simulation() {
+        assert!(Simulation::new(100, 10.0, 0.01, &Potential::Free, 0.0, 0.0, 0.5, 0.0, 0.0).is_err());
+        assert!(Simulation::new(64, 10.0, 0.0, &Potential::Free, 0.0, 0.0, 0.5, 0.0, 0.0).is_err());
+        assert!(Simulation::new(64, 10.0, 0.01, &Potential::Free, 0.0, 0.0, 0.0, 0.0, 0.0).is_err());
     }
 
     #[test]
@@ -162,15 +854,14 @@ mod tests {
         let bands = 8;
 
         // Test maximum density (1.0) should map to highest band
-        let band_index_max = (1.0 * (bands - 1) as f64).round() as f64;
-        assert_eq!(band_index_max, (bands - 1) as f64);
+        assert_eq!(density_color(1.0, bands), hsv_to_rgb(240.0, 255, 255));
+        assert_eq!(density_color(2.0, bands), hsv_to_rgb(240.0, 255, 255));
 
         // Test zero density should map to band 0
-        let band_index_min = (0.0 * (bands - 1) as f64).round() as f64;
-        assert_eq!(band_index_min, 0.0);
+        assert_eq!(density_color(0.0, bands), hsv_to_rgb(0.0, 255, 255));
 
         // Test 0.5 density
-        let band_index_mid = (0.5 * (bands - 1) as f64).round() as f64;
-        assert_eq!(band_index_mid, 4.0); // 0.5 * 7 = 3.5, rounds to 4
+        let band_index_mid = 4.0; // 0.5 * 7 = 3.5, rounds to 4
+        assert_eq!(density_color(0.5, bands), hsv_to_rgb((band_index_mid / 7.0 * 240.0) as f32, 255, 255));
     }
-}
\ No newline at end of file
+}
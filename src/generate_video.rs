@@ -3,8 +3,148 @@ use std::io::Write;
 use std::process::Command;
 use std::path::Path;
 
+use crate::big_fixed::BigFixed;
+use crate::decimal::Decimal;
 use crate::generate_mandelbrot;
+use crate::generate_buddhabrot;
+use crate::generate_lyapunov;
+use crate::generate_bifurcation;
+use crate::generate_attractor;
+use crate::generate_flame;
+use crate::generate_ifs;
+use crate::generate_newton;
 use crate::generate_schrodinger;
+use crate::generate_hydrogen::{self, Orbital, Slice};
+use crate::orbit_trap::Trap;
+use crate::perturbation::precision_for;
+
+/// Interpolates linearly between two decimals in high precision.
+///
+/// Deep zoom centers have more digits than an `f64` holds, so the path between
+/// them is computed in fixed-point arithmetic.
+///
+/// # Arguments
+///
+/// * `start` - Value at t = 0.0
+/// * `end` - Value at t = 1.0
+/// * `t` - Interpolation parameter
+/// * `frac_bits` - Number of fractional bits to compute with
+pub fn lerp_decimal(start: &Decimal, end: &Decimal, t: f64, frac_bits: u32) -> Decimal {
+    let start = start.to_big_fixed(frac_bits);
+    let end = end.to_big_fixed(frac_bits);
+    let step = &(&end - &start) * &BigFixed::from_f64(t, frac_bits);
+    Decimal::from(&start + &step)
+}
+
+/// Computes the Julia constant c for a point in time of the video.
+///
+/// With the 'line' path, c moves from the start value to the end value using the
+/// same smoothstep easing as the camera. With the 'circle' path, c makes one full
+/// turn at constant angular speed around the end point (a missing coordinate
+/// counts as 0), starting at the start value, so the clip loops seamlessly.
+///
+/// Panics on other paths, which `generate_video` rejects before rendering.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments
+/// * `t` - Linear progress through the video (0.0 to 1.0)
+/// * `smooth_t` - Eased progress through the video (0.0 to 1.0)
+///
+/// # Returns
+///
+/// A tuple (re, im) with the Julia constant for this frame.
+pub fn julia_constant_at(args: &crate::parameters::Args, t: f64, smooth_t: f64) -> (f64, f64) {
+    match args.julia_path.as_str() {
+        "line" => {
+            let end_re = args.end_julia_re.unwrap_or(args.julia_re);
+            let end_im = args.end_julia_im.unwrap_or(args.julia_im);
+            (
+                args.julia_re + (end_re - args.julia_re) * smooth_t,
+                args.julia_im + (end_im - args.julia_im) * smooth_t,
+            )
+        }
+        "circle" => {
+            let center_re = args.end_julia_re.unwrap_or(0.0);
+            let center_im = args.end_julia_im.unwrap_or(0.0);
+            let dx = args.julia_re - center_re;
+            let dy = args.julia_im - center_im;
+            let radius = (dx * dx + dy * dy).sqrt();
+            let angle = dy.atan2(dx) + 2.0 * std::f64::consts::PI * t;
+            (center_re + radius * angle.cos(), center_im + radius * angle.sin())
+        }
+        _ => panic!("Unknown Julia path: {}", args.julia_path),
+    }
+}
+
+/// Computes the orbit trap geometry for a point in time of the video.
+///
+/// Center, radius and angle move from their start values to the end values using
+/// the same smoothstep easing as the camera; missing end values keep the start value.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments
+/// * `trap` - The trap at the start of the video
+/// * `smooth_t` - Eased progress through the video (0.0 to 1.0)
+pub fn trap_at(args: &crate::parameters::Args, trap: &Trap, smooth_t: f64) -> Trap {
+    let lerp = |start: f64, end: Option<f64>| start + (end.unwrap_or(start) - start) * smooth_t;
+    trap.with_geometry(
+        lerp(args.trap_x, args.end_trap_x),
+        lerp(args.trap_y, args.end_trap_y),
+        lerp(args.trap_radius, args.end_trap_radius),
+        lerp(args.trap_angle, args.end_trap_angle),
+    )
+}
+
+/// Computes the attractor parameters for a point in time of the video.
+///
+/// Every parameter moves from its start value to its end value using the same
+/// smoothstep easing as the camera, morphing the attractor.
+///
+/// # Arguments
+///
+/// * `start` - Parameters at the start of the video
+/// * `end` - Parameters at the end of the video
+/// * `smooth_t` - Eased progress through the video (0.0 to 1.0)
+pub fn attractor_params_at(start: &[f64], end: &[f64], smooth_t: f64) -> Vec<f64> {
+    start.iter().zip(end).map(|(start, end)| start + (end - start) * smooth_t).collect()
+}
+
+/// Computes the hydrogen orbital and slice plane for a point in time of the video.
+///
+/// The slice angles move from their start values to the end values using the
+/// same smoothstep easing as the camera. The magnetic quantum number steps
+/// through the integers between its start and end values, each held for an
+/// equal share of the eased progress. Missing end values keep the start value.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments
+/// * `smooth_t` - Eased progress through the video (0.0 to 1.0)
+pub fn orbital_at(args: &crate::parameters::Args, smooth_t: f64) -> (Orbital, Slice) {
+    let lerp = |start: f64, end: Option<f64>| start + (end.unwrap_or(start) - start) * smooth_t;
+    let end_m = args.end_orbital_m.unwrap_or(args.orbital_m);
+    let values = (end_m - args.orbital_m).abs() + 1;
+    let index = ((smooth_t * values as f64) as i32).min(values - 1);
+    let m = args.orbital_m + index * (end_m - args.orbital_m).signum();
+    let orbital = generate_hydrogen::orbital_or_exit(args.orbital_n, args.orbital_l, m);
+    (orbital, Slice::new(lerp(args.slice_tilt, args.end_slice_tilt), lerp(args.slice_azimuth, args.end_slice_azimuth)))
+}
+
+/// Computes the simulated time of the Schrödinger equation for a point in time of the video.
+///
+/// The simulated time runs at constant speed from the start time to the end
+/// time, without easing, so the frames show the true dynamics.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments
+/// * `t` - Linear progress through the video (0.0 to 1.0)
+pub fn schrodinger_time_at(args: &crate::parameters::Args, t: f64) -> f64 {
+    let end = args.end_schrodinger_time.unwrap_or(args.schrodinger_time);
+    args.schrodinger_time + (end - args.schrodinger_time) * t
+}
 
 /// Generates a video by creating frames with smooth transitions and encoding them with ffmpeg.
 ///
@@ -13,47 +153,104 @@ use crate::generate_schrodinger;
 /// * `args` - The parsed command line arguments
 /// * `output_path` - Path where the video should be saved
 pub fn generate_video(args: &crate::parameters::Args, output_path: &str) {
+    if args.function == "schrodinger" && args.schrodinger_mode != "evolve" {
+        eprintln!("Only the 'evolve' mode of the 'schrodinger' function can be animated");
+        std::process::exit(1);
+    }
+    if args.julia_path != "line" && args.julia_path != "circle" {
+        eprintln!("Unknown Julia path: {}", args.julia_path);
+        std::process::exit(1);
+    }
+    if args.function == "hydrogen" {
+        // Check the quantum numbers at the end before rendering any frame
+        orbital_at(args, 1.0);
+    }
     std::fs::create_dir_all(&args.frames_dir).expect("Failed to create frames directory");
     let total_frames = (args.fps * args.duration).round() as u32;
     println!("Generating {} frames for video...", total_frames);
 
-    let end_cx = args.end_center_x.unwrap_or(args.center_x);
-    let end_cy = args.end_center_y.unwrap_or(args.center_y);
-    let end_z = args.end_zoom.unwrap_or(args.zoom);
+    let end_cx = args.end_center_x.as_ref().unwrap_or(&args.center_x);
+    let end_cy = args.end_center_y.as_ref().unwrap_or(&args.center_y);
+    let start_z = args.zoom.to_f64();
+    let end_z = args.end_zoom.as_ref().unwrap_or(&args.zoom).to_f64();
+
+    // Enough precision to place the center within a pixel at the deepest zoom
+    let frac_bits = precision_for(1.0 / start_z.max(end_z).max(1.0));
+    let start_trap = matches!(args.function.as_str(), "mandelbrot" | "julia" | "formula").then(|| Trap::from_args(args));
+    let (start_attractor, end_attractor) = if args.function == "attractor" {
+        let start = generate_attractor::params_or_exit(&args.attractor, args.attractor_params.as_deref());
+        let end = match &args.end_attractor_params {
+            Some(end) => generate_attractor::params_or_exit(&args.attractor, Some(end)),
+            None => start.clone(),
+        };
+        (start, end)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    let mut simulation = (args.function == "schrodinger").then(|| generate_schrodinger::Simulation::from_args(args));
 
     let mut log_entries = Vec::new();
     for i in 0..total_frames {
         let t = if total_frames > 1 { i as f64 / (total_frames - 1) as f64 } else { 0.0 };
         // Apply smoothstep easing for smoother transitions
         let smooth_t = 3.0 * t * t - 2.0 * t * t * t;
-        let cx = args.center_x + (end_cx - args.center_x) * smooth_t;
-        let cy = args.center_y + (end_cy - args.center_y) * smooth_t;
-        let z = if args.zoom > 0.0 && end_z > 0.0 {
-            let log_start = args.zoom.ln();
+        let cx = lerp_decimal(&args.center_x, end_cx, smooth_t, frac_bits);
+        let cy = lerp_decimal(&args.center_y, end_cy, smooth_t, frac_bits);
+        let z = if start_z > 0.0 && end_z > 0.0 {
+            let log_start = start_z.ln();
             let log_end = end_z.ln();
             let log_z = log_start + (log_end - log_start) * smooth_t;
             log_z.exp()
         } else {
-            args.zoom + (end_z - args.zoom) * smooth_t
+            start_z + (end_z - start_z) * smooth_t
         };
+        let (julia_re, julia_im) = julia_constant_at(args, t, smooth_t);
+        let trap = start_trap.as_ref().map(|start_trap| trap_at(args, start_trap, smooth_t));
+        let attractor_params = attractor_params_at(&start_attractor, &end_attractor, smooth_t);
         let output_basename = Path::new(output_path).file_name().unwrap_or_else(|| std::ffi::OsStr::new("video")).to_string_lossy();
         let frame_path = format!("{}/{}_frame_{:04}.png", args.frames_dir, output_basename, i);
         match args.function.as_str() {
-            "mandelbrot" => generate_mandelbrot::generate_mandelbrot(args.width, args.height, args.max_iterations, args.bands, cx, cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path),
-            "schrodinger" => generate_schrodinger::generate_schrodinger(args.width, args.height, args.bands, cx, cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path),
+            "mandelbrot" => generate_mandelbrot::generate_mandelbrot(args.width, args.height, args.max_iterations, args.bands, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, &args.color_mode, &args.interior, args.bailout, trap.as_ref().expect("Trap is created for the escape-time functions"), args.stripe_density, &args.fractal, args.power, &args.engine),
+            "julia" => generate_mandelbrot::generate_julia(args.width, args.height, args.max_iterations, args.bands, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, &args.color_mode, &args.interior, args.bailout, trap.as_ref().expect("Trap is created for the escape-time functions"), args.stripe_density, &args.fractal, args.power, julia_re, julia_im),
+            "formula" => generate_mandelbrot::generate_formula(args.width, args.height, args.max_iterations, args.bands, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, &args.color_mode, &args.interior, args.bailout, trap.as_ref().expect("Trap is created for the escape-time functions"), args.stripe_density, &args.formula, &args.formula_seed, julia_re, julia_im),
+            "buddhabrot" => generate_buddhabrot::generate_buddhabrot(args.width, args.height, args.max_iterations, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, args.bailout, args.samples, args.seed, args.red_iterations, args.green_iterations, args.blue_iterations, args.anti_buddhabrot, &args.sampling),
+            "newton" => generate_newton::generate_newton(args.width, args.height, args.max_iterations, args.bands, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, &args.newton_coefficients, args.newton_roots.as_deref(), args.newton_relaxation, &args.newton_variant),
+            "lyapunov" => generate_lyapunov::generate_lyapunov(args.width, args.height, args.max_iterations, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, &args.lyapunov_sequence),
+            "bifurcation" => generate_bifurcation::generate_bifurcation(args.width, args.height, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, &args.bifurcation_map, args.bifurcation_transient, args.bifurcation_samples),
+            "attractor" => generate_attractor::generate_attractor(args.width, args.height, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, args.samples, args.seed, &args.attractor, &attractor_params),
+            "flame" => generate_flame::generate_flame(args.width, args.height, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, args.samples, args.seed, &args.flame_transforms, args.flame_gamma, args.flame_vibrancy),
+            "ifs" => generate_ifs::generate_ifs(args.width, args.height, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, args.samples, args.seed, &args.ifs, &args.ifs_maps, args.chaos_vertices, args.chaos_ratio, &args.chaos_rule),
+            "schrodinger" => {
+                let simulation = simulation.as_mut().expect("Simulation is created for the 'schrodinger' function");
+                simulation.advance_to(schrodinger_time_at(args, t));
+                generate_schrodinger::generate_schrodinger(args.width, args.height, args.bands, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, simulation, &args.wave_display)
+            }
+            "hydrogen" => {
+                let (orbital, slice) = orbital_at(args, smooth_t);
+                generate_hydrogen::generate_hydrogen(args.width, args.height, args.bands, &cx, &cy, z, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &frame_path, &orbital, &slice, &args.wave_display)
+            }
             _ => panic!("Unknown function: {}", args.function),
         }
         let time = i as f64 / args.fps;
-        log_entries.push((i + 1, time, cx, cy, z));
+        log_entries.push((i + 1, time, cx, cy, z, julia_re, julia_im));
         println!("Generated frame {}", i + 1);
     }
 
     // Write log
     let log_path = format!("{}.log", output_path);
     let mut log_file = File::create(&log_path).expect("Failed to create log file");
-    writeln!(log_file, "Frame,Time,X,Y,Zoom").expect("Failed to write log header");
-    for (frame, time, x, y, zoom) in log_entries {
-        writeln!(log_file, "{},{:.2},{:.6},{:.6},{:.6}", frame, time, x, y, zoom).expect("Failed to write log entry");
+    let log_julia = args.function == "julia" || (args.function == "formula" && args.formula_seed == "julia");
+    if log_julia {
+        writeln!(log_file, "Frame,Time,X,Y,Zoom,CRe,CIm").expect("Failed to write log header");
+    } else {
+        writeln!(log_file, "Frame,Time,X,Y,Zoom").expect("Failed to write log header");
+    }
+    for (frame, time, x, y, zoom, julia_re, julia_im) in log_entries {
+        if log_julia {
+            writeln!(log_file, "{},{:.2},{},{},{:.6},{:.6},{:.6}", frame, time, x, y, zoom, julia_re, julia_im).expect("Failed to write log entry");
+        } else {
+            writeln!(log_file, "{},{:.2},{},{},{:.6}", frame, time, x, y, zoom).expect("Failed to write log entry");
+        }
     }
     println!("Log written to {}", log_path);
 
@@ -61,7 +258,7 @@ pub fn generate_video(args: &crate::parameters::Args, output_path: &str) {
     let video_path = format!("{}.mp4", output_path);
     let output_basename = Path::new(output_path).file_name().unwrap_or_else(|| std::ffi::OsStr::new("video")).to_string_lossy();
     if let Ok(status) = Command::new("ffmpeg")
-        .args(&["-y", "-r", &args.fps.to_string(), "-i", &format!("{}/{}_frame_%04d.png", args.frames_dir, output_basename), "-c:v", "libx264", "-pix_fmt", "yuv420p", &video_path])
+        .args(["-y", "-r", &args.fps.to_string(), "-i", &format!("{}/{}_frame_%04d.png", args.frames_dir, output_basename), "-c:v", "libx264", "-pix_fmt", "yuv420p", &video_path])
         .status()
     {
         if status.success() {
@@ -81,6 +278,8 @@ pub fn generate_video(args: &crate::parameters::Args, output_path: &str) {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::decimal::Decimal;
     use crate::parameters::Args;
 
     #[test]
@@ -94,6 +293,20 @@ mod tests {
         assert_eq!(result, 3.0);
     }
 
+    #[test]
+    fn test_lerp_decimal() {
+        let start: Decimal = "1.0".parse().unwrap();
+        let end: Decimal = "5.0".parse().unwrap();
+        assert_eq!(lerp_decimal(&start, &end, 0.5, 64).to_string(), "3");
+        assert_eq!(lerp_decimal(&start, &end, 0.0, 64), 1.0);
+        assert_eq!(lerp_decimal(&start, &end, 1.0, 64), 5.0);
+
+        // Digits far beyond f64 precision are kept
+        let start: Decimal = "-0.74364388703715870475219150611".parse().unwrap();
+        let end: Decimal = "-0.74364388703715870475219150613".parse().unwrap();
+        assert_eq!(lerp_decimal(&start, &end, 0.5, 128).to_string(), "-0.74364388703715870475219150612");
+    }
+
     #[test]
     fn test_logarithmic_interpolation() {
         // Test logarithmic interpolation for zoom
@@ -123,6 +336,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::zero_divided_by_zero)]
     fn test_interpolation_parameter_calculation() {
         let total_frames = 10;
 
@@ -150,9 +364,9 @@ mod tests {
             output_path: Some("test.mp4".to_string()),
             config: None,
             bands: 16,
-            center_x: 1.0,
-            center_y: 2.0,
-            zoom: 3.0,
+            center_x: Decimal::from(1.0),
+            center_y: Decimal::from(2.0),
+            zoom: Decimal::from(3.0),
             m_size: 10.0,
             grid_input: None,
             end_center_x: None,
@@ -166,6 +380,92 @@ mod tests {
             zoom_text_y: 110,
             zoom_font_size: 20.0,
             function: "mandelbrot".to_string(),
+            threads: 0,
+            color_mode: "bands".to_string(),
+            interior: "solid".to_string(),
+            bailout: 2.0,
+            julia_re: -0.8,
+            julia_im: 0.156,
+            end_julia_re: None,
+            end_julia_im: None,
+            julia_path: "line".to_string(),
+            engine: "direct".to_string(),
+            center: None,
+            scale: None,
+            end_center: None,
+            end_scale: None,
+            fractal: "mandelbrot".to_string(),
+            power: 2.0,
+            formula: "z^2 + c".to_string(),
+            formula_seed: "mandelbrot".to_string(),
+            trap: "point".to_string(),
+            trap_x: 0.0,
+            trap_y: 0.0,
+            trap_radius: 1.0,
+            trap_angle: 0.0,
+            trap_width: 0.1,
+            trap_image: None,
+            end_trap_x: None,
+            end_trap_y: None,
+            end_trap_radius: None,
+            end_trap_angle: None,
+            stripe_density: 5.0,
+            samples: 1000000,
+            seed: 0,
+            red_iterations: None,
+            green_iterations: None,
+            blue_iterations: None,
+            anti_buddhabrot: false,
+            sampling: "uniform".to_string(),
+            newton_coefficients: "1; 0; 0; -1".to_string(),
+            newton_roots: None,
+            newton_relaxation: 1.0,
+            newton_variant: "newton".to_string(),
+            lyapunov_sequence: "AB".to_string(),
+            bifurcation_map: "logistic".to_string(),
+            bifurcation_transient: 1000,
+            bifurcation_samples: 10000,
+            attractor: "clifford".to_string(),
+            attractor_params: None,
+            end_attractor_params: None,
+            flame_gamma: 4.0,
+            flame_vibrancy: 1.0,
+            flame_transforms: Vec::new(),
+            ifs: "fern".to_string(),
+            ifs_maps: Vec::new(),
+            chaos_vertices: 3,
+            chaos_ratio: 0.5,
+            chaos_rule: "any".to_string(),
+            packet_x: 0.0,
+            packet_y: 0.0,
+            packet_sigma: 0.5,
+            packet_kx: 0.0,
+            packet_ky: 0.0,
+            schrodinger_grid: 256,
+            schrodinger_time: 0.0,
+            end_schrodinger_time: None,
+            schrodinger_dt: 0.005,
+            potential: "free".to_string(),
+            potential_height: 20.0,
+            potential_width: 0.2,
+            well_width: 4.0,
+            barrier_gap: 1.0,
+            slit_separation: 1.0,
+            slit_width: 0.3,
+            harmonic_omega: 1.0,
+            potential_formula: "0.5 * (x^2 + y^2)".to_string(),
+            wave_display: "density".to_string(),
+            schrodinger_mode: "evolve".to_string(),
+            eigenstates: 6,
+            eigenstate_dimensions: 2,
+            orbital_n: 2,
+            orbital_l: 1,
+            orbital_m: 0,
+            end_orbital_m: None,
+            slice_tilt: 90.0,
+            slice_azimuth: 0.0,
+            end_slice_tilt: None,
+            end_slice_azimuth: None,
         };
 
         // Test that end values fall back to start values when None
@@ -187,14 +487,14 @@ mod tests {
             output_path: Some("test.mp4".to_string()),
             config: None,
             bands: 16,
-            center_x: 1.0,
-            center_y: 2.0,
-            zoom: 3.0,
+            center_x: Decimal::from(1.0),
+            center_y: Decimal::from(2.0),
+            zoom: Decimal::from(3.0),
             m_size: 10.0,
             grid_input: Some("grid_input.png".to_string()),
-            end_center_x: Some(4.0),
-            end_center_y: Some(5.0),
-            end_zoom: Some(6.0),
+            end_center_x: Some(Decimal::from(4.0)),
+            end_center_y: Some(Decimal::from(5.0)),
+            end_zoom: Some(Decimal::from(6.0)),
             fps: 30.0,
             duration: 1.0,
             frames_dir: "frames".to_string(),
@@ -203,6 +503,92 @@ mod tests {
             zoom_text_y: 110,
             zoom_font_size: 20.0,
             function: "mandelbrot".to_string(),
+            threads: 0,
+            color_mode: "bands".to_string(),
+            interior: "solid".to_string(),
+            bailout: 2.0,
+            julia_re: -0.8,
+            julia_im: 0.156,
+            end_julia_re: None,
+            end_julia_im: None,
+            julia_path: "line".to_string(),
+            engine: "direct".to_string(),
+            center: None,
+            scale: None,
+            end_center: None,
+            end_scale: None,
+            fractal: "mandelbrot".to_string(),
+            power: 2.0,
+            formula: "z^2 + c".to_string(),
+            formula_seed: "mandelbrot".to_string(),
+            trap: "point".to_string(),
+            trap_x: 0.0,
+            trap_y: 0.0,
+            trap_radius: 1.0,
+            trap_angle: 0.0,
+            trap_width: 0.1,
+            trap_image: None,
+            end_trap_x: None,
+            end_trap_y: None,
+            end_trap_radius: None,
+            end_trap_angle: None,
+            stripe_density: 5.0,
+            samples: 1000000,
+            seed: 0,
+            red_iterations: None,
+            green_iterations: None,
+            blue_iterations: None,
+            anti_buddhabrot: false,
+            sampling: "uniform".to_string(),
+            newton_coefficients: "1; 0; 0; -1".to_string(),
+            newton_roots: None,
+            newton_relaxation: 1.0,
+            newton_variant: "newton".to_string(),
+            lyapunov_sequence: "AB".to_string(),
+            bifurcation_map: "logistic".to_string(),
+            bifurcation_transient: 1000,
+            bifurcation_samples: 10000,
+            attractor: "clifford".to_string(),
+            attractor_params: None,
+            end_attractor_params: None,
+            flame_gamma: 4.0,
+            flame_vibrancy: 1.0,
+            flame_transforms: Vec::new(),
+            ifs: "fern".to_string(),
+            ifs_maps: Vec::new(),
+            chaos_vertices: 3,
+            chaos_ratio: 0.5,
+            chaos_rule: "any".to_string(),
+            packet_x: 0.0,
+            packet_y: 0.0,
+            packet_sigma: 0.5,
+            packet_kx: 0.0,
+            packet_ky: 0.0,
+            schrodinger_grid: 256,
+            schrodinger_time: 0.0,
+            end_schrodinger_time: None,
+            schrodinger_dt: 0.005,
+            potential: "free".to_string(),
+            potential_height: 20.0,
+            potential_width: 0.2,
+            well_width: 4.0,
+            barrier_gap: 1.0,
+            slit_separation: 1.0,
+            slit_width: 0.3,
+            harmonic_omega: 1.0,
+            potential_formula: "0.5 * (x^2 + y^2)".to_string(),
+            wave_display: "density".to_string(),
+            schrodinger_mode: "evolve".to_string(),
+            eigenstates: 6,
+            eigenstate_dimensions: 2,
+            orbital_n: 2,
+            orbital_l: 1,
+            orbital_m: 0,
+            end_orbital_m: None,
+            slice_tilt: 90.0,
+            slice_azimuth: 0.0,
+            end_slice_tilt: None,
+            end_slice_azimuth: None,
         };
 
         let end_cx = args.end_center_x.unwrap_or(args.center_x);
@@ -213,4 +599,173 @@ mod tests {
         assert_eq!(end_cy, 5.0);
         assert_eq!(end_z, 6.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_julia_constant_along_line() {
+        let mut args = Args {
+            width: 800,
+            height: 600,
+            max_iterations: 1000,
+            output_path: Some("test.mp4".to_string()),
+            config: None,
+            bands: 16,
+            center_x: Decimal::from(1.0),
+            center_y: Decimal::from(2.0),
+            zoom: Decimal::from(3.0),
+            m_size: 10.0,
+            grid_input: Some("grid_input.png".to_string()),
+            end_center_x: Some(Decimal::from(4.0)),
+            end_center_y: Some(Decimal::from(5.0)),
+            end_zoom: Some(Decimal::from(6.0)),
+            fps: 30.0,
+            duration: 1.0,
+            frames_dir: "frames".to_string(),
+            font_path: "/font.ttf".to_string(),
+            zoom_text_x: 10,
+            zoom_text_y: 110,
+            zoom_font_size: 20.0,
+            function: "mandelbrot".to_string(),
+            threads: 0,
+            color_mode: "bands".to_string(),
+            interior: "solid".to_string(),
+            bailout: 2.0,
+            julia_re: -0.8,
+            julia_im: 0.156,
+            end_julia_re: Some(0.0),
+            end_julia_im: Some(0.5),
+            julia_path: "line".to_string(),
+            engine: "direct".to_string(),
+            center: None,
+            scale: None,
+            end_center: None,
+            end_scale: None,
+            fractal: "mandelbrot".to_string(),
+            power: 2.0,
+            formula: "z^2 + c".to_string(),
+            formula_seed: "mandelbrot".to_string(),
+            trap: "point".to_string(),
+            trap_x: 0.0,
+            trap_y: 0.0,
+            trap_radius: 1.0,
+            trap_angle: 0.0,
+            trap_width: 0.1,
+            trap_image: None,
+            end_trap_x: None,
+            end_trap_y: None,
+            end_trap_radius: None,
+            end_trap_angle: None,
+            stripe_density: 5.0,
+            samples: 1000000,
+            seed: 0,
+            red_iterations: None,
+            green_iterations: None,
+            blue_iterations: None,
+            anti_buddhabrot: false,
+            sampling: "uniform".to_string(),
+            newton_coefficients: "1; 0; 0; -1".to_string(),
+            newton_roots: None,
+            newton_relaxation: 1.0,
+            newton_variant: "newton".to_string(),
+            lyapunov_sequence: "AB".to_string(),
+            bifurcation_map: "logistic".to_string(),
+            bifurcation_transient: 1000,
+            bifurcation_samples: 10000,
+            attractor: "clifford".to_string(),
+            attractor_params: None,
+            end_attractor_params: None,
+            flame_gamma: 4.0,
+            flame_vibrancy: 1.0,
+            flame_transforms: Vec::new(),
+            ifs: "fern".to_string(),
+            ifs_maps: Vec::new(),
+            chaos_vertices: 3,
+            chaos_ratio: 0.5,
+            chaos_rule: "any".to_string(),
+            packet_x: 0.0,
+            packet_y: 0.0,
+            packet_sigma: 0.5,
+            packet_kx: 0.0,
+            packet_ky: 0.0,
+            schrodinger_grid: 256,
+            schrodinger_time: 0.0,
+            end_schrodinger_time: None,
+            schrodinger_dt: 0.005,
+            potential: "free".to_string(),
+            potential_height: 20.0,
+            potential_width: 0.2,
+            well_width: 4.0,
+            barrier_gap: 1.0,
+            slit_separation: 1.0,
+            slit_width: 0.3,
+            harmonic_omega: 1.0,
+            potential_formula: "0.5 * (x^2 + y^2)".to_string(),
+            wave_display: "density".to_string(),
+            schrodinger_mode: "evolve".to_string(),
+            eigenstates: 6,
+            eigenstate_dimensions: 2,
+            orbital_n: 2,
+            orbital_l: 1,
+            orbital_m: 0,
+            end_orbital_m: None,
+            slice_tilt: 90.0,
+            slice_azimuth: 0.0,
+            end_slice_tilt: None,
+            end_slice_azimuth: None,
+        };
+
+        assert_eq!(julia_constant_at(&args, 0.0, 0.0), (-0.8, 0.156));
+        assert_eq!(julia_constant_at(&args, 1.0, 1.0), (0.0, 0.5));
+        let (re, im) = julia_constant_at(&args, 0.5, 0.5);
+        assert!((re - -0.4).abs() < 1e-12);
+        assert!((im - 0.328).abs() < 1e-12);
+
+        // Around a circle centered at the end point, returning to the start
+        args.julia_path = "circle".to_string();
+        let (re, im) = julia_constant_at(&args, 0.0, 0.0);
+        assert!((re - -0.8).abs() < 1e-12 && (im - 0.156).abs() < 1e-12);
+        let (re, im) = julia_constant_at(&args, 1.0, 1.0);
+        assert!((re - -0.8).abs() < 1e-12 && (im - 0.156).abs() < 1e-12);
+        let (re, im) = julia_constant_at(&args, 0.5, 0.5);
+        assert!((re - 0.8).abs() < 1e-12 && (im - 0.844).abs() < 1e-12);
+
+        // Trap geometry eases to the end values, missing ones stay put
+        args.end_trap_x = Some(1.0);
+        args.end_trap_radius = Some(3.0);
+        let start_trap = Trap::from_args(&args);
+        assert_eq!(trap_at(&args, &start_trap, 0.0), start_trap);
+        let halfway = trap_at(&args, &start_trap, 0.5);
+        assert_eq!((halfway.x, halfway.y, halfway.radius, halfway.angle), (0.5, 0.0, 2.0, 0.0));
+
+        // Simulated time runs linearly, and stands still without an end time
+        assert_eq!(schrodinger_time_at(&args, 0.5), 0.0);
+        args.schrodinger_time = 1.0;
+        args.end_schrodinger_time = Some(3.0);
+        assert_eq!(schrodinger_time_at(&args, 0.25), 1.5);
+        assert_eq!(schrodinger_time_at(&args, 1.0), 3.0);
+
+        // m steps through every value for an equal share, the slice eases
+        args.orbital_n = 3;
+        args.orbital_l = 2;
+        args.orbital_m = -2;
+        args.end_orbital_m = Some(2);
+        args.end_slice_tilt = Some(0.0);
+        let m_at = |smooth_t: f64| orbital_at(&args, smooth_t).0;
+        assert_eq!(m_at(0.0), Orbital::new(3, 2, -2).unwrap());
+        assert_eq!(m_at(0.19), Orbital::new(3, 2, -2).unwrap());
+        assert_eq!(m_at(0.21), Orbital::new(3, 2, -1).unwrap());
+        assert_eq!(m_at(0.5), Orbital::new(3, 2, 0).unwrap());
+        assert_eq!(m_at(1.0), Orbital::new(3, 2, 2).unwrap());
+        assert_eq!(orbital_at(&args, 0.5).1, Slice::new(45.0, 0.0));
+    }
+
+    #[test]
+    fn test_attractor_params_at() {
+        let start = [-1.4, 1.6, 1.0, 0.7];
+        let end = [-1.8, 1.6, 2.0, 0.7];
+        assert_eq!(attractor_params_at(&start, &end, 0.0), start.to_vec());
+        assert_eq!(attractor_params_at(&start, &end, 1.0), end.to_vec());
+        let halfway = attractor_params_at(&start, &end, 0.5);
+        assert!((halfway[0] - -1.6).abs() < 1e-12);
+        assert_eq!(&halfway[1..], &[1.6, 1.5, 0.7]);
+    }
+}
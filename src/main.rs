@@ -1,23 +1,77 @@
 mod generate_mandelbrot;
 mod generate_schrodinger;
+mod generate_hydrogen;
 mod generate_video;
 mod generate_manual;
 mod hsv_to_rgb;
 mod config;
+mod big_fixed;
+mod decimal;
+mod perturbation;
 mod parameters;
+mod render_parallel;
+mod formula;
+mod generate_buddhabrot;
+mod generate_newton;
+mod generate_lyapunov;
+mod generate_bifurcation;
+mod generate_attractor;
+mod generate_flame;
+mod generate_ifs;
+mod rng;
+mod fft;
+mod eigenstates;
+mod orbit_trap;
 
 fn main() {
     let (args, output_path) = parameters::prepare_parameters();
 
-    let is_video = args.end_center_x.is_some() || args.end_center_y.is_some() || args.end_zoom.is_some();
+    // Every renderer runs its parallel work on the global pool
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(args.threads)
+        .build_global()
+        .expect("Failed to create render thread pool");
+
+    let is_video = args.end_center_x.is_some() || args.end_center_y.is_some() || args.end_zoom.is_some()
+        || args.end_julia_re.is_some() || args.end_julia_im.is_some()
+        || args.end_trap_x.is_some() || args.end_trap_y.is_some() || args.end_trap_radius.is_some() || args.end_trap_angle.is_some()
+        || args.end_attractor_params.is_some() || args.end_schrodinger_time.is_some()
+        || args.end_orbital_m.is_some() || args.end_slice_tilt.is_some() || args.end_slice_azimuth.is_some();
 
     if is_video {
         generate_video::generate_video(&args, &output_path);
     } else {
         match args.function.as_str() {
-            "mandelbrot" => generate_mandelbrot::generate_mandelbrot(args.width, args.height, args.max_iterations, args.bands, args.center_x, args.center_y, args.zoom, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path),
-            "schrodinger" => generate_schrodinger::generate_schrodinger(args.width, args.height, args.bands, args.center_x, args.center_y, args.zoom, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path),
-            "manual" => generate_manual::generate_manual(args.width, args.height, args.max_iterations, args.bands, args.center_x, args.center_y, args.zoom, args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path),
+            "mandelbrot" => generate_mandelbrot::generate_mandelbrot(args.width, args.height, args.max_iterations, args.bands, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, &args.color_mode, &args.interior, args.bailout, &orbit_trap::Trap::from_args(&args), args.stripe_density, &args.fractal, args.power, &args.engine),
+            "julia" => generate_mandelbrot::generate_julia(args.width, args.height, args.max_iterations, args.bands, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, &args.color_mode, &args.interior, args.bailout, &orbit_trap::Trap::from_args(&args), args.stripe_density, &args.fractal, args.power, args.julia_re, args.julia_im),
+            "formula" => generate_mandelbrot::generate_formula(args.width, args.height, args.max_iterations, args.bands, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, &args.color_mode, &args.interior, args.bailout, &orbit_trap::Trap::from_args(&args), args.stripe_density, &args.formula, &args.formula_seed, args.julia_re, args.julia_im),
+            "buddhabrot" => generate_buddhabrot::generate_buddhabrot(args.width, args.height, args.max_iterations, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, args.bailout, args.samples, args.seed, args.red_iterations, args.green_iterations, args.blue_iterations, args.anti_buddhabrot, &args.sampling),
+            "newton" => generate_newton::generate_newton(args.width, args.height, args.max_iterations, args.bands, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, &args.newton_coefficients, args.newton_roots.as_deref(), args.newton_relaxation, &args.newton_variant),
+            "lyapunov" => generate_lyapunov::generate_lyapunov(args.width, args.height, args.max_iterations, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, &args.lyapunov_sequence),
+            "bifurcation" => generate_bifurcation::generate_bifurcation(args.width, args.height, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, &args.bifurcation_map, args.bifurcation_transient, args.bifurcation_samples),
+            "attractor" => generate_attractor::generate_attractor(args.width, args.height, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, args.samples, args.seed, &args.attractor, &generate_attractor::params_or_exit(&args.attractor, args.attractor_params.as_deref())),
+            "flame" => generate_flame::generate_flame(args.width, args.height, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, args.samples, args.seed, &args.flame_transforms, args.flame_gamma, args.flame_vibrancy),
+            "ifs" => generate_ifs::generate_ifs(args.width, args.height, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, args.samples, args.seed, &args.ifs, &args.ifs_maps, args.chaos_vertices, args.chaos_ratio, &args.chaos_rule),
+            "schrodinger" => {
+                let mut simulation = generate_schrodinger::Simulation::from_args(&args);
+                match args.schrodinger_mode.as_str() {
+                    "evolve" => {
+                        simulation.advance_to(args.schrodinger_time);
+                        generate_schrodinger::generate_schrodinger(args.width, args.height, args.bands, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, &simulation, &args.wave_display)
+                    }
+                    "eigenstates" => generate_schrodinger::generate_eigenstates(args.width, args.height, args.bands, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, &simulation, &args.wave_display, args.eigenstates, args.eigenstate_dimensions, args.seed),
+                    _ => {
+                        eprintln!("Unknown Schrödinger mode: {}", args.schrodinger_mode);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "hydrogen" => {
+                let orbital = generate_hydrogen::orbital_or_exit(args.orbital_n, args.orbital_l, args.orbital_m);
+                let slice = generate_hydrogen::Slice::new(args.slice_tilt, args.slice_azimuth);
+                generate_hydrogen::generate_hydrogen(args.width, args.height, args.bands, &args.center_x, &args.center_y, args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path, &orbital, &slice, &args.wave_display)
+            }
+            "manual" => generate_manual::generate_manual(args.width, args.height, args.max_iterations, args.bands, args.center_x.to_f64(), args.center_y.to_f64(), args.zoom.to_f64(), args.m_size, &args.font_path, args.zoom_text_x, args.zoom_text_y, args.zoom_font_size, &output_path),
             "grid" => {
                 if let Some(grid_input) = &args.grid_input {
                     generate_mandelbrot::add_grid_to_image(grid_input).unwrap_or_else(|e| {
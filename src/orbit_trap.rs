@@ -0,0 +1,220 @@
+use std::sync::Arc;
+
+use image::{Rgba, RgbaImage};
+
+use crate::hsv_to_rgb::hsv_to_rgb;
+
+/// Shape an orbit is measured against.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrapShape {
+    /// Distance to the trap center.
+    Point,
+    /// Distance to the line through the center at `angle`.
+    Line,
+    /// Distance to the nearer of two perpendicular lines through the center.
+    Cross,
+    /// Distance to the circle of `radius` around the center.
+    Circle,
+    /// First orbit point that lands on an opaque pixel of an image centered on the
+    /// trap center, scaled so that its longer side spans `2 * radius`.
+    Image(Arc<RgbaImage>),
+}
+
+/// Orbit trap geometry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trap {
+    pub shape: TrapShape,
+    /// Real part of the trap center.
+    pub x: f64,
+    /// Imaginary part of the trap center.
+    pub y: f64,
+    /// Radius of the circle trap, half size of the image trap.
+    pub radius: f64,
+    /// Angle of the line and cross traps in degrees.
+    pub angle: f64,
+    /// Distance over which the trap color fades out.
+    pub width: f64,
+}
+
+/// Closest approach of an orbit to a trap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrapHit {
+    /// Minimum distance of the orbit to the trap (0 for image hits).
+    pub distance: f64,
+    /// Real part of the orbit point closest to the trap.
+    pub x: f64,
+    /// Imaginary part of the orbit point closest to the trap.
+    pub y: f64,
+}
+
+impl Trap {
+    /// Creates a trap from a shape name as used on the command line and in config files.
+    ///
+    /// Exits the process with an error message if the name is unknown, or if the
+    /// image of an 'image' trap is missing or cannot be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - 'point', 'line', 'cross', 'circle' or 'image'.
+    /// * `x` - Real part of the trap center.
+    /// * `y` - Imaginary part of the trap center.
+    /// * `radius` - Radius of the circle trap, half size of the image trap.
+    /// * `angle` - Angle of the line and cross traps in degrees.
+    /// * `width` - Distance over which the trap color fades out.
+    /// * `image_path` - Path to the image of the 'image' trap.
+    pub fn from_name(name: &str, x: f64, y: f64, radius: f64, angle: f64, width: f64, image_path: Option<&str>) -> Trap {
+        let shape = match name {
+            "point" => TrapShape::Point,
+            "line" => TrapShape::Line,
+            "cross" => TrapShape::Cross,
+            "circle" => TrapShape::Circle,
+            "image" => {
+                let image_path = image_path.unwrap_or_else(|| {
+                    eprintln!("The image trap requires the --trap-image parameter");
+                    std::process::exit(1);
+                });
+                let image = image::open(image_path).unwrap_or_else(|e| {
+                    eprintln!("Failed to read trap image '{}': {}", image_path, e);
+                    std::process::exit(1);
+                });
+                TrapShape::Image(Arc::new(image.to_rgba8()))
+            }
+            _ => {
+                eprintln!("Unknown trap: {}", name);
+                std::process::exit(1);
+            }
+        };
+        Trap { shape, x, y, radius, angle, width }
+    }
+
+    /// Creates the trap described by the trap parameters of the command line.
+    pub fn from_args(args: &crate::parameters::Args) -> Trap {
+        Trap::from_name(&args.trap, args.trap_x, args.trap_y, args.trap_radius, args.trap_angle, args.trap_width, args.trap_image.as_deref())
+    }
+
+    /// Returns the same trap moved to a new center, radius and angle.
+    ///
+    /// Image data is shared, so this is cheap enough to call for every video frame.
+    pub fn with_geometry(&self, x: f64, y: f64, radius: f64, angle: f64) -> Trap {
+        Trap { shape: self.shape.clone(), x, y, radius, angle, width: self.width }
+    }
+
+    /// Distance of a point to the trap, or `None` if the point misses an image trap.
+    fn distance(&self, zx: f64, zy: f64) -> Option<f64> {
+        let dx = zx - self.x;
+        let dy = zy - self.y;
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        match &self.shape {
+            TrapShape::Point => Some(dx.hypot(dy)),
+            TrapShape::Line => Some((dy * cos - dx * sin).abs()),
+            TrapShape::Cross => Some((dy * cos - dx * sin).abs().min((dx * cos + dy * sin).abs())),
+            TrapShape::Circle => Some((dx.hypot(dy) - self.radius).abs()),
+            TrapShape::Image(image) => image_pixel(image, dx, dy, self.radius).filter(|pixel| pixel[3] > 0).map(|_| 0.0),
+        }
+    }
+
+    /// Records an orbit point, keeping the closest one to the trap.
+    ///
+    /// Image traps keep the first point that hits the image.
+    pub fn visit(&self, hit: &mut Option<TrapHit>, zx: f64, zy: f64) {
+        if matches!(self.shape, TrapShape::Image(_)) && hit.is_some() {
+            return;
+        }
+        if let Some(distance) = self.distance(zx, zy)
+            && hit.is_none_or(|best| distance < best.distance)
+        {
+            *hit = Some(TrapHit { distance, x: zx, y: zy });
+        }
+    }
+
+    /// Color of an orbit that came closest to the trap at `hit`.
+    ///
+    /// Geometric traps glow from red at the trap through the hue circle to dark blue
+    /// at `width` away; image traps take the color of the pixel that was hit.
+    pub fn color(&self, hit: &TrapHit) -> Rgba<u8> {
+        match &self.shape {
+            TrapShape::Image(image) => image_pixel(image, hit.x - self.x, hit.y - self.y, self.radius).unwrap_or(Rgba([0, 0, 0, 255])),
+            _ => {
+                let falloff = (-hit.distance / self.width.max(f64::MIN_POSITIVE)).exp();
+                hsv_to_rgb(((1.0 - falloff) * 240.0) as f32, 255, (falloff * 255.0) as u8)
+            }
+        }
+    }
+}
+
+/// Looks up the image pixel under an offset from the trap center.
+fn image_pixel(image: &RgbaImage, dx: f64, dy: f64, radius: f64) -> Option<Rgba<u8>> {
+    let (width, height) = image.dimensions();
+    let pixels_per_unit = width.max(height) as f64 / (2.0 * radius);
+    let px = dx * pixels_per_unit + width as f64 / 2.0;
+    let py = dy * pixels_per_unit + height as f64 / 2.0;
+    if px < 0.0 || py < 0.0 || px >= width as f64 || py >= height as f64 {
+        return None;
+    }
+    Some(*image.get_pixel(px as u32, py as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trap(shape: TrapShape) -> Trap {
+        Trap { shape, x: 1.0, y: 0.0, radius: 0.5, angle: 0.0, width: 0.1 }
+    }
+
+    #[test]
+    fn test_trap_distances() {
+        assert_eq!(trap(TrapShape::Point).distance(1.0, 2.0), Some(2.0));
+        assert_eq!(trap(TrapShape::Line).distance(5.0, -0.25), Some(0.25));
+        assert_eq!(trap(TrapShape::Cross).distance(1.1, 3.0), Some(0.10000000000000009));
+        assert_eq!(trap(TrapShape::Circle).distance(1.0, 2.0), Some(1.5));
+
+        // A vertical line through the center
+        let vertical = Trap { angle: 90.0, ..trap(TrapShape::Line) };
+        assert!((vertical.distance(1.5, 7.0).unwrap() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_visit_keeps_closest_point() {
+        let point = trap(TrapShape::Point);
+        let mut hit = None;
+        for &(x, y) in &[(3.0, 0.0), (1.0, 0.5), (2.0, 0.0)] {
+            point.visit(&mut hit, x, y);
+        }
+        assert_eq!(hit, Some(TrapHit { distance: 0.5, x: 1.0, y: 0.5 }));
+    }
+
+    #[test]
+    fn test_image_trap() {
+        // 2x2 image: left column opaque red, right column transparent
+        let mut image = RgbaImage::new(2, 2);
+        for y in 0..2 {
+            image.put_pixel(0, y, Rgba([255, 0, 0, 255]));
+            image.put_pixel(1, y, Rgba([0, 0, 0, 0]));
+        }
+        let image_trap = trap(TrapShape::Image(Arc::new(image)));
+
+        let mut hit = None;
+        // Outside, transparent, opaque, then opaque again (ignored)
+        for &(x, y) in &[(3.0, 0.0), (1.25, 0.0), (0.75, 0.1), (0.8, -0.1)] {
+            image_trap.visit(&mut hit, x, y);
+        }
+        assert_eq!(hit, Some(TrapHit { distance: 0.0, x: 0.75, y: 0.1 }));
+        assert_eq!(image_trap.color(&hit.unwrap()), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_trap_color_fades_with_distance() {
+        let point = trap(TrapShape::Point);
+        assert_eq!(point.color(&TrapHit { distance: 0.0, x: 1.0, y: 0.0 }), Rgba([255, 0, 0, 255]));
+        let far = point.color(&TrapHit { distance: 10.0, x: 11.0, y: 0.0 });
+        assert_eq!(far, Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_with_geometry() {
+        let moved = trap(TrapShape::Circle).with_geometry(-1.0, 0.5, 2.0, 45.0);
+        assert_eq!((moved.x, moved.y, moved.radius, moved.angle, moved.width), (-1.0, 0.5, 2.0, 45.0, 0.1));
+        assert_eq!(moved.shape, TrapShape::Circle);
+    }
+}
@@ -1,5 +1,10 @@
 use clap::{Parser, CommandFactory, FromArgMatches};
 
+use crate::decimal::{ComplexDecimal, Decimal};
+use crate::generate_flame::FlameTransform;
+use crate::generate_ifs::IfsMap;
+use crate::generate_mandelbrot::{plane_to_center, scale_to_zoom};
+
 /// CLI argument parser.
 #[derive(Parser)]
 pub struct Args {
@@ -31,17 +36,17 @@ pub struct Args {
     #[clap(short, long, default_value = "16")]
     pub bands: u32,
 
-    /// X coordinate of the center (pixel offset from base center).
+    /// X coordinate of the center (pixel offset from base center, any number of digits).
     #[clap(long, default_value = "0.0")]
-    pub center_x: f64,
+    pub center_x: Decimal,
 
-    /// Y coordinate of the center (pixel offset from base center).
+    /// Y coordinate of the center (pixel offset from base center, any number of digits).
     #[clap(long, default_value = "0.0")]
-    pub center_y: f64,
+    pub center_y: Decimal,
 
     /// Zoom level (1.0 = default, higher values zoom in).
     #[clap(long, default_value = "1.0")]
-    pub zoom: f64,
+    pub zoom: Decimal,
 
     /// Size of the mathematical space (square, affects coordinate range).
     #[clap(long, default_value = "10.0")]
@@ -49,15 +54,15 @@ pub struct Args {
 
     /// End X coordinate for video transition.
     #[clap(long)]
-    pub end_center_x: Option<f64>,
+    pub end_center_x: Option<Decimal>,
 
     /// End Y coordinate for video transition.
     #[clap(long)]
-    pub end_center_y: Option<f64>,
+    pub end_center_y: Option<Decimal>,
 
     /// End zoom level for video transition.
     #[clap(long)]
-    pub end_zoom: Option<f64>,
+    pub end_zoom: Option<Decimal>,
 
     /// Frames per second for video.
     #[clap(long, default_value = "30.0")]
@@ -87,9 +92,353 @@ pub struct Args {
     #[clap(long, default_value = "20.0")]
     pub zoom_font_size: f32,
 
-    /// Function to generate: 'mandelbrot', 'schrodinger', or 'manual'.
+    /// Function to generate: 'mandelbrot', 'julia', 'formula', 'buddhabrot', 'newton', 'lyapunov', 'bifurcation', 'attractor', 'flame', 'ifs', 'schrodinger', 'hydrogen', or 'manual'.
     #[clap(long, default_value = "mandelbrot")]
     pub function: String,
+
+    /// Number of render threads (0 = one per available core).
+    #[clap(long, default_value = "0")]
+    pub threads: usize,
+
+    /// Coloring mode for escape-time fractals: 'bands', 'smooth', 'distance', 'trap', 'stripe' or 'triangle'.
+    #[clap(long, default_value = "bands")]
+    pub color_mode: String,
+
+    /// Coloring of points inside the set: 'solid', 'period', 'multiplier' or 'multiplier_angle'.
+    #[clap(long, default_value = "solid")]
+    pub interior: String,
+
+    /// Escape radius for the iteration (larger values give more accurate smooth coloring).
+    #[clap(long, default_value = "2.0")]
+    pub bailout: f64,
+
+    /// Real part of the constant c for the Julia set.
+    #[clap(long, default_value = "-0.8", allow_hyphen_values = true)]
+    pub julia_re: f64,
+
+    /// Imaginary part of the constant c for the Julia set.
+    #[clap(long, default_value = "0.156", allow_hyphen_values = true)]
+    pub julia_im: f64,
+
+    /// End real part of the Julia constant for video (circle center for the 'circle' path).
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_julia_re: Option<f64>,
+
+    /// End imaginary part of the Julia constant for video (circle center for the 'circle' path).
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_julia_im: Option<f64>,
+
+    /// Path of the Julia constant in video: 'line' (start to end) or 'circle' (around the end point).
+    #[clap(long, default_value = "line")]
+    pub julia_path: String,
+
+    /// Mandelbrot iteration engine: 'direct' (f64) or 'perturbation' (deep zoom).
+    #[clap(long, default_value = "direct")]
+    pub engine: String,
+
+    /// Center in complex plane coordinates as 're,im' (overrides center_x/center_y).
+    #[clap(long, allow_hyphen_values = true)]
+    pub center: Option<ComplexDecimal>,
+
+    /// Extent of the shorter image side in complex plane units (overrides zoom).
+    #[clap(long)]
+    pub scale: Option<f64>,
+
+    /// End center in complex plane coordinates for video transition (overrides end_center_x/end_center_y).
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_center: Option<ComplexDecimal>,
+
+    /// End scale in complex plane units for video transition (overrides end_zoom).
+    #[clap(long)]
+    pub end_scale: Option<f64>,
+
+    /// Escape-time formula: 'mandelbrot', 'burning_ship', 'tricorn' or 'multibrot'.
+    #[clap(long, default_value = "mandelbrot")]
+    pub fractal: String,
+
+    /// Exponent d of the multibrot formula z^d + c (integer or real, greater than 1).
+    #[clap(long, default_value = "2.0")]
+    pub power: f64,
+
+    /// Iteration formula for the 'formula' function, in z, c and pixel (e.g. "z^3 - z + c").
+    #[clap(long, default_value = "z^2 + c")]
+    pub formula: String,
+
+    /// Seeding of the 'formula' function: 'mandelbrot' (z0 = 0, c = pixel) or 'julia' (z0 = pixel, c = julia constant).
+    #[clap(long, default_value = "mandelbrot")]
+    pub formula_seed: String,
+
+    /// Orbit trap shape for the 'trap' color mode: 'point', 'line', 'cross', 'circle' or 'image'.
+    #[clap(long, default_value = "point")]
+    pub trap: String,
+
+    /// Real part of the orbit trap center.
+    #[clap(long, default_value = "0.0", allow_hyphen_values = true)]
+    pub trap_x: f64,
+
+    /// Imaginary part of the orbit trap center.
+    #[clap(long, default_value = "0.0", allow_hyphen_values = true)]
+    pub trap_y: f64,
+
+    /// Radius of the circle trap, half size of the image trap.
+    #[clap(long, default_value = "1.0")]
+    pub trap_radius: f64,
+
+    /// Angle of the line and cross traps in degrees.
+    #[clap(long, default_value = "0.0", allow_hyphen_values = true)]
+    pub trap_angle: f64,
+
+    /// Distance from the trap over which its color fades out.
+    #[clap(long, default_value = "0.1")]
+    pub trap_width: f64,
+
+    /// Image for the 'image' trap; transparent pixels don't trap.
+    #[clap(long)]
+    pub trap_image: Option<String>,
+
+    /// End real part of the orbit trap center for video.
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_trap_x: Option<f64>,
+
+    /// End imaginary part of the orbit trap center for video.
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_trap_y: Option<f64>,
+
+    /// End orbit trap radius for video.
+    #[clap(long)]
+    pub end_trap_radius: Option<f64>,
+
+    /// End orbit trap angle in degrees for video.
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_trap_angle: Option<f64>,
+
+    /// Number of stripes per turn around the origin for the 'stripe' color mode.
+    #[clap(long, default_value = "5.0")]
+    pub stripe_density: f64,
+
+    /// Number of random c values sampled by the 'buddhabrot' function, or orbit points plotted by the 'attractor' function.
+    #[clap(long, default_value = "1000000")]
+    pub samples: u64,
+
+    /// Seed of the random number generator, for reproducible renders.
+    #[clap(long, default_value = "0")]
+    pub seed: u64,
+
+    /// Iteration limit of the red channel for a 'buddhabrot' Nebulabrot (defaults to max_iterations).
+    #[clap(long)]
+    pub red_iterations: Option<u32>,
+
+    /// Iteration limit of the green channel for a 'buddhabrot' Nebulabrot (defaults to max_iterations).
+    #[clap(long)]
+    pub green_iterations: Option<u32>,
+
+    /// Iteration limit of the blue channel for a 'buddhabrot' Nebulabrot (defaults to max_iterations).
+    #[clap(long)]
+    pub blue_iterations: Option<u32>,
+
+    /// Accumulate orbits that never escape instead (anti-Buddhabrot).
+    #[clap(long)]
+    pub anti_buddhabrot: bool,
+
+    /// Sampling of c for the 'buddhabrot' function: 'uniform' or 'importance' (for zoomed-in views).
+    #[clap(long, default_value = "uniform")]
+    pub sampling: String,
+
+    /// Polynomial coefficients for the 'newton' function, highest degree first, separated by ';' (default z^3 - 1).
+    #[clap(long, default_value = "1; 0; 0; -1", allow_hyphen_values = true)]
+    pub newton_coefficients: String,
+
+    /// Polynomial roots for the 'newton' function separated by ';', e.g. "1; -0.5+0.866i; -0.5-0.866i" (overrides the coefficients).
+    #[clap(long, allow_hyphen_values = true)]
+    pub newton_roots: Option<String>,
+
+    /// Damping factor of the Newton step (1.0 = plain Newton, other values give the relaxed method).
+    #[clap(long, default_value = "1.0", allow_hyphen_values = true)]
+    pub newton_relaxation: f64,
+
+    /// Variant of the 'newton' function: 'newton' (color by root) or 'nova' (z0 = 1, c = pixel added every step).
+    #[clap(long, default_value = "newton")]
+    pub newton_variant: String,
+
+    /// A/B sequence of growth rates for the 'lyapunov' function, e.g. "AABAB".
+    #[clap(long, default_value = "AB")]
+    pub lyapunov_sequence: String,
+
+    /// 1D map of the 'bifurcation' function: 'logistic', 'sine' or 'tent'.
+    #[clap(long, default_value = "logistic")]
+    pub bifurcation_map: String,
+
+    /// Iterations discarded per orbit of the 'bifurcation' function before points are plotted.
+    #[clap(long, default_value = "1000")]
+    pub bifurcation_transient: u32,
+
+    /// Orbit points plotted per image column of the 'bifurcation' function.
+    #[clap(long, default_value = "10000")]
+    pub bifurcation_samples: u32,
+
+    /// Attractor of the 'attractor' function: 'clifford', 'dejong', 'svensson', 'hopalong', 'lorenz' or 'rossler'.
+    #[clap(long, default_value = "clifford")]
+    pub attractor: String,
+
+    /// Attractor parameters separated by ';', e.g. "-1.4; 1.6; 1.0; 0.7" (defaults to the classic parameters).
+    #[clap(long, allow_hyphen_values = true)]
+    pub attractor_params: Option<String>,
+
+    /// End attractor parameters for video, separated by ';'.
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_attractor_params: Option<String>,
+
+    /// Gamma of the 'flame' function's log-density display.
+    #[clap(long, default_value = "4.0")]
+    pub flame_gamma: f64,
+
+    /// Vibrancy of the 'flame' function: 1.0 keeps colors saturated, 0.0 applies gamma per channel.
+    #[clap(long, default_value = "1.0")]
+    pub flame_vibrancy: f64,
+
+    /// Transforms of the 'flame' function, only set as [[flame_transforms]] in the config file (empty = built-in flame).
+    #[clap(skip)]
+    pub flame_transforms: Vec<FlameTransform>,
+
+    /// System of the 'ifs' function: 'fern', 'sierpinski', 'carpet', 'dragon', 'chaos' (regular polygon) or 'custom'.
+    #[clap(long, default_value = "fern")]
+    pub ifs: String,
+
+    /// Maps of a 'custom' IFS, only set as [[ifs_maps]] in the config file.
+    #[clap(skip)]
+    pub ifs_maps: Vec<IfsMap>,
+
+    /// Number of polygon vertices for '--ifs chaos'.
+    #[clap(long, default_value = "3")]
+    pub chaos_vertices: u32,
+
+    /// Fraction of the distance to the chosen vertex covered by a jump for '--ifs chaos'.
+    #[clap(long, default_value = "0.5")]
+    pub chaos_ratio: f64,
+
+    /// Restriction on consecutive maps of the 'ifs' function: 'any', 'no-repeat', 'no-neighbor' or 'no-counterclockwise'.
+    #[clap(long, default_value = "any")]
+    pub chaos_rule: String,
+
+    /// X coordinate of the initial wave packet center for the 'schrodinger' function.
+    #[clap(long, default_value = "0.0", allow_hyphen_values = true)]
+    pub packet_x: f64,
+
+    /// Y coordinate of the initial wave packet center for the 'schrodinger' function.
+    #[clap(long, default_value = "0.0", allow_hyphen_values = true)]
+    pub packet_y: f64,
+
+    /// Width σ of the initial wave packet (standard deviation of |ψ|²).
+    #[clap(long, default_value = "0.5")]
+    pub packet_sigma: f64,
+
+    /// Initial momentum of the wave packet along x (units with ħ = m = 1).
+    #[clap(long, default_value = "0.0", allow_hyphen_values = true)]
+    pub packet_kx: f64,
+
+    /// Initial momentum of the wave packet along y (units with ħ = m = 1).
+    #[clap(long, default_value = "0.0", allow_hyphen_values = true)]
+    pub packet_ky: f64,
+
+    /// Grid points per side of the 'schrodinger' simulation box (a power of two).
+    #[clap(long, default_value = "256")]
+    pub schrodinger_grid: u32,
+
+    /// Simulated time shown by the 'schrodinger' function (start time for video).
+    #[clap(long, default_value = "0.0", allow_hyphen_values = true)]
+    pub schrodinger_time: f64,
+
+    /// End simulated time for video of the 'schrodinger' function.
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_schrodinger_time: Option<f64>,
+
+    /// Time step of the 'schrodinger' simulation (a free particle needs no intermediate steps).
+    #[clap(long, default_value = "0.005")]
+    pub schrodinger_dt: f64,
+
+    /// Potential of the 'schrodinger' function: 'free', 'harmonic', 'square_well', 'infinite_well', 'barrier', 'double_barrier', 'double_slit' or 'formula'.
+    #[clap(long, default_value = "free")]
+    pub potential: String,
+
+    /// Height of barriers and of the walls of the 'square_well' potential.
+    #[clap(long, default_value = "20.0", allow_hyphen_values = true)]
+    pub potential_height: f64,
+
+    /// Thickness of barriers and of the 'double_slit' wall.
+    #[clap(long, default_value = "0.2")]
+    pub potential_width: f64,
+
+    /// Side of the 'square_well' and 'infinite_well' potentials.
+    #[clap(long, default_value = "4.0")]
+    pub well_width: f64,
+
+    /// Distance between the two barriers of the 'double_barrier' potential.
+    #[clap(long, default_value = "1.0")]
+    pub barrier_gap: f64,
+
+    /// Distance between the slit centers of the 'double_slit' potential.
+    #[clap(long, default_value = "1.0")]
+    pub slit_separation: f64,
+
+    /// Width of each slit of the 'double_slit' potential.
+    #[clap(long, default_value = "0.3")]
+    pub slit_width: f64,
+
+    /// Angular frequency ω of the 'harmonic' potential ½ω²(x² + y²).
+    #[clap(long, default_value = "1.0")]
+    pub harmonic_omega: f64,
+
+    /// Potential V(x, y) of the 'formula' potential, e.g. "0.5 * (x^2 + 4 * y^2)".
+    #[clap(long, default_value = "0.5 * (x^2 + y^2)")]
+    pub potential_formula: String,
+
+    /// Part of the wave function drawn by the 'schrodinger' and 'hydrogen' functions: 'density' (|ψ|² in bands), 'phase' (domain coloring), 'real' or 'imaginary'.
+    #[clap(long, default_value = "density")]
+    pub wave_display: String,
+
+    /// What the 'schrodinger' function computes: 'evolve' (the wave packet at --schrodinger-time) or 'eigenstates' (the lowest energy eigenstates of the potential).
+    #[clap(long, default_value = "evolve")]
+    pub schrodinger_mode: String,
+
+    /// Number of eigenstates computed in the 'eigenstates' mode of the 'schrodinger' function, on the --schrodinger-grid grid rather than the image pixels.
+    #[clap(long, default_value = "6")]
+    pub eigenstates: u32,
+
+    /// Dimensions of the 'eigenstates' mode: 2 for the whole box, or 1 for the potential along the x axis.
+    #[clap(long, default_value = "2")]
+    pub eigenstate_dimensions: u32,
+
+    /// Principal quantum number n of the 'hydrogen' function.
+    #[clap(long, default_value = "2")]
+    pub orbital_n: u32,
+
+    /// Azimuthal quantum number l of the 'hydrogen' function (0 to n - 1).
+    #[clap(long, default_value = "1")]
+    pub orbital_l: u32,
+
+    /// Magnetic quantum number m of the 'hydrogen' function (-l to l).
+    #[clap(long, default_value = "0", allow_hyphen_values = true)]
+    pub orbital_m: i32,
+
+    /// End magnetic quantum number for video, stepping through the values in between.
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_orbital_m: Option<i32>,
+
+    /// Tilt in degrees of the 'hydrogen' slice plane about the x axis: 0 is the xy plane, 90 the xz plane with z up.
+    #[clap(long, default_value = "90.0", allow_hyphen_values = true)]
+    pub slice_tilt: f64,
+
+    /// Rotation in degrees of the 'hydrogen' slice plane about the z axis.
+    #[clap(long, default_value = "0.0", allow_hyphen_values = true)]
+    pub slice_azimuth: f64,
+
+    /// End slice tilt in degrees for video.
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_slice_tilt: Option<f64>,
+
+    /// End slice azimuth in degrees for video.
+    #[clap(long, allow_hyphen_values = true)]
+    pub end_slice_azimuth: Option<f64>,
 }
 
 pub fn prepare_parameters() -> (Args, String) {
@@ -100,6 +449,8 @@ pub fn prepare_parameters() -> (Args, String) {
     let config_path = args.config.clone();
     crate::config::load_config(&mut args, config_path);
 
+    apply_plane_coordinates(&mut args);
+
     // Ensure output_path is set (except for grid function)
     let output_path = if args.function == "grid" {
         "dummy".to_string() // not used
@@ -117,9 +468,41 @@ pub fn prepare_parameters() -> (Args, String) {
     (args, output_path)
 }
 
+/// Converts complex-plane coordinates to the pixel-offset center and zoom used by the renderers.
+///
+/// `center`/`scale` (and `end_center`/`end_scale` for videos) are given in
+/// mathematical units and are converted for the current `width`, `height` and
+/// `m_size`, so the view stays on the same location when those change.
+pub fn apply_plane_coordinates(args: &mut Args) {
+    for scale in [args.scale, args.end_scale].into_iter().flatten() {
+        if scale <= 0.0 {
+            eprintln!("scale must be positive, got {}", scale);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(center) = &args.center {
+        let (center_x, center_y) = plane_to_center(center, args.width, args.height, args.m_size);
+        args.center_x = center_x;
+        args.center_y = center_y;
+    }
+    if let Some(end_center) = &args.end_center {
+        let (center_x, center_y) = plane_to_center(end_center, args.width, args.height, args.m_size);
+        args.end_center_x = Some(center_x);
+        args.end_center_y = Some(center_y);
+    }
+    if let Some(scale) = args.scale {
+        args.zoom = Decimal::from(scale_to_zoom(scale, args.m_size));
+    }
+    if let Some(end_scale) = args.end_scale {
+        args.end_zoom = Some(Decimal::from(scale_to_zoom(end_scale, args.m_size)));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::decimal::Decimal;
 
     #[test]
     fn test_args_default_values() {
@@ -130,9 +513,9 @@ mod tests {
             output_path: Some("test.png".to_string()),
             config: None,
             bands: 16,
-            center_x: -0.5,
-            center_y: 0.0,
-            zoom: 1.0,
+            center_x: Decimal::from(-0.5),
+            center_y: Decimal::from(0.0),
+            zoom: Decimal::from(1.0),
             m_size: 10.0,
             grid_input: None,
             end_center_x: None,
@@ -146,6 +529,92 @@ mod tests {
             zoom_text_y: 110,
             zoom_font_size: 20.0,
             function: "mandelbrot".to_string(),
+            threads: 0,
+            color_mode: "bands".to_string(),
+            interior: "solid".to_string(),
+            bailout: 2.0,
+            julia_re: -0.8,
+            julia_im: 0.156,
+            end_julia_re: None,
+            end_julia_im: None,
+            julia_path: "line".to_string(),
+            engine: "direct".to_string(),
+            center: None,
+            scale: None,
+            end_center: None,
+            end_scale: None,
+            fractal: "mandelbrot".to_string(),
+            power: 2.0,
+            formula: "z^2 + c".to_string(),
+            formula_seed: "mandelbrot".to_string(),
+            trap: "point".to_string(),
+            trap_x: 0.0,
+            trap_y: 0.0,
+            trap_radius: 1.0,
+            trap_angle: 0.0,
+            trap_width: 0.1,
+            trap_image: None,
+            end_trap_x: None,
+            end_trap_y: None,
+            end_trap_radius: None,
+            end_trap_angle: None,
+            stripe_density: 5.0,
+            samples: 1000000,
+            seed: 0,
+            red_iterations: None,
+            green_iterations: None,
+            blue_iterations: None,
+            anti_buddhabrot: false,
+            sampling: "uniform".to_string(),
+            newton_coefficients: "1; 0; 0; -1".to_string(),
+            newton_roots: None,
+            newton_relaxation: 1.0,
+            newton_variant: "newton".to_string(),
+            lyapunov_sequence: "AB".to_string(),
+            bifurcation_map: "logistic".to_string(),
+            bifurcation_transient: 1000,
+            bifurcation_samples: 10000,
+            attractor: "clifford".to_string(),
+            attractor_params: None,
+            end_attractor_params: None,
+            flame_gamma: 4.0,
+            flame_vibrancy: 1.0,
+            flame_transforms: Vec::new(),
+            ifs: "fern".to_string(),
+            ifs_maps: Vec::new(),
+            chaos_vertices: 3,
+            chaos_ratio: 0.5,
+            chaos_rule: "any".to_string(),
+            packet_x: 0.0,
+            packet_y: 0.0,
+            packet_sigma: 0.5,
+            packet_kx: 0.0,
+            packet_ky: 0.0,
+            schrodinger_grid: 256,
+            schrodinger_time: 0.0,
+            end_schrodinger_time: None,
+            schrodinger_dt: 0.005,
+            potential: "free".to_string(),
+            potential_height: 20.0,
+            potential_width: 0.2,
+            well_width: 4.0,
+            barrier_gap: 1.0,
+            slit_separation: 1.0,
+            slit_width: 0.3,
+            harmonic_omega: 1.0,
+            potential_formula: "0.5 * (x^2 + y^2)".to_string(),
+            wave_display: "density".to_string(),
+            schrodinger_mode: "evolve".to_string(),
+            eigenstates: 6,
+            eigenstate_dimensions: 2,
+            orbital_n: 2,
+            orbital_l: 1,
+            orbital_m: 0,
+            end_orbital_m: None,
+            slice_tilt: 90.0,
+            slice_azimuth: 0.0,
+            end_slice_tilt: None,
+            end_slice_azimuth: None,
         };
 
         assert_eq!(args.width, 800);
@@ -179,14 +648,14 @@ mod tests {
             output_path: Some("custom.png".to_string()),
             config: Some("config.conf".to_string()),
             bands: 32,
-            center_x: -0.75,
-            center_y: 0.1,
-            zoom: 2.0,
+            center_x: Decimal::from(-0.75),
+            center_y: Decimal::from(0.1),
+            zoom: Decimal::from(2.0),
             m_size: 20.0,
             grid_input: Some("input.png".to_string()),
-            end_center_x: Some(-0.8),
-            end_center_y: Some(0.2),
-            end_zoom: Some(4.0),
+            end_center_x: Some(Decimal::from(-0.8)),
+            end_center_y: Some(Decimal::from(0.2)),
+            end_zoom: Some(Decimal::from(4.0)),
             fps: 60.0,
             duration: 15.0,
             frames_dir: "custom_frames".to_string(),
@@ -195,6 +664,92 @@ mod tests {
             zoom_text_y: 150,
             zoom_font_size: 24.0,
             function: "schrodinger".to_string(),
+            threads: 0,
+            color_mode: "bands".to_string(),
+            interior: "solid".to_string(),
+            bailout: 2.0,
+            julia_re: -0.8,
+            julia_im: 0.156,
+            end_julia_re: None,
+            end_julia_im: None,
+            julia_path: "line".to_string(),
+            engine: "direct".to_string(),
+            center: None,
+            scale: None,
+            end_center: None,
+            end_scale: None,
+            fractal: "mandelbrot".to_string(),
+            power: 2.0,
+            formula: "z^2 + c".to_string(),
+            formula_seed: "mandelbrot".to_string(),
+            trap: "point".to_string(),
+            trap_x: 0.0,
+            trap_y: 0.0,
+            trap_radius: 1.0,
+            trap_angle: 0.0,
+            trap_width: 0.1,
+            trap_image: None,
+            end_trap_x: None,
+            end_trap_y: None,
+            end_trap_radius: None,
+            end_trap_angle: None,
+            stripe_density: 5.0,
+            samples: 1000000,
+            seed: 0,
+            red_iterations: None,
+            green_iterations: None,
+            blue_iterations: None,
+            anti_buddhabrot: false,
+            sampling: "uniform".to_string(),
+            newton_coefficients: "1; 0; 0; -1".to_string(),
+            newton_roots: None,
+            newton_relaxation: 1.0,
+            newton_variant: "newton".to_string(),
+            lyapunov_sequence: "AB".to_string(),
+            bifurcation_map: "logistic".to_string(),
+            bifurcation_transient: 1000,
+            bifurcation_samples: 10000,
+            attractor: "clifford".to_string(),
+            attractor_params: None,
+            end_attractor_params: None,
+            flame_gamma: 4.0,
+            flame_vibrancy: 1.0,
+            flame_transforms: Vec::new(),
+            ifs: "fern".to_string(),
+            ifs_maps: Vec::new(),
+            chaos_vertices: 3,
+            chaos_ratio: 0.5,
+            chaos_rule: "any".to_string(),
+            packet_x: 0.0,
+            packet_y: 0.0,
+            packet_sigma: 0.5,
+            packet_kx: 0.0,
+            packet_ky: 0.0,
+            schrodinger_grid: 256,
+            schrodinger_time: 0.0,
+            end_schrodinger_time: None,
+            schrodinger_dt: 0.005,
+            potential: "free".to_string(),
+            potential_height: 20.0,
+            potential_width: 0.2,
+            well_width: 4.0,
+            barrier_gap: 1.0,
+            slit_separation: 1.0,
+            slit_width: 0.3,
+            harmonic_omega: 1.0,
+            potential_formula: "0.5 * (x^2 + y^2)".to_string(),
+            wave_display: "density".to_string(),
+            schrodinger_mode: "evolve".to_string(),
+            eigenstates: 6,
+            eigenstate_dimensions: 2,
+            orbital_n: 2,
+            orbital_l: 1,
+            orbital_m: 0,
+            end_orbital_m: None,
+            slice_tilt: 90.0,
+            slice_azimuth: 0.0,
+            end_slice_tilt: None,
+            end_slice_azimuth: None,
         };
 
         assert_eq!(args.width, 1024);
@@ -206,9 +761,9 @@ mod tests {
         assert_eq!(args.center_x, -0.75);
         assert_eq!(args.center_y, 0.1);
         assert_eq!(args.zoom, 2.0);
-        assert_eq!(args.end_center_x, Some(-0.8));
-        assert_eq!(args.end_center_y, Some(0.2));
-        assert_eq!(args.end_zoom, Some(4.0));
+        assert_eq!(args.end_center_x, Some(Decimal::from(-0.8)));
+        assert_eq!(args.end_center_y, Some(Decimal::from(0.2)));
+        assert_eq!(args.end_zoom, Some(Decimal::from(4.0)));
         assert_eq!(args.fps, 60.0);
         assert_eq!(args.duration, 15.0);
         assert_eq!(args.frames_dir, "custom_frames");
@@ -218,4 +773,31 @@ mod tests {
         assert_eq!(args.zoom_font_size, 24.0);
         assert_eq!(args.function, "schrodinger");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_apply_plane_coordinates() {
+        let mut args = Args::parse_from([
+            "mathillu", "--width", "400", "--height", "300", "--m-size", "10",
+            "--center", "-0.75,0.1", "--scale", "2.5", "--end-center", "0.25,-0.5", "--end-scale", "0.5",
+        ]);
+        apply_plane_coordinates(&mut args);
+
+        // 300 pixels span m_size = 10 units at zoom 1.0
+        assert_eq!(args.center_x, -22.5);
+        assert_eq!(args.center_y, 3.0);
+        assert_eq!(args.zoom, 4.0);
+        assert_eq!(args.end_center_x.unwrap(), 7.5);
+        assert_eq!(args.end_center_y.unwrap(), -15.0);
+        assert_eq!(args.end_zoom.unwrap(), 20.0);
+    }
+
+    #[test]
+    fn test_apply_plane_coordinates_keeps_pixel_offsets() {
+        let mut args = Args::parse_from(["mathillu", "--center-x", "12.5", "--zoom", "3"]);
+        apply_plane_coordinates(&mut args);
+
+        assert_eq!(args.center_x, 12.5);
+        assert_eq!(args.zoom, 3.0);
+        assert_eq!(args.end_center_x, None);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::big_fixed::BigFixed;
+use crate::generate_mandelbrot::Escape;
+
+/// Number of guard bits added on top of the pixel spacing when choosing the
+/// precision of the reference orbit.
+const GUARD_BITS: u32 = 64;
+
+/// Chooses the fixed-point precision needed to resolve a given pixel spacing.
+///
+/// # Arguments
+///
+/// * `pixel_size` - Distance between neighbouring pixels in the complex plane.
+///
+/// # Returns
+///
+/// The number of fractional bits for the high-precision reference point.
+pub fn precision_for(pixel_size: f64) -> u32 {
+    let pixel_bits = if pixel_size > 0.0 { (-pixel_size.log2()).ceil().max(0.0) as u32 } else { 0 };
+    pixel_bits + GUARD_BITS
+}
+
+/// Computes the orbit of the reference point in arbitrary precision.
+///
+/// The iteration is done entirely in fixed-point arithmetic; only the resulting
+/// orbit values, which are bounded by the bailout radius, are rounded to `f64`.
+///
+/// # Arguments
+///
+/// * `center_re` - Real part of the reference point c.
+/// * `center_im` - Imaginary part of the reference point c.
+/// * `max_iterations` - Maximum number of iterations to perform.
+/// * `bailout` - Escape radius; the orbit is cut off once |Z| exceeds it.
+///
+/// # Returns
+///
+/// The orbit Z₀ = 0, Z₁, ... up to and including the first escaped value
+/// (or `max_iterations` values). Always contains at least two entries.
+pub fn reference_orbit(center_re: &BigFixed, center_im: &BigFixed, max_iterations: u32, bailout: f64) -> Vec<(f64, f64)> {
+    let frac_bits = center_re.frac_bits().max(center_im.frac_bits());
+    let bailout_sqr = bailout * bailout;
+    let two = BigFixed::from_f64(2.0, frac_bits);
+    let mut zx = BigFixed::zero(frac_bits);
+    let mut zy = BigFixed::zero(frac_bits);
+    let mut orbit = vec![(0.0, 0.0)];
+
+    for _ in 0..max_iterations.max(1) {
+        let xx = &zx * &zx;
+        let yy = &zy * &zy;
+        let xy = &zx * &zy;
+        zx = &(&xx - &yy) + center_re;
+        zy = &(&two * &xy) + center_im;
+
+        let z = (zx.to_f64(), zy.to_f64());
+        orbit.push(z);
+        if z.0 * z.0 + z.1 * z.1 > bailout_sqr {
+            break;
+        }
+    }
+
+    orbit
+}
+
+/// Calculates the Mandelbrot iteration count of a point relative to a reference orbit.
+///
+/// Instead of iterating z directly, the difference δ = z - Z to the reference
+/// orbit is iterated in `f64` as δ' = 2Zδ + δ² + δc, which stays accurate far
+/// beyond the precision of `f64` coordinates. When the delta becomes larger
+/// than the full value (|Z + δ| < |δ|, the sign of a glitch caused by lost
+/// precision) or the reference orbit runs out, the orbit is rebased onto the
+/// start of the reference orbit.
+///
+/// # Arguments
+///
+/// * `orbit` - Reference orbit from `reference_orbit`.
+/// * `dcx` - Real part of the offset δc of the point from the reference point.
+/// * `dcy` - Imaginary part of the offset δc of the point from the reference point.
+/// * `max_iterations` - Maximum number of iterations to perform.
+/// * `bailout` - Escape radius; the orbit escapes once |z| exceeds it.
+///
+/// # Returns
+///
+/// The escape result, equivalent to iterating c = reference + δc directly.
+pub fn calc_perturbed(orbit: &[(f64, f64)], dcx: f64, dcy: f64, max_iterations: u32, bailout: f64) -> Escape {
+    let bailout_sqr = bailout * bailout;
+    let last = orbit.len() - 1;
+    let mut dx = 0.0;
+    let mut dy = 0.0;
+    let mut zx = 0.0;
+    let mut zy = 0.0;
+    let mut derivative_x = 0.0;
+    let mut derivative_y = 0.0;
+    let mut reference = 0;
+    let mut iteration = 0;
+
+    while iteration < max_iterations {
+        let (rx, ry) = orbit[reference];
+        // dz/dc of the full orbit: dz' = 2 z dz + 1
+        let (full_x, full_y) = (rx + dx, ry + dy);
+        (derivative_x, derivative_y) = (
+            2.0 * (full_x * derivative_x - full_y * derivative_y) + 1.0,
+            2.0 * (full_x * derivative_y + full_y * derivative_x),
+        );
+        let ndx = 2.0 * (rx * dx - ry * dy) + dx * dx - dy * dy + dcx;
+        let ndy = 2.0 * (rx * dy + ry * dx) + 2.0 * dx * dy + dcy;
+        dx = ndx;
+        dy = ndy;
+        reference += 1;
+        iteration += 1;
+
+        let (rx, ry) = orbit[reference];
+        zx = rx + dx;
+        zy = ry + dy;
+        let norm_sqr = zx * zx + zy * zy;
+        if norm_sqr > bailout_sqr {
+            break;
+        }
+
+        // Rebase when the delta dominates or the reference orbit is exhausted
+        if norm_sqr < dx * dx + dy * dy || reference == last {
+            dx = zx;
+            dy = zy;
+            reference = 0;
+        }
+    }
+
+    Escape {
+        iterations: iteration,
+        norm_sqr: zx * zx + zy * zy,
+        derivative_norm_sqr: derivative_x * derivative_x + derivative_y * derivative_y,
+        cycle: None,
+        trap: None,
+        average: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn direct(cx: f64, cy: f64, max_iterations: u32) -> u32 {
+        let (mut x, mut y, mut i) = (0.0_f64, 0.0_f64, 0);
+        while x * x + y * y <= 4.0 && i < max_iterations {
+            let xt = x * x - y * y + cx;
+            y = 2.0 * x * y + cy;
+            x = xt;
+            i += 1;
+        }
+        i
+    }
+
+    #[test]
+    fn test_precision_for() {
+        assert_eq!(precision_for(1.0), GUARD_BITS);
+        assert_eq!(precision_for(2f64.powi(-100)), 100 + GUARD_BITS);
+    }
+
+    #[test]
+    fn test_reference_orbit_matches_f64() {
+        let orbit = reference_orbit(&BigFixed::from_f64(-0.5, 128), &BigFixed::from_f64(0.5, 128), 50, 2.0);
+        assert_eq!(orbit.len(), 51);
+        let (mut x, mut y) = (0.0_f64, 0.0_f64);
+        for &(rx, ry) in &orbit[1..] {
+            let xt = x * x - y * y - 0.5;
+            y = 2.0 * x * y + 0.5;
+            x = xt;
+            assert!((rx - x).abs() < 1e-12 && (ry - y).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_reference_orbit_stops_on_escape() {
+        let orbit = reference_orbit(&BigFixed::from_f64(1.0, 64), &BigFixed::zero(64), 100, 2.0);
+        // 0 -> 1 -> 2 -> 5 (escaped)
+        assert_eq!(orbit, vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (5.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_perturbation_matches_direct_iteration() {
+        // Reference point inside the set, pixels spread over a shallow view
+        let (ref_x, ref_y) = (-0.75, 0.1);
+        let orbit = reference_orbit(&BigFixed::from_f64(ref_x, 128), &BigFixed::from_f64(ref_y, 128), 500, 2.0);
+        let mut matches = 0;
+        let mut total = 0;
+        for i in 0..40 {
+            for j in 0..40 {
+                let dcx = (i as f64 - 20.0) * 0.01;
+                let dcy = (j as f64 - 20.0) * 0.01;
+                let perturbed = calc_perturbed(&orbit, dcx, dcy, 500, 2.0).iterations;
+                if perturbed == direct(ref_x + dcx, ref_y + dcy, 500) {
+                    matches += 1;
+                }
+                total += 1;
+            }
+        }
+        // Rounding may differ right at the boundary of the set
+        assert!(matches * 100 >= total * 98, "{} of {} matched", matches, total);
+    }
+
+    #[test]
+    fn test_perturbation_tracks_derivative() {
+        let orbit = reference_orbit(&BigFixed::from_f64(-0.75, 128), &BigFixed::from_f64(0.1, 128), 1000, 100.0);
+        let (mut x, mut y, mut dx, mut dy) = (0.0_f64, 0.0_f64, 0.0_f64, 0.0_f64);
+        let (cx, cy) = (-0.75 + 0.3, 0.1 + 0.5);
+        while x * x + y * y <= 100.0 * 100.0 {
+            (dx, dy) = (2.0 * (x * dx - y * dy) + 1.0, 2.0 * (x * dy + y * dx));
+            (x, y) = (x * x - y * y + cx, 2.0 * x * y + cy);
+        }
+        let escape = calc_perturbed(&orbit, 0.3, 0.5, 1000, 100.0);
+        assert!((escape.derivative_norm_sqr / (dx * dx + dy * dy) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_perturbation_resolves_deep_zoom() {
+        // Pixels 1e-20 apart near a well-known seahorse valley location; plain f64
+        // cannot tell these points apart.
+        let center_re = BigFixed::parse("-0.743643887037158704752191506114774", 160).unwrap();
+        let center_im = BigFixed::parse("0.131825904205311970493132056385139", 160).unwrap();
+        let orbit = reference_orbit(&center_re, &center_im, 20000, 2.0);
+
+        let mut counts: Vec<u32> = (0..32)
+            .map(|i| calc_perturbed(&orbit, (i as f64 - 16.0) * 1e-20, 0.0, 20000, 2.0).iterations)
+            .collect();
+        counts.sort();
+        counts.dedup();
+        assert!(counts.len() > 4, "only {} distinct iteration counts", counts.len());
+
+        let direct_counts: Vec<u32> = (0..32)
+            .map(|i| direct(center_re.to_f64() + (i as f64 - 16.0) * 1e-20, center_im.to_f64(), 20000))
+            .collect();
+        assert!(direct_counts.iter().all(|&c| c == direct_counts[0]));
+    }
+}
@@ -0,0 +1,142 @@
+use image::{Rgba, RgbaImage};
+use rayon::prelude::*;
+
+/// Number of image rows rendered together as one tile.
+const TILE_ROWS: usize = 16;
+
+/// Fills an image by evaluating a per-pixel color function on the current rayon pool.
+///
+/// The image is split into horizontal tiles of `TILE_ROWS` rows which are rendered
+/// independently. Every pixel is computed exactly as in a serial pass, so the output
+/// is byte-identical regardless of the number of threads.
+///
+/// # Arguments
+///
+/// * `imgbuf` - The image to fill.
+/// * `color_at` - Returns the color of the pixel at (x, y).
+pub fn render_parallel<F>(imgbuf: &mut RgbaImage, color_at: F)
+where
+    F: Fn(u32, u32) -> Rgba<u8> + Sync,
+{
+    let width = imgbuf.width() as usize;
+    if width == 0 || imgbuf.height() == 0 {
+        return;
+    }
+    let row_len = width * 4;
+
+    imgbuf.par_chunks_mut(row_len * TILE_ROWS).enumerate().for_each(|(tile, chunk)| {
+        for (i, pixel) in chunk.chunks_exact_mut(4).enumerate() {
+            let x = (i % width) as u32;
+            let y = (tile * TILE_ROWS + i / width) as u32;
+            pixel.copy_from_slice(&color_at(x, y).0);
+        }
+    });
+}
+
+/// A histogram bin that can be merged with the same bin of another histogram.
+///
+/// Bins hold integer counts, so merging in any order gives the same result.
+pub trait HistogramBin: Copy + Default + Send {
+    /// Adds the counts of `other` to this bin.
+    fn merge(&mut self, other: Self);
+}
+
+impl HistogramBin for u64 {
+    fn merge(&mut self, other: u64) {
+        *self += other;
+    }
+}
+
+impl HistogramBin for [u64; 4] {
+    fn merge(&mut self, other: [u64; 4]) {
+        self.iter_mut().zip(other).for_each(|(a, b)| *a += b);
+    }
+}
+
+/// Accumulates a histogram over numbered chunks of work on the current rayon pool.
+///
+/// Every worker thread fills its own histogram with every `workers`-th chunk, and
+/// the histograms are merged at the end, so memory grows with the number of
+/// threads rather than with the number of chunks. As long as `fill` only depends
+/// on the chunk number, the result is identical regardless of the number of threads.
+///
+/// # Arguments
+///
+/// * `chunks` - Number of chunks.
+/// * `bins` - Number of bins of the histogram.
+/// * `fill` - Adds the counts of one chunk to a histogram.
+pub fn accumulate_chunks<T, F>(chunks: u64, bins: usize, fill: F) -> Vec<T>
+where
+    T: HistogramBin,
+    F: Fn(u64, &mut [T]) + Sync,
+{
+    let workers = (rayon::current_num_threads() as u64).clamp(1, chunks.max(1));
+    (0..workers)
+        .into_par_iter()
+        .map(|worker| {
+            let mut histogram = vec![T::default(); bins];
+            for chunk in (worker..chunks).step_by(workers as usize) {
+                fill(chunk, &mut histogram);
+            }
+            histogram
+        })
+        .reduce_with(|mut a, b| {
+            a.iter_mut().zip(b).for_each(|(a, b)| a.merge(b));
+            a
+        })
+        .expect("There is at least one worker")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::ImageBuffer;
+
+    fn pattern(x: u32, y: u32) -> Rgba<u8> {
+        Rgba([(x * 7 % 256) as u8, (y * 13 % 256) as u8, ((x ^ y) % 256) as u8, 255])
+    }
+
+    #[test]
+    fn test_render_parallel_matches_serial() {
+        let (width, height) = (37, 53); // not a multiple of the tile size
+
+        let mut serial: RgbaImage = ImageBuffer::new(width, height);
+        for (x, y, pixel) in serial.enumerate_pixels_mut() {
+            *pixel = pattern(x, y);
+        }
+
+        for threads in [1, 3, 0] {
+            let mut parallel: RgbaImage = ImageBuffer::new(width, height);
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| render_parallel(&mut parallel, pattern));
+            assert_eq!(serial.as_raw(), parallel.as_raw());
+        }
+    }
+
+    #[test]
+    fn test_accumulate_chunks_is_deterministic() {
+        // Every chunk adds a pattern of its own to a few bins
+        let fill = |chunk: u64, histogram: &mut [[u64; 4]]| {
+            for i in 0..5 {
+                let bin = ((chunk * 7 + i * 3) % histogram.len() as u64) as usize;
+                histogram[bin][0] += chunk;
+                histogram[bin][1] += 1;
+            }
+        };
+        let mut serial = vec![[0u64; 4]; 11];
+        (0..100).for_each(|chunk| fill(chunk, &mut serial));
+
+        for threads in [1, 4] {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            assert_eq!(pool.install(|| accumulate_chunks(100, 11, fill)), serial);
+        }
+        assert_eq!(accumulate_chunks(0, 3, fill), vec![[0; 4]; 3]);
+    }
+
+    #[test]
+    fn test_render_parallel_empty_image() {
+        let mut imgbuf: RgbaImage = ImageBuffer::new(0, 10);
+        render_parallel(&mut imgbuf, pattern);
+        assert_eq!(imgbuf.as_raw().len(), 0);
+    }
+}